@@ -0,0 +1,434 @@
+//! Configuration file of the CLI, with named profiles
+//!
+//! The file is `$XDG_CONFIG_HOME/deep-translator/config.toml`, or
+//! `~/.config/deep-translator/config.toml`. A profile has the engine, its credentials, the
+//! default languages and the proxies. The credentials at the top of a profile are the
+//! ones of its engine, the other engines have theirs in their own table, e.g.
+//! `[profiles.work.microsoft]`, so a key is never sent to another vendor. The credentials
+//! can also be in environment variables, e.g. `DEEPL_API_KEY`, they win over the ones of
+//! the profile, and the arguments of the command line win over both.
+//!
+//!```toml
+//! default_profile = "work"
+//!
+//! [profiles.work]
+//! engine = "deepl"
+//! api_key = "279a2e9d-83b3-c416-7e2d-f721593e42a0:fx"
+//! source = "en"
+//! target = "de"
+//! proxies = ["http://proxy.example.com:8080"]
+//!
+//! [profiles.work.microsoft]
+//! api_key = "8b6e0c7f2d6a4b1f9c3e5a7d2f4b6c8e"
+//! region = "westeurope"
+//!
+//! [profiles.local]
+//! engine = "libre"
+//! url = "http://localhost:5000"
+//!```
+//!
+//!```rust,no_run
+//! use deeptranslator::{config::Config, Translator};
+//!
+//! fn main() -> Result<(), deeptranslator::Error> {
+//!     let profile = Config::load()?.profile(Some("work"))?;
+//!     let translator = Translator::with_engine("en", "de", profile.engine("deepl")?);
+//!
+//!     println!("{}", translator.engine.name());
+//!     Ok(())
+//! }
+//!```
+
+use crate::{Engine, Error};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    path::Path,
+    path::PathBuf,
+    str::FromStr,
+};
+
+/// Path of the configuration file, instead of the one of the user
+pub const CONFIG_VAR: &str = "DEEP_TRANSLATOR_CONFIG";
+/// Key of detectlanguage.com, to detect the language of the texts
+pub const DETECTLANGUAGE_API_KEY: &str = "DETECTLANGUAGE_API_KEY";
+pub const DEEPL_API_KEY: &str = "DEEPL_API_KEY";
+pub const LIBRE_API_KEY: &str = "LIBRE_API_KEY";
+pub const LIBRE_URL: &str = "LIBRE_URL";
+pub const MICROSOFT_TRANSLATOR_KEY: &str = "MICROSOFT_TRANSLATOR_KEY";
+pub const MICROSOFT_TRANSLATOR_REGION: &str = "MICROSOFT_TRANSLATOR_REGION";
+pub const MYMEMORY_EMAIL: &str = "MYMEMORY_EMAIL";
+pub const PAPAGO_CLIENT_ID: &str = "PAPAGO_CLIENT_ID";
+pub const PAPAGO_SECRET_KEY: &str = "PAPAGO_SECRET_KEY";
+pub const QCRI_API_KEY: &str = "QCRI_API_KEY";
+pub const YANDEX_API_KEY: &str = "YANDEX_API_KEY";
+
+/// Names of the engines, as the CLI and the profiles write them
+pub const ENGINES: [&str; 10] = [
+    "deepl",
+    "google",
+    "libre",
+    "linguee",
+    "microsoft",
+    "mymemory",
+    "papago",
+    "pons",
+    "qcri",
+    "yandex",
+];
+
+/// The profiles of the configuration file
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Config {
+    /// Profile used when none is given, `default` when it is not set
+    #[serde(default)]
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// The engine of a profile with its credentials, languages and proxies
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Profile {
+    /// One of [`ENGINES`]
+    pub engine: Option<String>,
+    pub source: Option<String>,
+    pub target: Option<String>,
+    pub proxies: Vec<String>,
+    /// Credentials of `engine`, they are not used by the other engines
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    pub deepl: Credentials,
+    pub libre: Credentials,
+    pub microsoft: Credentials,
+    pub mymemory: Credentials,
+    pub papago: Credentials,
+    pub qcri: Credentials,
+    pub yandex: Credentials,
+    /// Key of detectlanguage.com, for the language detection
+    pub detection_api_key: Option<String>,
+    /// Keys that are not of the profile, e.g. misspelled ones, the configuration fails on them
+    #[serde(flatten, deserialize_with = "unknown_keys", skip_serializing)]
+    unknown: BTreeSet<String>,
+}
+
+/// Credentials and options of one engine. `api_key` is the key of Deepl, Libre, Microsoft,
+/// Qcri or Yandex
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Credentials {
+    pub api_key: Option<String>,
+    /// Region of the Microsoft resource
+    pub region: Option<String>,
+    /// Endpoint of LibreTranslate
+    pub url: Option<String>,
+    /// Whether Deepl uses the free api, by default keys ending in `:fx` do
+    pub free: Option<bool>,
+    /// Email of MyMemory, it raises the daily limit
+    pub email: Option<String>,
+    pub client_id: Option<String>,
+    pub secret_key: Option<String>,
+    /// Domain of Qcri
+    pub domain: Option<String>,
+}
+
+impl Config {
+    /// Path of the configuration file of the user, `None` when there is no home folder
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_VAR) {
+            return Some(path.into());
+        }
+
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|config| !config.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+            .map(|config| config.join("deep-translator").join("config.toml"))
+    }
+
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// The configuration of the user, it is empty when there is no file
+    pub fn load() -> Result<Self, Error> {
+        match Self::path().filter(|path| path.exists()) {
+            Some(path) => Self::open(path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// The profile with the name, or the default one. A missing default profile is empty
+    pub fn profile(&self, name: Option<&str>) -> Result<Profile, Error> {
+        let default = self.default_profile.as_deref().unwrap_or("default");
+
+        match (self.profiles.get(name.unwrap_or(default)), name) {
+            (Some(profile), _) => Ok(profile.clone()),
+            (None, None) => Ok(Profile::default()),
+            (None, Some(name)) => Err(Error::Config(format!("there is no profile `{name}`"))),
+        }
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let config: Self = toml::from_str(s).map_err(|err: toml::de::Error| Error::Parse {
+            line: err
+                .span()
+                .map_or(1, |span| s[..span.start].lines().count().max(1)),
+            message: err.message().to_string(),
+        })?;
+
+        for (name, profile) in &config.profiles {
+            if let Some(key) = profile.unknown.first() {
+                return Err(Error::Config(format!(
+                    "unknown key `{key}` in the profile `{name}`"
+                )));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The keys left by the fields of [`Profile`], they are unknown
+fn unknown_keys<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeSet<String>, D::Error> {
+    use serde::{de::IgnoredAny, Deserialize};
+
+    Ok(BTreeMap::<String, IgnoredAny>::deserialize(deserializer)?
+        .into_keys()
+        .collect())
+}
+
+/// Value of the environment variable, or else of the profile
+#[inline(always)]
+fn credential(var: &str, value: &Option<String>) -> Option<String> {
+    env::var(var)
+        .ok()
+        .filter(|value| !value.is_empty())
+        .or_else(|| value.clone())
+}
+
+impl Profile {
+    /// Key of detectlanguage.com, from the environment or the profile
+    #[inline(always)]
+    pub fn detection_api_key(&self) -> Option<String> {
+        credential(DETECTLANGUAGE_API_KEY, &self.detection_api_key)
+    }
+
+    /// Credentials of the engine with the name, those of its table. The credentials at the
+    /// top of the profile fill the missing ones only when it is the engine of the profile
+    pub fn credentials(&self, name: &str) -> Credentials {
+        let table = match name.to_lowercase().as_str() {
+            "deepl" => &self.deepl,
+            "libre" => &self.libre,
+            "microsoft" => &self.microsoft,
+            "mymemory" => &self.mymemory,
+            "papago" => &self.papago,
+            "qcri" => &self.qcri,
+            "yandex" => &self.yandex,
+            _ => return Credentials::default(),
+        };
+
+        match &self.engine {
+            Some(engine) if engine.eq_ignore_ascii_case(name) => {
+                table.clone().or(self.credentials.clone())
+            }
+            _ => table.clone(),
+        }
+    }
+
+    /// The engine with the name, one of [`ENGINES`], with its credentials, see
+    /// [`Profile::credentials`]
+    #[inline(always)]
+    pub fn engine(&self, name: &str) -> Result<Engine, Error> {
+        self.credentials(name).engine(name)
+    }
+}
+
+impl Credentials {
+    /// These credentials, with the ones of `fallback` where they are missing
+    pub fn or(self, fallback: Credentials) -> Self {
+        Self {
+            api_key: self.api_key.or(fallback.api_key),
+            region: self.region.or(fallback.region),
+            url: self.url.or(fallback.url),
+            free: self.free.or(fallback.free),
+            email: self.email.or(fallback.email),
+            client_id: self.client_id.or(fallback.client_id),
+            secret_key: self.secret_key.or(fallback.secret_key),
+            domain: self.domain.or(fallback.domain),
+        }
+    }
+
+    /// Whether the engine with the name has what it needs in the environment or in these
+    /// credentials, Libre needs its url to not use the public instance
+    pub fn is_configured(&self, name: &str) -> bool {
+        match name.to_lowercase().as_str() {
+            "deepl" => credential(DEEPL_API_KEY, &self.api_key).is_some(),
+            "libre" => credential(LIBRE_URL, &self.url).is_some(),
+            "microsoft" => credential(MICROSOFT_TRANSLATOR_KEY, &self.api_key).is_some(),
+            "papago" => {
+                credential(PAPAGO_CLIENT_ID, &self.client_id).is_some()
+                    && credential(PAPAGO_SECRET_KEY, &self.secret_key).is_some()
+            }
+            "qcri" => credential(QCRI_API_KEY, &self.api_key).is_some(),
+            "yandex" => credential(YANDEX_API_KEY, &self.api_key).is_some(),
+            name => name.parse::<Engine>().is_ok(),
+        }
+    }
+
+    /// The engine with the name, one of [`ENGINES`], with the credentials of the environment
+    /// and these ones. The engines that need a key fail without one
+    #[inline(always)]
+    pub fn engine(&self, name: &str) -> Result<Engine, Error> {
+        self.engine_with(name, &Credentials::default())
+    }
+
+    /// [`Credentials::engine`], where the `explicit` credentials, e.g. the arguments of the
+    /// command line, win over the environment, which wins over these ones
+    pub fn engine_with(&self, name: &str, explicit: &Credentials) -> Result<Engine, Error> {
+        let get = |var: &str, field: fn(&Credentials) -> &Option<String>| {
+            field(explicit)
+                .clone()
+                .or_else(|| credential(var, field(self)))
+        };
+        let required = |var: &str, field: fn(&Credentials) -> &Option<String>, id: &str| {
+            get(var, field).ok_or_else(|| {
+                Error::MissingCredentials(format!(
+                    "{name} needs `{id}` in the profile, in a `{name}` table when it \
+                    is not the engine of the profile, or the {var} environment variable"
+                ))
+            })
+        };
+
+        let mut engine = name.parse::<Engine>()?;
+        match &mut engine {
+            Engine::Deepl {
+                api_key,
+                use_free_api,
+                ..
+            } => {
+                *api_key = required(DEEPL_API_KEY, |c| &c.api_key, "api_key")?;
+                *use_free_api = explicit
+                    .free
+                    .or(self.free)
+                    .unwrap_or(api_key.ends_with(":fx"));
+            }
+            Engine::Libre(libre) => {
+                libre.api_key = get(LIBRE_API_KEY, |c| &c.api_key).unwrap_or_default();
+                libre.url = get(LIBRE_URL, |c| &c.url)
+                    .unwrap_or_else(|| "https://libretranslate.de/".into());
+                libre.alternatives = 1;
+            }
+            Engine::Microsoft(microsoft) => {
+                microsoft.api_key = required(MICROSOFT_TRANSLATOR_KEY, |c| &c.api_key, "api_key")?;
+                microsoft.region =
+                    get(MICROSOFT_TRANSLATOR_REGION, |c| &c.region).unwrap_or_default();
+            }
+            Engine::MyMemory { email, .. } => {
+                *email = get(MYMEMORY_EMAIL, |c| &c.email).unwrap_or_default();
+            }
+            Engine::Papago {
+                client_id,
+                secret_key,
+            } => {
+                *client_id = required(PAPAGO_CLIENT_ID, |c| &c.client_id, "client_id")?;
+                *secret_key = required(PAPAGO_SECRET_KEY, |c| &c.secret_key, "secret_key")?;
+            }
+            Engine::Qcri(qcri) => {
+                qcri.api_key = required(QCRI_API_KEY, |c| &c.api_key, "api_key")?;
+                qcri.domain = explicit
+                    .domain
+                    .clone()
+                    .or_else(|| self.domain.clone())
+                    .unwrap_or_default();
+            }
+            Engine::Yandex { api_key } => {
+                *api_key = required(YANDEX_API_KEY, |c| &c.api_key, "api_key")?;
+            }
+            Engine::Google | Engine::Linguee { .. } | Engine::Pons => {}
+        }
+
+        Ok(engine)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_line_wins_over_environment() {
+        env::set_var(LIBRE_URL, "http://environment/");
+        let profile = Credentials {
+            url: Some("http://profile/".into()),
+            ..Default::default()
+        };
+        let explicit = Credentials {
+            url: Some("http://command-line/".into()),
+            ..Default::default()
+        };
+
+        let url = |engine| match engine {
+            Ok(Engine::Libre(libre)) => libre.url,
+            engine => panic!("{engine:?}"),
+        };
+        assert_eq!(
+            url(profile.engine_with("libre", &explicit)),
+            "http://command-line/"
+        );
+        assert_eq!(url(profile.engine("libre")), "http://environment/");
+        env::remove_var(LIBRE_URL);
+        assert_eq!(url(profile.engine("libre")), "http://profile/");
+    }
+
+    #[test]
+    fn unknown_keys_fail() {
+        let error = |config: &str| match config.parse::<Config>() {
+            Err(Error::Config(message) | Error::Parse { message, .. }) => message,
+            config => panic!("{config:?}"),
+        };
+
+        assert!(
+            error("[profiles.work]\napikey = \"key\"").ends_with("`apikey` in the profile `work`")
+        );
+        assert!(error("[profiles.work.microsft]\napi_key = \"key\"")
+            .ends_with("`microsft` in the profile `work`"));
+        assert!(error("[profiles.work.microsoft]\nregoin = \"eu\"").contains("`regoin`"));
+    }
+
+    #[test]
+    fn top_credentials_are_only_for_the_engine_of_the_profile() {
+        let config: Config = r#"
+            [profiles.work]
+            engine = "Microsoft"
+            api_key = "top"
+            region = "westeurope"
+
+            [profiles.work.microsoft]
+            api_key = "table"
+
+            [profiles.work.deepl]
+            free = true
+        "#
+        .parse()
+        .unwrap();
+        let profile = config.profile(Some("work")).unwrap();
+
+        let microsoft = profile.credentials("microsoft");
+        assert_eq!(microsoft.api_key.as_deref(), Some("table"));
+        assert_eq!(microsoft.region.as_deref(), Some("westeurope"));
+
+        let deepl = profile.credentials("deepl");
+        assert_eq!(deepl.api_key, None);
+        assert_eq!(deepl.region, None);
+        assert_eq!(deepl.free, Some(true));
+        assert_eq!(profile.credentials("google"), Credentials::default());
+    }
+}
@@ -1,19 +1,14 @@
+use crate::{translator::response_status, Error};
 use serde_json::Value;
 use std::fmt;
 
+const API_URL: &str = "https://ws.detectlanguage.com/0.2/detect";
+
 pub enum QText {
     Str(String),
     ListStr(Vec<String>),
 }
 
-impl QText {
-    fn is_empty(&self) -> bool {
-        match self {
-            QText::Str(value) => value.is_empty(),
-            QText::ListStr(value) => value.is_empty(),
-        }
-    }
-}
 impl From<String> for QText {
     fn from(value: String) -> QText {
         QText::Str(value)
@@ -37,66 +32,69 @@ impl fmt::Display for QText {
 
 /// send a request and return the response body parsed as dictionary
 #[cfg_attr(target_arch = "wasm32", allow(unused_mut))]
-async fn get_request_body<T: Into<QText>>(text: T, api_key: &str) -> Value {
-    debug_assert!(
-        !api_key.is_empty(),
-        "you need to get an API_KEY for this to work. \n\
-        Get one for free here: https://detectlanguage.com/documentation"
-    );
+async fn get_request_body<T: Into<QText>>(text: T, api_key: &str) -> Result<Value, Error> {
+    if api_key.is_empty() {
+        return Err(Error::MissingCredentials(
+            "the language detection needs an api key of detectlanguage.com, \
+            get one for free here: https://detectlanguage.com/documentation \
+            and pass it as the `api_key` argument"
+                .into(),
+        ));
+    }
 
     let text = T::into(text);
 
-    debug_assert!(!text.is_empty(), "Please provide an input text");
-
     let mut client = reqwest::Client::builder();
     #[cfg(not(target_arch = "wasm32"))]
     {
         client = client.user_agent("Detect Language API Rust Client 1.4.0");
     }
 
-    let req: Value = client
-        .build()
-        .unwrap()
-        .post("https://ws.detectlanguage.com/0.2/detect")
-        .header("User-Agent", format!("Bearer {api_key}").as_str())
-        .json(&serde_json::json!({
-            "q": text.to_string()
-        }))
+    let q = match text {
+        QText::Str(value) => Value::String(value),
+        QText::ListStr(value) => value.into(),
+    };
+    let response = client
+        .build()?
+        .post(API_URL)
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({ "q": q }))
         .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap();
+        .await?;
 
-    req["data"].clone()
+    let mut req: Value = response_status(response)?.json().await?;
+    Ok(req["data"].take())
 }
 
 /// function responsible for detecting the language from a text
-pub async fn single(text: &str, api_key: &str, detailed: bool) -> Value {
-    let body = get_request_body(text.to_string(), api_key).await;
-    let detections = &body["detections"];
-
-    if detailed {
-        return detections[0].clone();
+pub async fn single(text: &str, api_key: &str, detailed: bool) -> Result<Value, Error> {
+    let body = get_request_body(text.to_string(), api_key).await?;
+    let detection = &body["detections"][0];
+
+    match (detection.is_object(), detailed) {
+        (false, _) => Err(Error::DetectionNotFound),
+        (true, true) => Ok(detection.clone()),
+        (true, false) => Ok(detection["language"].clone()),
     }
-
-    detections[0]["language"].clone()
 }
 
-/// function responsible for detecting the language from a text
-pub async fn batch(text_list: Vec<String>, api_key: &str, detailed: bool) -> Vec<Value> {
-    let body = get_request_body(text_list, api_key).await;
-    let detections = &body["detections"];
-
-    let Value::Array(detections) = detections else {
-        unreachable!()
+/// function responsible for detecting the language of each text of the list, the texts
+/// without a detection are `null`
+pub async fn batch(
+    text_list: Vec<String>,
+    api_key: &str,
+    detailed: bool,
+) -> Result<Vec<Value>, Error> {
+    let body = get_request_body(text_list, api_key).await?;
+
+    let Value::Array(detections) = &body["detections"] else {
+        return Err(Error::DetectionNotFound);
     };
     let res = detections.iter().map(|obj| obj[0].clone()).collect();
 
     if detailed {
-        res
+        Ok(res)
     } else {
-        res.iter().map(|obj| obj["language"].clone()).collect()
+        Ok(res.iter().map(|obj| obj["language"].clone()).collect())
     }
 }
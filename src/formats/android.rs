@@ -0,0 +1,506 @@
+//! Android `strings.xml` resources
+//!
+//! The `<string>`, the items of `<plurals>` and of `<string-array>` are translated, the
+//! resources with `translatable="false"` are left out of the translated file, as Android
+//! expects them only in the default `values/` folder. The format specifiers, `%1$s`, and
+//! the content of `<xliff:g>` are kept as they are, the apostrophes and quotes of the
+//! translation are escaped.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::android, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "pt-BR", Engine::Google);
+//!     let resources = android::Resources::open("res/values/strings.xml")?;
+//!
+//!     let (translation, report) = resources.translate(&translator).await;
+//!     println!("{} strings translated", report.translated);
+//!
+//!     // res/values-pt-rBR/strings.xml
+//!     let folder = android::values_dir("res", &translator.target);
+//!     std::fs::create_dir_all(&folder)?;
+//!     Ok(std::fs::write(folder.join("strings.xml"), translation)?)
+//! }
+//!```
+
+use super::{
+    xml::{attribute, closing_tag, escape, mask, next_tag, set_attribute, unescape, unmask},
+    Report,
+};
+use crate::{Error, PlaceholderSyntax, Translator};
+use futures::StreamExt;
+use std::{
+    fmt,
+    ops::Range,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// Elements whose content is kept as it is
+const NATIVE_CODE_ELEMENTS: [&str; 1] = ["xliff:g"];
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum EntryKind {
+    /// `<string>`
+    String,
+    /// An `<item>` of `<plurals>`, with its quantity
+    Plural(String),
+    /// An `<item>` of `<string-array>`, with its index
+    ArrayItem(usize),
+}
+
+/// A string of the resources, the value is the inner XML of the element
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Entry {
+    pub name: String,
+    pub kind: EntryKind,
+    pub value: String,
+    pub translatable: bool,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Span {
+    entry: Entry,
+    /// Content of the element
+    content: Range<usize>,
+    /// Lines of the resource, removed from the translation when it is not translatable
+    resource: Range<usize>,
+    /// Lines of the `<item>`, or of the `<string>`
+    element: Range<usize>,
+}
+
+/// A `strings.xml` file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Resources {
+    text: String,
+    spans: Vec<Span>,
+}
+
+/// Folder of the resources of a locale, `values-de`, `values-pt-rBR` or `values-b+zh+Hans`
+pub fn values_dir<P: AsRef<Path>>(res: P, locale: &str) -> PathBuf {
+    let parts = locale.split(['-', '_']).collect::<Vec<_>>();
+    let qualifier = match parts.as_slice() {
+        [language] => language.to_string(),
+        [language, region]
+            if region.len() == 2 && region.chars().all(|c| c.is_ascii_alphabetic()) =>
+        {
+            format!("{language}-r{}", region.to_uppercase())
+        }
+        _ => format!("b+{}", parts.join("+")),
+    };
+
+    res.as_ref().join(format!("values-{qualifier}"))
+}
+
+/// Quantities of the `<plurals>` of the language, e.g. `ru` or `pt-BR`, from the plural
+/// rules of CLDR. `None` for the languages that are not known
+pub fn plural_quantities(language: &str) -> Option<&'static [&'static str]> {
+    let language = language.to_lowercase();
+    let primary = language.split(['-', '_']).next().unwrap_or_default();
+
+    let quantities: &[&str] = match primary {
+        "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "km" | "lo" | "my" | "jv" => &["other"],
+        "en" | "de" | "nl" | "sv" | "da" | "no" | "nb" | "nn" | "fi" | "et" | "el" | "hu"
+        | "bg" | "tr" | "af" | "sq" | "az" | "ka" | "kk" | "ky" | "mn" | "ne" | "ta" | "te"
+        | "ml" | "kn" | "mr" | "gu" | "hi" | "bn" | "ur" | "sw" | "eo" | "fy" | "lb" | "is"
+        | "uz" | "eu" | "gl" | "fa" | "hy" => &["one", "other"],
+        "fr" | "es" | "it" | "pt" | "ca" => &["one", "many", "other"],
+        "he" => &["one", "two", "other"],
+        "sr" | "hr" | "bs" | "ro" => &["one", "few", "other"],
+        "lv" => &["zero", "one", "other"],
+        "ru" | "uk" | "be" | "pl" | "cs" | "sk" | "lt" => &["one", "few", "many", "other"],
+        "sl" => &["one", "two", "few", "other"],
+        "ga" => &["one", "two", "few", "many", "other"],
+        "ar" | "cy" => &["zero", "one", "two", "few", "many", "other"],
+        _ => return None,
+    };
+
+    Some(quantities)
+}
+
+/// The whole lines of the range, with their indentation and line break, when nothing
+/// else is on them
+fn whole_lines(s: &str, range: Range<usize>) -> Range<usize> {
+    let line_start = s[..range.start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = s[range.end..]
+        .find('\n')
+        .map_or(s.len(), |index| range.end + index + 1);
+
+    let alone = s[line_start..range.start].trim().is_empty();
+    match alone && s[range.end..line_end].trim().is_empty() {
+        true => line_start..line_end,
+        false => range,
+    }
+}
+
+/// Decode the escaping of Android, the whitespace of unquoted text is collapsed
+fn unescape_android(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut quoted = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => unescaped.push('\n'),
+                Some('t') => unescaped.push('\t'),
+                Some('u') => {
+                    let code = chars.by_ref().take(4).collect::<String>();
+                    match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                        Some(c) => unescaped.push(c),
+                        None => unescaped.push_str(&format!("\\u{code}")),
+                    }
+                }
+                Some(c) => unescaped.push(c),
+                None => {}
+            },
+            '"' => quoted = !quoted,
+            c if c.is_whitespace() && !quoted => {
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                unescaped.push(' ');
+            }
+            c => unescaped.push(c),
+        }
+    }
+
+    unescaped
+}
+
+/// Escape the text for Android, then for XML
+fn escape_android(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    if text.starts_with(['@', '?']) {
+        escaped.push('\\');
+    }
+
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\'' => escaped.push_str("\\'"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+
+    escape(&escaped)
+}
+
+impl Resources {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.spans.iter().map(|span| &span.entry)
+    }
+
+    /// Translate the strings, the result is the `strings.xml` of the target language of the
+    /// translator. The `<plurals>` have the quantities of the target language, see
+    /// [`plural_quantities`], the missing ones are a copy of `other`
+    pub async fn translate(&self, translator: &Translator) -> (String, Report) {
+        let mut placeholders = translator.placeholders.clone();
+        if !placeholders.contains(&PlaceholderSyntax::Printf) {
+            placeholders.push(PlaceholderSyntax::Printf);
+        }
+
+        // The items of the quantities the target language does not have are removed
+        let quantities = plural_quantities(&translator.target);
+        let needed = |span: &&Span| match (&span.entry.kind, quantities) {
+            (EntryKind::Plural(quantity), Some(quantities)) => {
+                quantities.contains(&quantity.as_str())
+            }
+            _ => true,
+        };
+
+        let pending = self
+            .spans
+            .iter()
+            .filter(|span| span.entry.translatable)
+            .filter(needed)
+            .map(|span| {
+                let content = &self.text[span.content.clone()];
+                let decode = |text: &str| unescape_android(&unescape(text));
+                (
+                    span,
+                    mask(content, &placeholders, &NATIVE_CODE_ELEMENTS, decode),
+                )
+            })
+            .filter(|(_, (masked, _))| masked.has_text())
+            .collect::<Vec<_>>();
+
+        let translations = futures::stream::iter(
+            pending
+                .iter()
+                .map(|(_, (masked, _))| translator.translate_masked_text(masked)),
+        )
+        .buffered(translator.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        let mut report = Report::default();
+        let mut edits = pending
+            .iter()
+            .zip(translations)
+            .filter_map(|((span, (_, tags)), translation)| {
+                let translation = report.record(&span.entry.value, translation)?;
+                let content = unmask(translation.trim(), tags, escape_android);
+                Some((span.content.clone(), content))
+            })
+            .collect::<Vec<_>>();
+
+        if let Some(quantities) = quantities {
+            let plurals = self
+                .spans
+                .iter()
+                .filter(|span| span.entry.translatable)
+                .filter(|span| matches!(span.entry.kind, EntryKind::Plural(_)));
+            let mut added = Vec::new();
+
+            for span in plurals {
+                let EntryKind::Plural(quantity) = &span.entry.kind else {
+                    continue;
+                };
+                if !needed(&span) {
+                    edits.push((span.element.clone(), String::new()));
+                }
+                if quantity != "other" {
+                    continue;
+                }
+
+                // The missing quantities go before `other`, with its translation
+                let present = self
+                    .spans
+                    .iter()
+                    .filter(|other| other.resource == span.resource)
+                    .filter_map(|other| match &other.entry.kind {
+                        EntryKind::Plural(quantity) => Some(quantity.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                let content = edits
+                    .iter()
+                    .find(|(range, _)| *range == span.content)
+                    .map_or(span.entry.value.as_str(), |(_, content)| content);
+                let item = &self.text[span.element.clone()];
+                let head = &item[..span.content.start - span.element.start];
+                let tail = &item[span.content.end - span.element.start..];
+
+                let missing = quantities
+                    .iter()
+                    .filter(|quantity| !present.contains(quantity))
+                    .map(|quantity| {
+                        let head = set_attribute(head, "quantity", quantity);
+                        format!("{head}{content}{tail}")
+                    })
+                    .collect::<String>();
+                if !missing.is_empty() {
+                    added.push((span.element.start..span.element.start, missing));
+                }
+            }
+            edits.extend(added);
+        }
+
+        // Every item of a resource that is not translatable shares the same lines
+        let mut removed = self
+            .spans
+            .iter()
+            .filter(|span| !span.entry.translatable)
+            .map(|span| (span.resource.clone(), String::new()))
+            .collect::<Vec<_>>();
+        removed.dedup();
+        edits.extend(removed);
+
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut translation = self.text.clone();
+        for (range, replacement) in edits {
+            translation.replace_range(range, &replacement);
+        }
+
+        (translation, report)
+    }
+}
+
+impl FromStr for Resources {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = |position: usize| s[..position].lines().count().max(1);
+        let unclosed = |tag: &super::xml::Tag| Error::Parse {
+            line: line(tag.range.start),
+            message: format!("unclosed <{}>", tag.name),
+        };
+
+        let mut spans = Vec::new();
+        let mut from = 0;
+
+        while let Some(tag) = next_tag(s, from) {
+            from = tag.range.end;
+            if tag.closing
+                || tag.empty
+                || !["string", "plurals", "string-array"].contains(&tag.name)
+            {
+                continue;
+            }
+
+            let close = closing_tag(s, &tag).ok_or_else(|| unclosed(&tag))?;
+            let name = attribute(tag.raw(s), "name")
+                .unwrap_or_default()
+                .to_string();
+            let translatable = attribute(tag.raw(s), "translatable") != Some("false");
+
+            let resource = whole_lines(s, tag.range.start..close.range.end);
+
+            let mut span = |kind, content: Range<usize>, element| {
+                spans.push(Span {
+                    entry: Entry {
+                        name: name.clone(),
+                        kind,
+                        value: s[content.clone()].to_string(),
+                        translatable,
+                    },
+                    content,
+                    resource: resource.clone(),
+                    element,
+                })
+            };
+
+            if tag.name == "string" {
+                span(
+                    EntryKind::String,
+                    tag.range.end..close.range.start,
+                    resource.clone(),
+                );
+            } else {
+                let mut item_from = tag.range.end;
+                let mut index = 0;
+
+                while let Some(item) = next_tag(&s[..close.range.start], item_from) {
+                    item_from = item.range.end;
+                    if item.closing || item.empty || item.name != "item" {
+                        continue;
+                    }
+
+                    let item_close = closing_tag(s, &item).ok_or_else(|| unclosed(&item))?;
+                    let kind = match tag.name {
+                        "plurals" => {
+                            let quantity = attribute(item.raw(s), "quantity").unwrap_or_default();
+                            EntryKind::Plural(quantity.to_string())
+                        }
+                        _ => EntryKind::ArrayItem(index),
+                    };
+
+                    span(
+                        kind,
+                        item.range.end..item_close.range.start,
+                        whole_lines(s, item.range.start..item_close.range.end),
+                    );
+                    item_from = item_close.range.end;
+                    index += 1;
+                }
+            }
+
+            from = close.range.end;
+        }
+
+        Ok(Self {
+            text: s.to_string(),
+            spans,
+        })
+    }
+}
+
+impl fmt::Display for Resources {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.text.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    const STRINGS: &str = "<resources>\n\
+        \x20   <string name=\"app\" translatable=\"false\">Fox</string>\n\
+        \x20   <string name=\"hello\">Hello %1$s, it\\'s <xliff:g id=\"n\">Fox</xliff:g></string>\n\
+        \x20   <plurals name=\"files\">\n\
+        \x20       <item quantity=\"one\">%d file</item>\n\
+        \x20       <item quantity=\"other\">%d files</item>\n\
+        \x20   </plurals>\n\
+        </resources>\n";
+
+    #[test]
+    fn entries() {
+        let resources = STRINGS.parse::<Resources>().unwrap();
+        let entries = resources
+            .entries()
+            .map(|entry| (entry.name.as_str(), &entry.kind, entry.translatable))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            entries,
+            [
+                ("app", &EntryKind::String, false),
+                ("hello", &EntryKind::String, true),
+                ("files", &EntryKind::Plural("one".into()), true),
+                ("files", &EntryKind::Plural("other".into()), true),
+            ]
+        );
+        assert_eq!(unescape_android("it\\'s  \"a  b\"\\n"), "it's a  b\n");
+        assert_eq!(escape_android("@it's <b>"), "\\@it\\'s &lt;b&gt;");
+        assert_eq!(
+            values_dir("res", "pt-BR"),
+            Path::new("res").join("values-pt-rBR")
+        );
+        assert_eq!(
+            values_dir("res", "zh-Hans"),
+            Path::new("res").join("values-b+zh+Hans")
+        );
+    }
+
+    #[tokio::test]
+    async fn quantities_of_the_target() {
+        let resources = STRINGS.parse::<Resources>().unwrap();
+        let mut translator = translator(&[]);
+        translator.target = "ru".into();
+        for (text, translation) in [
+            ("Hello %1$s, it's \u{e000}", "Привет %1$s, это \u{e000}"),
+            ("%d file", "%d файл"),
+            ("%d files", "%d файлов"),
+        ] {
+            translator.memory.insert("en", "ru", text, translation);
+        }
+
+        let (translation, report) = resources.translate(&translator).await;
+        assert_eq!(report.translated, 3);
+        assert_eq!(
+            translation,
+            "<resources>\n\
+            \x20   <string name=\"hello\">Привет %1$s, это <xliff:g id=\"n\">Fox</xliff:g></string>\n\
+            \x20   <plurals name=\"files\">\n\
+            \x20       <item quantity=\"one\">%d файл</item>\n\
+            \x20       <item quantity=\"few\">%d файлов</item>\n\
+            \x20       <item quantity=\"many\">%d файлов</item>\n\
+            \x20       <item quantity=\"other\">%d файлов</item>\n\
+            \x20   </plurals>\n\
+            </resources>\n"
+        );
+
+        translator.target = "ja".into();
+        translator
+            .memory
+            .insert("en", "ja", "%d files", "%d ファイル");
+        let (translation, report) = resources.translate(&translator).await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(report.failed.len(), 1);
+        assert!(translation.contains(
+            "<plurals name=\"files\">\n        <item quantity=\"other\">%d ファイル</item>\n    </plurals>"
+        ));
+    }
+}
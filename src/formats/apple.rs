@@ -0,0 +1,599 @@
+//! Apple `.strings` files and `.xcstrings` string catalogs
+//!
+//! The format specifiers, `%@` and `%1$lld`, are kept as they are. A `.strings` file is
+//! translated into the file of the target language, saved in its `.lproj` folder, while a
+//! string catalog holds every language, the translations are added to it in the
+//! `needs_review` state.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::apple, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!
+//!     let strings = apple::Strings::open("en.lproj/Localizable.strings")?;
+//!     let (translation, _) = strings.translate(&translator).await;
+//!     // de.lproj/Localizable.strings
+//!     let folder = apple::lproj_dir(".", &translator.target);
+//!     std::fs::create_dir_all(&folder)?;
+//!     std::fs::write(folder.join("Localizable.strings"), translation)?;
+//!
+//!     let mut catalog = apple::StringCatalog::open("Localizable.xcstrings")?;
+//!     let report = catalog.translate(&translator).await;
+//!     println!("{} strings translated", report.translated);
+//!     catalog.save("Localizable.xcstrings")
+//! }
+//!```
+
+use super::{json::pointer, Report};
+use crate::{Error, PlaceholderSyntax, Translator};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::{
+    fmt, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+/// State of the translations added to a string catalog
+pub const REVIEW_STATE: &str = "needs_review";
+
+/// Folder of the resources of a locale, `de.lproj` or `pt-BR.lproj`
+#[inline(always)]
+pub fn lproj_dir<P: AsRef<Path>>(dir: P, locale: &str) -> PathBuf {
+    dir.as_ref()
+        .join(format!("{}.lproj", locale.replace('_', "-")))
+}
+
+/// The translator with the format specifiers protected
+fn with_format_specifiers(translator: &Translator) -> Translator {
+    let mut translator = translator.clone();
+    if !translator.placeholders.contains(&PlaceholderSyntax::Printf) {
+        translator.placeholders.push(PlaceholderSyntax::Printf);
+    }
+
+    translator
+}
+
+/// Read a file in UTF-8 or, as the older `.strings` files, in UTF-16 with a byte order mark
+fn read_to_string<P: AsRef<Path>>(path: P) -> Result<String, Error> {
+    let bytes = std::fs::read(path)?;
+    let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+        let units = bytes
+            .chunks_exact(2)
+            .map(|pair| from_bytes([pair[0], pair[1]]))
+            .collect::<Vec<_>>();
+
+        String::from_utf16(&units).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    };
+
+    let text = match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes)?,
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes)?,
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8(bytes)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?,
+    };
+
+    Ok(text)
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Part {
+    Verbatim(String),
+    Value {
+        key: String,
+        /// The quoted value as it is written in the file
+        raw: String,
+        text: String,
+    },
+}
+
+/// A `Localizable.strings` file, `"key" = "value";` pairs with comments
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Strings {
+    parts: Vec<Part>,
+}
+
+/// End of the quoted string that starts at the beginning of `text`
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut chars = text.char_indices().skip(1);
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '"' => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some('u' | 'U') => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                    Some(c) => unescaped.push(c),
+                    None => unescaped.push_str(&format!("\\U{code}")),
+                }
+            }
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+fn quote(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+
+    format!("\"{escaped}\"")
+}
+
+impl Strings {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        read_to_string(path)?.parse()
+    }
+
+    /// The keys and values of the file
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Value { key, text, .. } => Some((key.as_str(), text.as_str())),
+            Part::Verbatim(_) => None,
+        })
+    }
+
+    /// Translate the values, the result is the file of the target language of the
+    /// translator
+    pub async fn translate(&self, translator: &Translator) -> (String, Report) {
+        let translator = with_format_specifiers(translator);
+        let texts = self.entries().map(|(_, text)| text).collect::<Vec<_>>();
+        let mut translations = translator.translate_strings(&texts).await.into_iter();
+        let mut report = Report::default();
+
+        let translation = self
+            .parts
+            .iter()
+            .map(|part| match part {
+                Part::Verbatim(text) => text.clone(),
+                Part::Value { raw, text, .. } => {
+                    let translation = translations.next().unwrap();
+                    report
+                        .record(text, translation)
+                        .map_or_else(|| raw.clone(), |translated| quote(&translated))
+                }
+            })
+            .collect();
+
+        (translation, report)
+    }
+}
+
+impl FromStr for Strings {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = |position: usize, message: &str| Error::Parse {
+            line: s[..position].lines().count().max(1),
+            message: message.into(),
+        };
+
+        let mut parts = Vec::new();
+        let mut verbatim = 0;
+        let mut position = 0;
+
+        // Skip the whitespace and comments from `position`
+        let skip = |mut position: usize| -> Result<usize, Error> {
+            loop {
+                let rest = &s[position..];
+                let trimmed = rest.trim_start();
+                position += rest.len() - trimmed.len();
+
+                if trimmed.starts_with("/*") {
+                    let end = trimmed
+                        .find("*/")
+                        .ok_or_else(|| error(position, "unclosed comment"))?;
+                    position += end + 2;
+                } else if trimmed.starts_with("//") {
+                    position += trimmed.find('\n').unwrap_or(trimmed.len());
+                } else {
+                    return Ok(position);
+                }
+            }
+        };
+
+        loop {
+            position = skip(position)?;
+            let rest = &s[position..];
+            if rest.is_empty() {
+                break;
+            }
+
+            let key_len = match rest.starts_with('"') {
+                true => closing_quote(rest).ok_or_else(|| error(position, "unclosed key"))? + 1,
+                false => rest
+                    .find(|c: char| c.is_whitespace() || c == '=' || c == ';')
+                    .unwrap_or(rest.len()),
+            };
+            let key = match rest.starts_with('"') {
+                true => unescape(&rest[1..key_len - 1]),
+                false => rest[..key_len].to_string(),
+            };
+
+            position = skip(position + key_len)?;
+            if !s[position..].starts_with('=') {
+                return Err(error(position, "expected `=` after the key"));
+            }
+
+            position = skip(position + 1)?;
+            let rest = &s[position..];
+            if !rest.starts_with('"') {
+                return Err(error(position, "expected a quoted value"));
+            }
+
+            let value_len =
+                closing_quote(rest).ok_or_else(|| error(position, "unclosed value"))? + 1;
+            parts.push(Part::Verbatim(s[verbatim..position].to_string()));
+            parts.push(Part::Value {
+                key,
+                raw: rest[..value_len].to_string(),
+                text: unescape(&rest[1..value_len - 1]),
+            });
+
+            verbatim = position + value_len;
+            position = skip(verbatim)?;
+            if !s[position..].starts_with(';') {
+                return Err(error(position, "expected `;` after the value"));
+            }
+
+            position += 1;
+        }
+
+        parts.push(Part::Verbatim(s[verbatim..].to_string()));
+        Ok(Self { parts })
+    }
+}
+
+impl fmt::Display for Strings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Verbatim(text) | Part::Value { raw: text, .. } => text.fmt(f)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A `.xcstrings` string catalog, with the translations of every language
+#[derive(Debug, Clone, PartialEq)]
+pub struct StringCatalog {
+    pub value: Value,
+}
+
+/// Writes JSON as Xcode does, `"key" : value`
+struct XcodeFormatter(serde_json::ser::PrettyFormatter<'static>);
+
+impl serde_json::ser::Formatter for XcodeFormatter {
+    fn begin_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_array_value(writer, first)
+    }
+
+    fn end_array_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_array_value(writer)
+    }
+
+    fn begin_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.begin_object(writer)
+    }
+
+    fn end_object<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object(writer)
+    }
+
+    fn begin_object_key<W: ?Sized + io::Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.0.begin_object_key(writer, first)
+    }
+
+    fn begin_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b" : ")
+    }
+
+    fn end_object_value<W: ?Sized + io::Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.0.end_object_value(writer)
+    }
+}
+
+/// Paths of the `stringUnit` values of a localization, including its variations
+fn string_units(value: &Value, path: &mut Vec<String>, units: &mut Vec<Vec<String>>) {
+    let Value::Object(object) = value else {
+        return;
+    };
+
+    for (key, value) in object {
+        path.push(key.clone());
+        match key.as_str() {
+            "stringUnit" if value["value"].is_string() => units.push(path.clone()),
+            _ => string_units(value, path, units),
+        }
+        path.pop();
+    }
+}
+
+impl StringCatalog {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, format!("{self}\n"))?)
+    }
+
+    /// Language of the sources of the catalog
+    #[inline(always)]
+    pub fn source_language(&self) -> &str {
+        self.value["sourceLanguage"].as_str().unwrap_or("en")
+    }
+
+    /// Add the translations to the target language of the translator, the plural and
+    /// device variations are translated too. The strings marked with
+    /// `"shouldTranslate": false` and the ones that already have a translation are skipped
+    pub async fn translate(&mut self, translator: &Translator) -> Report {
+        let translator = with_format_specifiers(translator);
+        let source_language = self.source_language().to_string();
+        let target = translator.target.clone();
+        let mut pending = Vec::new();
+
+        if let Some(Value::Object(strings)) = self.value.get("strings") {
+            for (key, string) in strings {
+                let localizations = &string["localizations"];
+                if string["shouldTranslate"] == Value::Bool(false)
+                    || !localizations[&target].is_null()
+                {
+                    continue;
+                }
+
+                // Without a localization, the key is the source string
+                let source = match &localizations[&source_language] {
+                    Value::Null => {
+                        serde_json::json!({"stringUnit": {"state": "translated", "value": key}})
+                    }
+                    source => source.clone(),
+                };
+
+                let mut units = Vec::new();
+                string_units(&source, &mut Vec::new(), &mut units);
+                pending.extend(
+                    units
+                        .into_iter()
+                        .map(|unit| (key.clone(), source.clone(), unit)),
+                );
+            }
+        }
+
+        let texts = pending
+            .iter()
+            .map(|(_, source, unit)| {
+                let pointer = format!("{}/value", pointer(unit));
+                source
+                    .pointer(&pointer)
+                    .and_then(Value::as_str)
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<_>>();
+
+        let translations = translator.translate_strings(&texts).await;
+        let mut report = Report::default();
+        let mut localizations = Vec::<(String, Value)>::new();
+
+        for (((key, source, unit), text), translation) in
+            pending.iter().zip(&texts).zip(translations)
+        {
+            // A string is added only when all its variations are translated
+            let index = match localizations.iter().position(|(k, _)| k == key) {
+                Some(index) => index,
+                None => {
+                    localizations.push((key.clone(), source.clone()));
+                    localizations.len() - 1
+                }
+            };
+
+            let translated = report.record(text, translation);
+            let localization = &mut localizations[index].1;
+            match (translated, localization.pointer_mut(&pointer(unit))) {
+                (Some(translated), Some(string_unit)) => {
+                    *string_unit = serde_json::json!({"state": REVIEW_STATE, "value": translated});
+                }
+                _ => *localization = Value::Null,
+            }
+        }
+
+        let Some(Value::Object(strings)) = self.value.get_mut("strings") else {
+            return report;
+        };
+
+        for (key, localization) in localizations {
+            let Some(Value::Object(string)) = strings.get_mut(&key) else {
+                continue;
+            };
+            if localization.is_null() {
+                continue;
+            }
+
+            let Value::Object(all) = string
+                .entry("localizations")
+                .or_insert_with(|| Value::Object(Map::new()))
+            else {
+                continue;
+            };
+
+            // Xcode keeps the languages sorted
+            all.insert(target.clone(), localization);
+            let mut sorted = std::mem::take(all).into_iter().collect::<Vec<_>>();
+            sorted.sort_by(|(a, _), (b, _)| a.cmp(b));
+            all.extend(sorted);
+        }
+
+        report
+    }
+}
+
+impl FromStr for StringCatalog {
+    type Err = Error;
+
+    #[inline(always)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self {
+            value: serde_json::from_str(s)?,
+        })
+    }
+}
+
+impl fmt::Display for StringCatalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = Vec::new();
+        let formatter = XcodeFormatter(serde_json::ser::PrettyFormatter::new());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+
+        self.value
+            .serialize(&mut serializer)
+            .map_err(|_| fmt::Error)?;
+
+        String::from_utf8_lossy(&buffer).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn strings_are_translated_in_place() {
+        let strings = "/* Greeting */\n\"greeting\" = \"Hello \\\"%@\\\"\";\n\
+            farewell = \"Goodbye\"; // short\n\"later\" = \"Later\";\n"
+            .parse::<Strings>()
+            .unwrap();
+        assert_eq!(
+            strings.entries().collect::<Vec<_>>(),
+            [
+                ("greeting", "Hello \"%@\""),
+                ("farewell", "Goodbye"),
+                ("later", "Later")
+            ]
+        );
+
+        let translator = translator(&[("Hello \"%@\"", "Hallo \"%@\""), ("Goodbye", "Tschüss")]);
+        let (translation, report) = strings.translate(&translator).await;
+        assert_eq!(report.translated, 2);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(
+            translation,
+            "/* Greeting */\n\"greeting\" = \"Hallo \\\"%@\\\"\";\n\
+            farewell = \"Tschüss\"; // short\n\"later\" = \"Later\";\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn localizations_are_added_to_the_catalog() {
+        let plural = |one: &str, other: &str, state: &str| {
+            json!({"variations": {"plural": {
+                "one": {"stringUnit": {"state": state, "value": one}},
+                "other": {"stringUnit": {"state": state, "value": other}},
+            }}})
+        };
+        let mut catalog = StringCatalog {
+            value: json!({
+                "sourceLanguage": "en",
+                "strings": {
+                    "Hello": {},
+                    "%lld files": {"localizations": {
+                        "en": plural("%lld file", "%lld files", "translated"),
+                    }},
+                    "Fox": {"shouldTranslate": false},
+                    "Goodbye": {"localizations": {
+                        "de": {"stringUnit": {"state": "translated", "value": "Tschau"}},
+                    }},
+                    "Later": {},
+                },
+                "version": "1.0",
+            }),
+        };
+        let translator = translator(&[
+            ("Hello", "Hallo"),
+            ("%lld file", "%lld Datei"),
+            ("%lld files", "%lld Dateien"),
+            ("Goodbye", "Tschüss"),
+        ]);
+
+        let report = catalog.translate(&translator).await;
+        assert_eq!(report.translated, 3);
+        assert_eq!(report.failed.len(), 1);
+
+        let strings = &catalog.value["strings"];
+        assert_eq!(
+            strings["Hello"]["localizations"]["de"],
+            json!({"stringUnit": {"state": REVIEW_STATE, "value": "Hallo"}})
+        );
+        assert_eq!(
+            strings["%lld files"]["localizations"]["de"],
+            plural("%lld Datei", "%lld Dateien", REVIEW_STATE)
+        );
+        assert_eq!(strings["Fox"], json!({"shouldTranslate": false}));
+        assert_eq!(
+            strings["Goodbye"]["localizations"]["de"]["stringUnit"]["value"],
+            "Tschau"
+        );
+        assert_eq!(strings["Later"], json!({}));
+
+        let keys = strings["%lld files"]["localizations"]
+            .as_object()
+            .unwrap()
+            .keys();
+        assert_eq!(keys.collect::<Vec<_>>(), ["de", "en"]);
+        assert!(catalog.to_string().contains("\"sourceLanguage\" : \"en\""));
+    }
+}
@@ -0,0 +1,417 @@
+//! CSV and TSV spreadsheets
+//!
+//! The selected columns are translated in place, or into new columns next to them, one for
+//! each target language. The file is read and written in chunks of rows, so large files are
+//! never loaded whole, and the cells of a chunk are sent with
+//! [`Translator::translate_batch`].
+//!
+//!```rust,no_run
+//! use deeptranslator::{
+//!     formats::csv::{translate_csv, Column, CsvOptions},
+//!     Engine, Translator,
+//! };
+//! use std::{fs::File, io::BufReader};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let options = CsvOptions {
+//!         columns: vec![Column::Name("title".into()), Column::Index(3)],
+//!         new_columns: true,
+//!         targets: vec!["de".into(), "fr".into()],
+//!         ..Default::default()
+//!     };
+//!
+//!     let reader = BufReader::new(File::open("products.csv")?);
+//!     let writer = File::create("products.translated.csv")?;
+//!     let report = translate_csv(reader, writer, &translator, &options).await?;
+//!     println!("{} cells translated", report.translated);
+//!
+//!     Ok(())
+//! }
+//!```
+
+use super::Report;
+use crate::{translator::html::first_text, Error, Translator};
+use std::{
+    collections::HashMap,
+    fmt,
+    io::{BufRead, Write},
+    str::FromStr,
+};
+
+/// A column, by the name in the header or by its index, starting at 0
+#[derive(Debug, Clone, Eq, PartialEq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum Column {
+    Name(String),
+    Index(usize),
+}
+
+impl FromStr for Column {
+    type Err = ();
+
+    /// A number is an index, anything else the name of a column
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse() {
+            Ok(index) => Ok(Column::Index(index)),
+            Err(_) if !s.is_empty() => Ok(Column::Name(s.to_string())),
+            Err(_) => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Column::Name(name) => name.fmt(f),
+            Column::Index(index) => index.fmt(f),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CsvOptions {
+    /// `,` for CSV and `\t` for TSV
+    pub delimiter: char,
+    /// Whether the first row has the names of the columns
+    pub has_header: bool,
+    pub columns: Vec<Column>,
+    /// Add the translations as new columns, named `<column>_<target>`, instead of replacing
+    /// the source text
+    pub new_columns: bool,
+    /// Languages of the new columns, the target of the translator when it is empty. The
+    /// columns replaced in place are always translated to the target of the translator
+    pub targets: Vec<String>,
+    /// Rows read, translated and written at a time
+    pub chunk_size: usize,
+}
+
+impl Default for CsvOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            has_header: true,
+            columns: Vec::new(),
+            new_columns: false,
+            targets: Vec::new(),
+            chunk_size: 100,
+        }
+    }
+}
+
+/// Read a record, the quoted fields may have line breaks. `line` is the number of lines
+/// read so far
+fn read_record<R: BufRead>(
+    reader: &mut R,
+    delimiter: char,
+    line: &mut usize,
+) -> Result<Option<Vec<String>>, Error> {
+    let mut buffer = String::new();
+    if reader.read_line(&mut buffer)? == 0 {
+        return Ok(None);
+    }
+
+    *line += 1;
+    let start = *line;
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+
+    loop {
+        let mut chars = buffer
+            .strip_prefix('\u{feff}')
+            .unwrap_or(&buffer)
+            .chars()
+            .peekable();
+
+        while let Some(c) = chars.next() {
+            let field = fields.last_mut().unwrap();
+            match c {
+                '"' if quoted && chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' if quoted => quoted = false,
+                '"' if field.is_empty() => quoted = true,
+                '\r' | '\n' if !quoted => {}
+                c if c == delimiter && !quoted => fields.push(String::new()),
+                c => field.push(c),
+            }
+        }
+
+        if !quoted {
+            return Ok(Some(fields));
+        }
+
+        // The quoted field goes on in the next line
+        buffer.clear();
+        if reader.read_line(&mut buffer)? == 0 {
+            return Err(Error::Parse {
+                line: start,
+                message: "unclosed quoted field".into(),
+            });
+        }
+        *line += 1;
+    }
+}
+
+/// Write a record, the fields are quoted only when they need it
+fn write_record<W: Write>(writer: &mut W, record: &[String], delimiter: char) -> Result<(), Error> {
+    let fields = record
+        .iter()
+        .map(|field| {
+            let needs_quotes = field.contains([delimiter, '"', '\r', '\n'])
+                || field.starts_with(char::is_whitespace)
+                || field.ends_with(char::is_whitespace);
+
+            match needs_quotes {
+                true => format!("\"{}\"", field.replace('"', "\"\"")),
+                false => field.clone(),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    Ok(writeln!(writer, "{}", fields.join(&delimiter.to_string()))?)
+}
+
+/// Indices of the selected columns
+fn resolve(columns: &[Column], header: Option<&[String]>) -> Result<Vec<usize>, Error> {
+    let mut indices = columns
+        .iter()
+        .map(|column| match column {
+            Column::Index(index) => Ok(*index),
+            Column::Name(name) => header
+                .and_then(|header| header.iter().position(|field| field.trim() == name))
+                .ok_or_else(|| Error::Parse {
+                    line: 1,
+                    message: format!("there is no column named `{name}`"),
+                }),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    indices.sort_unstable();
+    indices.dedup();
+    Ok(indices)
+}
+
+/// Translate the cells of the rows, each distinct text is sent once
+async fn translate_rows(
+    translator: &Translator,
+    rows: &[Vec<String>],
+    columns: &[usize],
+    report: &mut Report,
+) -> HashMap<String, String> {
+    let mut texts = rows
+        .iter()
+        .flat_map(|row| columns.iter().filter_map(|column| row.get(*column)))
+        .filter(|text| text.chars().any(char::is_alphabetic))
+        .cloned()
+        .collect::<Vec<_>>();
+    texts.sort_unstable();
+    texts.dedup();
+
+    let translations = translator.translate_batch(&texts).await;
+    texts
+        .into_iter()
+        .zip(translations)
+        .filter_map(|(text, translation)| {
+            let translation =
+                translation.and_then(|value| first_text(value).ok_or(Error::TranslationNotFound));
+            let translated = report.record(&text, translation)?;
+            Some((text, translated))
+        })
+        .collect()
+}
+
+/// Translate the selected columns of a CSV or TSV file, it is read and written by chunks
+/// of [`CsvOptions::chunk_size`] rows. The cells that fail are kept in the source language
+pub async fn translate_csv<R: BufRead, W: Write>(
+    mut reader: R,
+    mut writer: W,
+    translator: &Translator,
+    options: &CsvOptions,
+) -> Result<Report, Error> {
+    let delimiter = options.delimiter;
+    let mut line = 0;
+    let header = match options.has_header {
+        true => read_record(&mut reader, delimiter, &mut line)?,
+        false => None,
+    };
+    let columns = resolve(&options.columns, header.as_deref())?;
+
+    let targets = match options.targets.is_empty() || !options.new_columns {
+        true => vec![translator.target.clone()],
+        false => options.targets.clone(),
+    };
+    let translators = targets
+        .iter()
+        .map(|target| {
+            let mut translator = translator.clone();
+            translator.target = target.clone();
+            translator
+        })
+        .collect::<Vec<_>>();
+
+    // The new columns go after their source column
+    let expand = |row: &[String], translations: &[HashMap<String, String>]| {
+        let mut expanded = Vec::with_capacity(row.len() + columns.len() * targets.len());
+        for (index, field) in row.iter().enumerate() {
+            let translated = |translations: &HashMap<String, String>| {
+                translations
+                    .get(field)
+                    .cloned()
+                    .unwrap_or_else(|| field.clone())
+            };
+
+            match columns.contains(&index) {
+                true if options.new_columns => {
+                    expanded.push(field.clone());
+                    expanded.extend(translations.iter().map(translated));
+                }
+                true => expanded.push(translated(&translations[0])),
+                false => expanded.push(field.clone()),
+            }
+        }
+
+        expanded
+    };
+
+    if let Some(header) = &header {
+        let record = match options.new_columns {
+            true => {
+                let mut expanded = Vec::new();
+                for (index, name) in header.iter().enumerate() {
+                    expanded.push(name.clone());
+                    if columns.contains(&index) {
+                        expanded.extend(targets.iter().map(|target| format!("{name}_{target}")));
+                    }
+                }
+                expanded
+            }
+            false => header.clone(),
+        };
+        write_record(&mut writer, &record, delimiter)?;
+    }
+
+    let mut report = Report::default();
+    loop {
+        let mut rows = Vec::with_capacity(options.chunk_size);
+        while rows.len() < options.chunk_size.max(1) {
+            match read_record(&mut reader, delimiter, &mut line)? {
+                Some(row) => rows.push(row),
+                None => break,
+            }
+        }
+
+        if rows.is_empty() {
+            break;
+        }
+
+        let mut translations = Vec::with_capacity(translators.len());
+        for translator in &translators {
+            translations.push(translate_rows(translator, &rows, &columns, &mut report).await);
+        }
+
+        for row in &rows {
+            write_record(&mut writer, &expand(row, &translations), delimiter)?;
+        }
+    }
+
+    writer.flush()?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(text: &str) -> Result<Vec<Vec<String>>, Error> {
+        let mut reader = text.as_bytes();
+        let mut line = 0;
+        std::iter::from_fn(|| read_record(&mut reader, ',', &mut line).transpose()).collect()
+    }
+
+    async fn translate(text: &str, translator: &Translator, options: &CsvOptions) -> String {
+        let mut output = Vec::new();
+        translate_csv(text.as_bytes(), &mut output, translator, options)
+            .await
+            .unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn quoted_fields() {
+        assert_eq!(
+            records("\u{feff}id,text\r\n1,\"Hello, \"\"world\"\"\"\n2,\"two\nlines\"\n").unwrap(),
+            [
+                vec!["id", "text"],
+                vec!["1", "Hello, \"world\""],
+                vec!["2", "two\nlines"]
+            ]
+        );
+        assert!(matches!(
+            records("id,text\n1,\"never\nclosed\n"),
+            Err(Error::Parse { line: 2, .. })
+        ));
+
+        let mut output = Vec::new();
+        let record = ["a,b", "say \"hi\"", "two\nlines", " space", "plain"].map(String::from);
+        write_record(&mut output, &record, ',').unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\",\" space\",plain\n"
+        );
+    }
+
+    #[test]
+    fn columns() {
+        assert_eq!("2".parse(), Ok(Column::Index(2)));
+        assert_eq!("title".parse(), Ok(Column::Name("title".into())));
+        assert_eq!("".parse::<Column>(), Err(()));
+
+        let header = ["id", " title", "body"].map(String::from);
+        let columns = [
+            Column::Name("body".into()),
+            Column::Index(1),
+            Column::Index(2),
+        ];
+        assert_eq!(resolve(&columns, Some(&header)).unwrap(), [1, 2]);
+        assert!(resolve(&[Column::Name("price".into())], Some(&header)).is_err());
+        assert!(resolve(&[Column::Name("id".into())], None).is_err());
+    }
+
+    #[tokio::test]
+    async fn selected_columns_are_translated() {
+        let translator = crate::formats::translator(&[("Red apple", "Roter Apfel")]);
+        let options = CsvOptions {
+            columns: vec![Column::Name("name".into())],
+            chunk_size: 1,
+            ..Default::default()
+        };
+
+        let csv = "id,name,note\n1,Red apple,Red apple\n2,\"Green,\npear\",42\n";
+        assert_eq!(
+            translate(csv, &translator, &options).await,
+            "id,name,note\n1,Roter Apfel,Red apple\n2,\"Green,\npear\",42\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn new_columns_for_each_target() {
+        let mut translator = crate::formats::translator(&[("Apple", "Apfel")]);
+        translator.memory.insert("en", "fr", "Apple", "Pomme");
+        let options = CsvOptions {
+            delimiter: '\t',
+            has_header: false,
+            columns: vec![Column::Index(0)],
+            new_columns: true,
+            targets: vec!["de".into(), "fr".into()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            translate("Apple\t1\n", &translator, &options).await,
+            "Apple\tApfel\tPomme\t1\n"
+        );
+    }
+}
@@ -0,0 +1,415 @@
+//! Project Fluent `.ftl` resources
+//!
+//! The values and attributes of messages are translated, the lines of a pattern as one text.
+//! The identifiers, comments, placeables (`{ $name }`, `{ -term }`), line breaks and the
+//! structure of select expressions are kept, only the text of their variants is translated.
+//! The terms are mostly brand and product names, their values are kept unless
+//! [`Resource::translate_terms`] is set. Their attributes are always kept, the selectors use
+//! them.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::fluent::Resource, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let resource = Resource::open("locales/en/main.ftl")?;
+//!
+//!     let (translation, report) = resource.translate(&translator).await;
+//!     println!("{} strings translated", report.translated);
+//!
+//!     Ok(std::fs::write("locales/de/main.ftl", translation)?)
+//! }
+//!```
+
+use super::{translate_pieces, Piece, Report};
+use crate::{translator::mask::Masked, Error, PlaceholderSyntax, Translator};
+use std::{fmt, path::Path, str::FromStr};
+
+/// A parsed `.ftl` file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Resource {
+    pieces: Vec<Piece>,
+    /// Indices of the pieces that are the values of terms
+    terms: Vec<usize>,
+    /// Translate the values of the terms too
+    pub translate_terms: bool,
+}
+
+#[inline(always)]
+fn is_identifier(id: &str) -> bool {
+    let id = id.strip_prefix('-').unwrap_or(id);
+    id.starts_with(|c: char| c.is_ascii_alphabetic())
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// End of the placeable opened at `open`, when it is closed in the same line
+fn placeable_end(text: &str, open: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut chars = text[open..].char_indices();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                chars.next();
+            }
+            '"' => in_string = !in_string,
+            '{' if !in_string => depth += 1,
+            '}' if !in_string => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open + index + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Number of placeables opened and not closed in the text
+fn unclosed(text: &str) -> usize {
+    let opened = text.matches('{').count();
+    opened.saturating_sub(text.matches('}').count())
+}
+
+/// Add a line of text to the pattern, its placeables are protected
+fn inline(masked: &mut Masked, text: &str) {
+    let mut start = 0;
+
+    while let Some(open) = text[start..].find('{').map(|open| start + open) {
+        let end = placeable_end(text, open).unwrap_or(text.len());
+        masked.push_text(&text[start..open]);
+        masked.push_protected(&text[open..end]);
+        start = end;
+    }
+
+    masked.push_text(&text[start..]);
+}
+
+/// Pieces of a resource being parsed. The lines of a pattern are translated as one text, the
+/// line breaks between them are protected, until a select expression, a variant or an
+/// attribute ends it
+struct Pieces<'a> {
+    pieces: Vec<Piece>,
+    placeholders: &'a [PlaceholderSyntax],
+    /// Text of the pattern that can go on in the next line
+    pattern: Option<Masked>,
+    /// Whitespace after the pattern, with the line break
+    gap: String,
+    /// Whether the entry being parsed is a term
+    in_term: bool,
+    /// Indices of the patterns of terms
+    terms: Vec<usize>,
+}
+
+impl<'a> Pieces<'a> {
+    fn new(placeholders: &'a [PlaceholderSyntax]) -> Self {
+        Self {
+            pieces: Vec::new(),
+            placeholders,
+            pattern: None,
+            gap: String::new(),
+            in_term: false,
+            terms: Vec::new(),
+        }
+    }
+
+    /// Syntax kept as it is, it ends the pattern
+    fn verbatim(&mut self, text: &str) {
+        if let Some(pattern) = self.pattern.take() {
+            if self.in_term {
+                self.terms.push(self.pieces.len());
+            }
+            self.pieces.push(Piece::Text(pattern));
+        }
+
+        let text = std::mem::take(&mut self.gap) + text;
+        if !text.is_empty() {
+            self.pieces.push(Piece::Verbatim(text));
+        }
+    }
+
+    /// Split a line of a pattern, return the number of select expressions it opens. The
+    /// pattern goes on in the next line unless a select expression is opened
+    fn pattern_line(&mut self, text: &str) -> usize {
+        // A select expression, or any placeable, that continues in the next lines
+        let mut select = None;
+        let mut start = 0;
+        while let Some(open) = text[start..].find('{').map(|open| start + open) {
+            match placeable_end(text, open) {
+                Some(end) => start = end,
+                None => {
+                    select = Some(open);
+                    break;
+                }
+            }
+        }
+
+        let end = select.unwrap_or(text.len());
+        let content = text[..end].trim_end();
+        if !content.is_empty() {
+            let placeholders = self.placeholders;
+            inline(
+                self.pattern
+                    .get_or_insert_with(|| Masked::new(placeholders)),
+                content,
+            );
+        }
+
+        match select {
+            Some(open) => {
+                self.verbatim(&text[content.len()..]);
+                unclosed(&text[open..])
+            }
+            None => {
+                self.gap.push_str(&text[content.len()..]);
+                0
+            }
+        }
+    }
+
+    /// A line that goes on with the pattern of the previous line
+    fn continuation(&mut self, indentation: &str, text: &str) -> usize {
+        match &mut self.pattern {
+            Some(pattern) => pattern.push_space(&(std::mem::take(&mut self.gap) + indentation)),
+            None => self.verbatim(indentation),
+        }
+
+        self.pattern_line(text)
+    }
+
+    /// The line break is kept in the gap while the pattern can go on
+    fn end_line(&mut self, eol: &str) {
+        match self.pattern {
+            Some(_) => self.gap.push_str(eol),
+            None => self.verbatim(eol),
+        }
+    }
+}
+
+impl Resource {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Parse a resource, the placeholders of the translator are protected too
+    pub fn parse(text: &str, placeholders: &[PlaceholderSyntax]) -> Result<Self, Error> {
+        let mut pieces = Pieces::new(placeholders);
+        let mut in_entry = false;
+        let mut depth = 0;
+
+        for (index, line) in text.split_inclusive('\n').enumerate() {
+            let content = line.trim_end_matches(['\n', '\r']);
+            let eol = &line[content.len()..];
+            let indented = content.starts_with([' ', '\t']);
+            let trimmed = content.trim_start();
+            let indentation = &content[..content.len() - trimmed.len()];
+
+            if trimmed.is_empty() || (depth == 0 && content.starts_with('#')) {
+                in_entry &= trimmed.is_empty();
+                pieces.verbatim(line);
+                continue;
+            }
+
+            if !indented && depth == 0 {
+                let Some((id, value)) = content.split_once('=') else {
+                    return Err(Error::Parse {
+                        line: index + 1,
+                        message: format!("expected a message or a term, found `{content}`"),
+                    });
+                };
+
+                if !is_identifier(id.trim()) {
+                    return Err(Error::Parse {
+                        line: index + 1,
+                        message: format!("invalid identifier `{}`", id.trim()),
+                    });
+                }
+
+                let value_start = content.len() - value.trim_start().len();
+                pieces.verbatim(&content[..value_start]);
+                pieces.in_term = id.trim().starts_with('-');
+                depth += pieces.pattern_line(&content[value_start..]);
+                in_entry = true;
+            } else if !in_entry {
+                return Err(Error::Parse {
+                    line: index + 1,
+                    message: "indented line outside of a message".into(),
+                });
+            } else if depth == 0 && trimmed.starts_with('.') && trimmed.contains('=') {
+                // Attributes, `.title = Text`. The ones of terms are only used by the
+                // selectors, e.g. `.gender = masculine`, they are kept as they are
+                let (attribute, value) = trimmed.split_once('=').unwrap_or_default();
+                let value_start = indentation.len() + attribute.len() + 1;
+                let value_start = value_start + value.len() - value.trim_start().len();
+
+                match pieces.in_term {
+                    true => pieces.verbatim(content),
+                    false => {
+                        pieces.verbatim(&content[..value_start]);
+                        depth += pieces.pattern_line(&content[value_start..]);
+                    }
+                }
+            } else if depth > 0 && trimmed.starts_with('}') {
+                depth -= 1;
+                let rest = trimmed[1..].trim_start();
+                pieces.verbatim(&content[..content.len() - rest.len()]);
+                depth += pieces.pattern_line(rest);
+            } else if depth > 0 && (trimmed.starts_with('[') || trimmed.starts_with("*[")) {
+                // Variants of a select expression, `*[other] Text`
+                let key_end = trimmed.find(']').map_or(trimmed.len(), |end| end + 1);
+                let value = trimmed[key_end..].trim_start();
+                let value_start = content.len() - value.len();
+
+                pieces.verbatim(&content[..value_start]);
+                depth += pieces.pattern_line(value);
+            } else {
+                depth += pieces.continuation(indentation, trimmed);
+            }
+
+            pieces.end_line(eol);
+        }
+
+        if depth > 0 {
+            return Err(Error::Parse {
+                line: text.lines().count(),
+                message: "unclosed placeable".into(),
+            });
+        }
+
+        pieces.verbatim("");
+        Ok(Self {
+            pieces: pieces.pieces,
+            terms: pieces.terms,
+            translate_terms: false,
+        })
+    }
+
+    /// Translate the messages, the result is the resource for the target
+    /// language of the translator
+    pub async fn translate(&self, translator: &Translator) -> (String, Report) {
+        let mut report = Report::default();
+        let Self {
+            mut pieces, terms, ..
+        } = match translator.placeholders.is_empty() {
+            true => self.clone(),
+            false => Self::parse(&self.to_string(), &translator.placeholders)
+                .unwrap_or_else(|_| self.clone()),
+        };
+
+        if !self.translate_terms {
+            for index in terms {
+                if let Piece::Text(masked) = &pieces[index] {
+                    pieces[index] = Piece::Verbatim(masked.original());
+                }
+            }
+        }
+
+        let translation = translate_pieces(translator, &pieces, &mut report).await;
+        (translation, report)
+    }
+}
+
+impl FromStr for Resource {
+    type Err = Error;
+
+    #[inline(always)]
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s, &[])
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for piece in &self.pieces {
+            match piece {
+                Piece::Verbatim(text) => text.fmt(f)?,
+                Piece::Text(masked) => masked.original().fmt(f)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    fn texts(resource: &Resource) -> Vec<&Masked> {
+        let texts = resource.pieces.iter().filter_map(|piece| match piece {
+            Piece::Text(masked) => Some(masked),
+            Piece::Verbatim(_) => None,
+        });
+        texts.collect()
+    }
+
+    #[test]
+    fn multiline_pattern_is_one_text() {
+        let ftl = "welcome =\n    Welcome to the app,\n    { $name }.\n    .title = Hello\n";
+        let resource = ftl.parse::<Resource>().unwrap();
+
+        let texts = texts(&resource);
+        assert_eq!(texts.len(), 2);
+        assert_eq!(texts[0].text, "Welcome to the app, __PH0__ __PH1__.");
+        assert_eq!(texts[0].original(), "Welcome to the app,\n    { $name }.");
+        assert_eq!(
+            texts[0]
+                .unmask("Willkommen in der App, __PH0__ __PH1__.")
+                .unwrap()
+                .0,
+            "Willkommen in der App,\n    { $name }."
+        );
+        assert_eq!(resource.to_string(), ftl);
+    }
+
+    #[tokio::test]
+    async fn terms_are_kept_unless_asked() {
+        let ftl = "-brand = Fox\n    .gender = masculine\nabout = About { -brand }\n";
+        let mut resource = ftl.parse::<Resource>().unwrap();
+        let translator = translator(&[("Fox", "Fuchs"), ("About { -brand }", "Über { -brand }")]);
+
+        let (translation, report) = resource.translate(&translator).await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(
+            translation,
+            "-brand = Fox\n    .gender = masculine\nabout = Über { -brand }\n"
+        );
+
+        // The attributes of the terms are kept even then
+        resource.translate_terms = true;
+        let (translation, report) = resource.translate(&translator).await;
+        assert_eq!(report.translated, 2);
+        assert_eq!(
+            translation,
+            "-brand = Fuchs\n    .gender = masculine\nabout = Über { -brand }\n"
+        );
+
+        // The text before a select expression of the next message is not a term
+        let ftl = "-brand = Fox\nemails = Hi { $count ->\n   *[other] you\n}\n";
+        assert_eq!(ftl.parse::<Resource>().unwrap().terms, [1]);
+    }
+
+    #[tokio::test]
+    async fn select_expressions() {
+        let ftl = "emails = { $count ->\n    [one] One email\n   *[other] { $count } emails\n}\n";
+        let resource = ftl.parse::<Resource>().unwrap();
+        let translator = translator(&[
+            ("One email", "Eine E-Mail"),
+            ("{ $count } emails", "{ $count } E-Mails"),
+        ]);
+
+        let (translation, _) = resource.translate(&translator).await;
+        assert_eq!(
+            translation,
+            "emails = { $count ->\n    [one] Eine E-Mail\n   *[other] { $count } E-Mails\n}\n"
+        );
+    }
+}
@@ -0,0 +1,290 @@
+//! Nested JSON locale files, as used by i18next and vue-i18n
+//!
+//! Every string of the document is translated, the keys, numbers, booleans and `null` are
+//! kept. The strings can be selected with key paths, `nav.*.title`, where `*` matches one
+//! key and `**` any number of them. The elements of arrays are matched by their index.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::json::Resource, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "en", Engine::Google);
+//!     let mut resource = Resource::open("locales/en.json")?;
+//!     resource.exclude.push("meta.**".into());
+//!
+//!     for (target, (translation, report)) in resource.translate_to_many(&translator, &["de", "fr"]).await {
+//!         println!("{target}: {} strings translated", report.translated);
+//!         translation.save(format!("locales/{target}.json"))?;
+//!     }
+//!
+//!     Ok(())
+//! }
+//!```
+
+use super::Report;
+use crate::{Error, Translator};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt, path::Path, str::FromStr};
+
+/// A JSON document and the strings of it that are translated
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resource {
+    pub value: Value,
+    /// Key paths of the strings to translate, all of them when it is empty
+    pub include: Vec<String>,
+    /// Key paths of the strings that are kept as they are, even if they are included
+    pub exclude: Vec<String>,
+    /// Indentation of the document, detected from the file
+    indent: String,
+}
+
+/// Whether the key path pattern, split by `.`, matches the path of a string
+fn matches(pattern: &[&str], path: &[String]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            matches(&pattern[1..], path) || (!path.is_empty() && matches(pattern, &path[1..]))
+        }
+        (Some(key), Some(segment)) if *key == "*" || key == segment => {
+            matches(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+/// JSON pointer of a path, `/nav/home`
+pub(crate) fn pointer(path: &[String]) -> String {
+    path.iter()
+        .map(|segment| format!("/{}", segment.replace('~', "~0").replace('/', "~1")))
+        .collect()
+}
+
+impl Resource {
+    #[inline(always)]
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            indent: "  ".into(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, format!("{self}\n"))?)
+    }
+
+    /// Whether the string at the path is translated
+    pub fn is_selected(&self, path: &[String]) -> bool {
+        let matches_any = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| matches(&pattern.split('.').collect::<Vec<_>>(), path))
+        };
+
+        (self.include.is_empty() || matches_any(&self.include)) && !matches_any(&self.exclude)
+    }
+
+    /// Paths of the selected strings, in the order of the document
+    fn strings(&self, value: &Value, path: &mut Vec<String>, strings: &mut Vec<Vec<String>>) {
+        match value {
+            Value::String(text) if !text.trim().is_empty() && self.is_selected(path) => {
+                strings.push(path.clone())
+            }
+            Value::Array(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    path.push(index.to_string());
+                    self.strings(element, path, strings);
+                    path.pop();
+                }
+            }
+            Value::Object(object) => {
+                for (key, value) in object {
+                    path.push(key.clone());
+                    self.strings(value, path, strings);
+                    path.pop();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// JSON pointers of the selected strings, with their text
+    pub(crate) fn selected(&self) -> Vec<(String, &str)> {
+        let mut paths = Vec::new();
+        self.strings(&self.value, &mut Vec::new(), &mut paths);
+
+        paths
+            .iter()
+            .map(|path| pointer(path))
+            .filter_map(|pointer| {
+                let text = self.value.pointer(&pointer)?.as_str()?;
+                Some((pointer, text))
+            })
+            .collect()
+    }
+
+    /// Translate the selected strings to the target language of the translator
+    pub async fn translate(&self, translator: &Translator) -> (Self, Report) {
+        let (pointers, texts): (Vec<_>, Vec<_>) = self.selected().into_iter().unzip();
+
+        let translations = translator.translate_strings(&texts).await;
+        let mut translation = self.clone();
+        let mut report = Report::default();
+
+        for ((pointer, text), result) in pointers.iter().zip(texts).zip(translations) {
+            if let Some(translated) = report.record(text, result) {
+                if let Some(value) = translation.value.pointer_mut(pointer) {
+                    *value = Value::String(translated);
+                }
+            }
+        }
+
+        (translation, report)
+    }
+
+    /// Translate the document to each of the target languages, one after the other
+    pub async fn translate_to_many<S: AsRef<str>>(
+        &self,
+        translator: &Translator,
+        targets: &[S],
+    ) -> BTreeMap<String, (Self, Report)> {
+        let mut translations = BTreeMap::new();
+
+        for target in targets {
+            let mut translator = translator.clone();
+            translator.target = target.as_ref().to_string();
+            translations.insert(translator.target.clone(), self.translate(&translator).await);
+        }
+
+        translations
+    }
+}
+
+impl FromStr for Resource {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut resource = Self::new(serde_json::from_str(s)?);
+
+        // The first indented line gives the indentation of the file
+        let indent = s.lines().skip(1).find_map(|line| {
+            let content = line.trim_start();
+            (content.len() < line.len()).then(|| &line[..line.len() - content.len()])
+        });
+
+        if let Some(indent) = indent {
+            resource.indent = indent.to_string();
+        }
+
+        Ok(resource)
+    }
+}
+
+impl fmt::Display for Resource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buffer = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+
+        self.value
+            .serialize(&mut serializer)
+            .map_err(|_| fmt::Error)?;
+
+        String::from_utf8_lossy(&buffer).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    fn path(path: &str) -> Vec<String> {
+        path.split('.').map(String::from).collect()
+    }
+
+    #[test]
+    fn key_paths() {
+        let pattern = |pattern: &'static str| pattern.split('.').collect::<Vec<_>>();
+
+        assert!(matches(&pattern("nav.home"), &path("nav.home")));
+        assert!(!matches(&pattern("nav.home"), &path("nav.home.title")));
+        assert!(!matches(&pattern("nav"), &path("nav.home")));
+        assert!(matches(&pattern("nav.*.title"), &path("nav.home.title")));
+        assert!(!matches(
+            &pattern("nav.*.title"),
+            &path("nav.home.menu.title")
+        ));
+        assert!(matches(&pattern("nav.**"), &path("nav.home.menu.title")));
+        assert!(matches(&pattern("nav.**"), &path("nav")));
+        assert!(matches(&pattern("**.title"), &path("title")));
+        assert!(matches(&pattern("**.title"), &path("nav.home.title")));
+        assert!(matches(&pattern("steps.1"), &path("steps.1")));
+        assert!(!matches(&pattern("steps.1"), &path("steps.0")));
+        assert!(matches(&pattern("steps.*.text"), &path("steps.0.text")));
+    }
+
+    #[test]
+    fn included_and_excluded_strings() {
+        let mut resource: Resource = r#"{
+            "nav": {"home": "Home", "about": "About"},
+            "steps": [{"text": "First"}, {"text": "Second"}],
+            "meta": {"title": "Site", "count": 2, "empty": " "}
+        }"#
+        .parse()
+        .unwrap();
+
+        let pointers = |resource: &Resource| {
+            resource
+                .selected()
+                .into_iter()
+                .map(|(pointer, _)| pointer)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            pointers(&resource),
+            [
+                "/nav/home",
+                "/nav/about",
+                "/steps/0/text",
+                "/steps/1/text",
+                "/meta/title"
+            ]
+        );
+
+        resource.include = vec!["nav.*".into(), "steps.1.text".into()];
+        resource.exclude = vec!["nav.about".into()];
+        assert_eq!(pointers(&resource), ["/nav/home", "/steps/1/text"]);
+        assert_eq!(pointer(&path("a/b.c~d")), "/a~1b/c~0d");
+    }
+
+    #[tokio::test]
+    async fn key_order_and_indentation_are_kept() {
+        let document =
+            "{\n    \"zebra\": \"Zebra\",\n    \"apple\": [\n        \"Apple\",\n        \
+            3\n    ],\n    \"mango\": null\n}";
+        let resource: Resource = document.parse().unwrap();
+        assert_eq!(resource.to_string(), document);
+
+        let translator = translator(&[("Zebra", "Zebra (de)"), ("Apple", "Apfel")]);
+        let (translation, report) = resource.translate(&translator).await;
+
+        assert_eq!(report.translated, 2);
+        assert_eq!(
+            translation.to_string(),
+            document
+                .replace("\"Zebra\",", "\"Zebra (de)\",")
+                .replace("\"Apple\"", "\"Apfel\"")
+        );
+    }
+}
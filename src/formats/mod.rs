@@ -0,0 +1,97 @@
+//! Translation of localization and document files
+
+pub mod android;
+pub mod apple;
+pub mod csv;
+pub mod fluent;
+pub mod json;
+pub mod po;
+pub mod subtitles;
+pub mod sync;
+pub mod tmx;
+pub mod xliff;
+pub(crate) mod xml;
+pub mod yaml;
+
+use crate::{translator::mask::Masked, Error, Translator};
+use futures::StreamExt;
+
+/// Outcome of translating a file, the strings that failed are left untranslated
+#[derive(Debug, Default)]
+pub struct Report {
+    /// Number of strings translated
+    pub translated: usize,
+    /// Strings that could not be translated, with the error of the translator
+    pub failed: Vec<(String, Error)>,
+}
+
+impl Report {
+    /// Take the translation or record the failure
+    pub(crate) fn record(&mut self, source: &str, result: Result<String, Error>) -> Option<String> {
+        match result {
+            Ok(translation) => {
+                self.translated += 1;
+                Some(translation)
+            }
+            Err(err) => {
+                self.failed.push((source.to_string(), err));
+                None
+            }
+        }
+    }
+}
+
+/// A part of a file kept in its original syntax, only the text is sent to the engine
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub(crate) enum Piece {
+    Verbatim(String),
+    Text(Masked),
+}
+
+/// Translate the text pieces and join the file back, the text that fails is kept as it is
+pub(crate) async fn translate_pieces(
+    translator: &Translator,
+    pieces: &[Piece],
+    report: &mut Report,
+) -> String {
+    let translations = futures::stream::iter(pieces.iter().map(|piece| async move {
+        match piece {
+            Piece::Text(masked) if masked.has_text() => {
+                Some(translator.translate_masked_text(masked).await)
+            }
+            _ => None,
+        }
+    }))
+    .buffered(translator.max_concurrency.max(1))
+    .collect::<Vec<_>>()
+    .await;
+
+    pieces
+        .iter()
+        .zip(translations)
+        .map(|(piece, translation)| match (piece, translation) {
+            (Piece::Verbatim(text), _) => text.clone(),
+            (Piece::Text(masked), None) => masked.original(),
+            (Piece::Text(masked), Some(translation)) => {
+                let original = masked.original();
+                report.record(&original, translation).unwrap_or(original)
+            }
+        })
+        .collect()
+}
+
+/// A translator that answers from its memory, the other texts fail as its engine can not
+/// be reached
+#[cfg(test)]
+pub(crate) fn translator(translations: &[(&str, &str)]) -> Translator {
+    let engine = crate::Engine::Libre(crate::Libre {
+        url: "http://127.0.0.1:9/".into(),
+        ..Default::default()
+    });
+    let mut translator = Translator::with_engine("en", "de", engine);
+    for (text, translation) in translations {
+        translator.memory.insert("en", "de", text, translation);
+    }
+
+    translator
+}
@@ -0,0 +1,681 @@
+//! Gettext `.po` and `.pot` files
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::po::Catalog, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let mut catalog = Catalog::open("messages.pot")?;
+//!
+//!     let report = catalog.translate(&translator).await;
+//!     println!("{} strings translated", report.translated);
+//!
+//!     catalog.save("de.po")
+//! }
+//!```
+
+use super::Report;
+use crate::{translator::mask::Masked, Error, PlaceholderSyntax, Translator};
+use futures::StreamExt;
+use std::{borrow::Cow, fmt, path::Path, str::FromStr};
+
+/// A message of the catalog, with its comments
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Entry {
+    /// `# comment`
+    pub translator_comments: Vec<String>,
+    /// `#. comment`
+    pub extracted_comments: Vec<String>,
+    /// `#: src/main.rs:42`
+    pub references: Vec<String>,
+    /// `#, fuzzy, c-format`
+    pub flags: Vec<String>,
+    /// `#| msgid "previous"`, kept as they are
+    pub previous: Vec<String>,
+    pub msgctxt: Option<String>,
+    pub msgid: String,
+    pub msgid_plural: Option<String>,
+    /// One string, or one per plural form when there is a `msgid_plural`
+    pub msgstr: Vec<String>,
+    /// Entries commented out with `#~`
+    pub obsolete: bool,
+}
+
+impl Entry {
+    /// The entry with the metadata of the catalog
+    #[inline(always)]
+    pub fn is_header(&self) -> bool {
+        self.msgid.is_empty() && self.msgctxt.is_none()
+    }
+
+    #[inline(always)]
+    pub fn is_translated(&self) -> bool {
+        !self.msgstr.is_empty() && self.msgstr.iter().all(|msgstr| !msgstr.is_empty())
+    }
+
+    #[inline(always)]
+    pub fn is_fuzzy(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "fuzzy")
+    }
+
+    /// The placeholders of the translator and the ones of the format flags of the entry,
+    /// e.g. `c-format` and `python-format`
+    fn placeholders(&self, translator: &Translator) -> Vec<PlaceholderSyntax> {
+        let mut placeholders = translator.placeholders.clone();
+
+        for flag in &self.flags {
+            let syntax = match flag.as_str() {
+                "c-format" | "objc-format" => PlaceholderSyntax::Printf,
+                "python-format" | "python-brace-format" => PlaceholderSyntax::Python,
+                "ruby-format" => PlaceholderSyntax::Ruby,
+                _ => continue,
+            };
+            if !placeholders.contains(&syntax) {
+                placeholders.push(syntax);
+            }
+        }
+
+        placeholders
+    }
+}
+
+/// Translate a message, its leading and trailing whitespace, e.g. a `\n`, is kept
+async fn translate_message(
+    translator: &Translator,
+    placeholders: &[PlaceholderSyntax],
+    text: &str,
+) -> Result<String, Error> {
+    let trimmed = text.trim();
+    let mut masked = Masked::new(placeholders);
+    masked.push_text(trimmed);
+
+    let translation = match masked.has_text() {
+        true => translator.translate_masked_text(&masked).await?,
+        false => trimmed.to_string(),
+    };
+
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    Ok(format!("{leading}{}{trailing}", translation.trim()))
+}
+
+/// `Plural-Forms` header of the language, e.g. `ru` or `pt-BR`, from the rules of gettext
+/// and CLDR. `None` for the languages that are not known
+pub fn plural_forms(language: &str) -> Option<&'static str> {
+    let language = language.to_lowercase().replace('_', "-");
+    let primary = language.split('-').next().unwrap_or_default();
+
+    let forms = match (language.as_str(), primary) {
+        ("pt-br", _) | (_, "fr" | "oc" | "fa" | "hy" | "ln") => "nplurals=2; plural=(n > 1);",
+        (_, "ja" | "zh" | "ko" | "vi" | "th" | "id" | "ms" | "km" | "lo" | "my" | "jv") => {
+            "nplurals=1; plural=0;"
+        }
+        (
+            _,
+            "en" | "de" | "nl" | "sv" | "da" | "no" | "nb" | "nn" | "fi" | "et" | "es" | "it"
+            | "pt" | "el" | "hu" | "bg" | "he" | "ca" | "eu" | "gl" | "tr" | "af" | "sq" | "az"
+            | "ka" | "kk" | "ky" | "mn" | "ne" | "ta" | "te" | "ml" | "kn" | "mr" | "gu" | "hi"
+            | "bn" | "ur" | "sw" | "eo" | "fy" | "lb" | "is" | "uz",
+        ) => "nplurals=2; plural=(n != 1);",
+        (_, "ru" | "uk" | "be" | "sr" | "hr" | "bs") => {
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && n%10<=4 && \
+            (n%100<10 || n%100>=20) ? 1 : 2);"
+        }
+        (_, "pl") => {
+            "nplurals=3; plural=(n==1 ? 0 : n%10>=2 && n%10<=4 && (n%100<10 || n%100>=20) \
+            ? 1 : 2);"
+        }
+        (_, "cs" | "sk") => "nplurals=3; plural=(n==1 ? 0 : n>=2 && n<=4 ? 1 : 2);",
+        (_, "lt") => {
+            "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n%10>=2 && (n%100<10 || \
+            n%100>=20) ? 1 : 2);"
+        }
+        (_, "lv") => "nplurals=3; plural=(n%10==1 && n%100!=11 ? 0 : n != 0 ? 1 : 2);",
+        (_, "ro") => "nplurals=3; plural=(n==1 ? 0 : (n==0 || (n%100 > 0 && n%100 < 20)) ? 1 : 2);",
+        (_, "sl") => {
+            "nplurals=4; plural=(n%100==1 ? 0 : n%100==2 ? 1 : n%100==3 || n%100==4 ? 2 : 3);"
+        }
+        (_, "cy") => "nplurals=4; plural=(n==1 ? 0 : n==2 ? 1 : n != 8 && n != 11 ? 2 : 3);",
+        (_, "ga") => {
+            "nplurals=5; plural=(n==1 ? 0 : n==2 ? 1 : n>2 && n<7 ? 2 : n>6 && n<11 ? 3 : 4);"
+        }
+        (_, "ar") => {
+            "nplurals=6; plural=(n==0 ? 0 : n==1 ? 1 : n==2 ? 2 : n%100>=3 && n%100<=10 ? 3 \
+            : n%100>=11 ? 4 : 5);"
+        }
+        _ => return None,
+    };
+
+    Some(forms)
+}
+
+/// The entries of a `.po` or `.pot` file
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub struct Catalog {
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Keyword {
+    Msgctxt,
+    Msgid,
+    MsgidPlural,
+    Msgstr(usize),
+}
+
+fn unescape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            res.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => res.push('\n'),
+            Some('t') => res.push('\t'),
+            Some('r') => res.push('\r'),
+            Some('a') => res.push('\x07'),
+            Some('b') => res.push('\x08'),
+            Some('f') => res.push('\x0c'),
+            Some('v') => res.push('\x0b'),
+            Some(other) => res.push(other),
+            None => res.push('\\'),
+        }
+    }
+
+    res
+}
+
+fn escape(text: &str) -> String {
+    let mut res = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' => res.push_str("\\\\"),
+            '"' => res.push_str("\\\""),
+            '\n' => res.push_str("\\n"),
+            '\t' => res.push_str("\\t"),
+            '\r' => res.push_str("\\r"),
+            '\x07' => res.push_str("\\a"),
+            '\x08' => res.push_str("\\b"),
+            '\x0c' => res.push_str("\\f"),
+            '\x0b' => res.push_str("\\v"),
+            c => res.push(c),
+        }
+    }
+
+    res
+}
+
+/// Content of a quoted string, `"text"`
+fn quoted(text: &str, line: usize) -> Result<String, Error> {
+    let text = text.trim();
+    match text
+        .strip_prefix('"')
+        .and_then(|text| text.strip_suffix('"'))
+    {
+        Some(inner) if text.len() >= 2 => Ok(unescape(inner)),
+        _ => Err(Error::Parse {
+            line,
+            message: format!("expected a quoted string, found `{text}`"),
+        }),
+    }
+}
+
+impl Catalog {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+
+    /// Value of a field of the header, e.g. `Language`
+    pub fn header(&self, name: &str) -> Option<&str> {
+        let header = self.entries.iter().find(|entry| entry.is_header())?;
+
+        header.msgstr.first()?.lines().find_map(|line| {
+            let (key, value) = line.split_once(':')?;
+            (key.trim() == name).then(|| value.trim())
+        })
+    }
+
+    /// Replace the value of a field of the header, it is added when it is missing
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        let index = match self.entries.iter().position(Entry::is_header) {
+            Some(index) => index,
+            None => {
+                self.entries.insert(0, Entry::default());
+                0
+            }
+        };
+
+        let header = &mut self.entries[index];
+        if header.msgstr.is_empty() {
+            header.msgstr.push(String::new());
+        }
+
+        let mut found = false;
+        let mut fields = header.msgstr[0]
+            .lines()
+            .map(|line| match line.split_once(':') {
+                Some((key, _)) if key.trim() == name => {
+                    found = true;
+                    format!("{name}: {value}")
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>();
+
+        if !found {
+            fields.push(format!("{name}: {value}"));
+        }
+
+        header.msgstr[0] = fields.iter().map(|field| format!("{field}\n")).collect();
+    }
+
+    /// Number of plural forms of the language of the catalog, from the `Plural-Forms`
+    /// header. Two when it is not set or not a number, e.g. `INTEGER` in a template
+    pub fn nplurals(&self) -> usize {
+        self.header("Plural-Forms")
+            .and_then(|forms| {
+                forms.split(';').find_map(|form| {
+                    let (key, value) = form.split_once('=')?;
+                    (key.trim() == "nplurals").then(|| value.trim().parse().ok())?
+                })
+            })
+            .unwrap_or(2)
+    }
+
+    /// Translate the untranslated messages, the plural forms are filled with the translation
+    /// of `msgid_plural`. Machine translations are marked as `fuzzy` so they are reviewed.
+    /// The placeholders of the format flags are protected, and the `msgctxt` is the
+    /// [`Translator::context`] of the message. The `Plural-Forms` header is the one of the
+    /// target language, see [`plural_forms`], the one of the catalog is kept for the others
+    pub async fn translate(&mut self, translator: &Translator) -> Report {
+        if let Some(forms) = plural_forms(&translator.target) {
+            self.set_header("Plural-Forms", forms);
+        }
+
+        let nplurals = self.nplurals().max(1);
+        let pending = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| !entry.is_header() && !entry.obsolete && !entry.is_translated())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        let messages = pending
+            .iter()
+            .map(|index| {
+                let entry = &self.entries[*index];
+                let placeholders = entry.placeholders(translator);
+                let translator = match &entry.msgctxt {
+                    Some(context) => Cow::Owned(Translator {
+                        context: Some(context.clone()),
+                        ..translator.clone()
+                    }),
+                    None => Cow::Borrowed(translator),
+                };
+
+                (entry, translator, placeholders)
+            })
+            .collect::<Vec<_>>();
+
+        let translations = futures::stream::iter(messages.iter().flat_map(
+            |(entry, translator, placeholders)| {
+                std::iter::once(&entry.msgid)
+                    .chain(&entry.msgid_plural)
+                    .map(move |text| translate_message(translator, placeholders, text))
+            },
+        ))
+        .buffered(translator.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        let mut translations = translations.into_iter();
+        let mut report = Report::default();
+
+        for index in pending {
+            let entry = &mut self.entries[index];
+            let singular = report.record(&entry.msgid, translations.next().unwrap());
+
+            let msgstr = match &entry.msgid_plural {
+                Some(msgid_plural) => {
+                    let plural = report.record(msgid_plural, translations.next().unwrap());
+                    singular.zip(plural).map(|(singular, plural)| {
+                        std::iter::once(singular)
+                            .chain(std::iter::repeat_n(plural, nplurals - 1))
+                            .collect()
+                    })
+                }
+                None => singular.map(|singular| vec![singular]),
+            };
+
+            if let Some(msgstr) = msgstr {
+                entry.msgstr = msgstr;
+                if !entry.is_fuzzy() {
+                    entry.flags.insert(0, "fuzzy".into());
+                }
+            }
+        }
+
+        if self.header("Language").is_none_or(str::is_empty) {
+            self.set_header("Language", &translator.target);
+        }
+
+        report
+    }
+}
+
+impl FromStr for Catalog {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut entries = Vec::new();
+        let mut entry = Entry::default();
+        let mut keyword: Option<Keyword> = None;
+
+        // An entry is complete once its `msgstr` is read and something else starts
+        let finish =
+            |entry: &mut Entry, keyword: &mut Option<Keyword>, entries: &mut Vec<Entry>| {
+                if keyword.is_some() {
+                    entries.push(std::mem::take(entry));
+                    *keyword = None;
+                }
+            };
+
+        for (index, line) in s.lines().enumerate() {
+            let number = index + 1;
+            let mut line = line.trim();
+
+            if line.is_empty() {
+                finish(&mut entry, &mut keyword, &mut entries);
+                continue;
+            }
+
+            let obsolete = line.starts_with("#~");
+            if obsolete {
+                line = line[2..].trim_start();
+            }
+
+            if let Some(after_hash) = line.strip_prefix('#') {
+                if matches!(keyword, Some(Keyword::Msgstr(..))) {
+                    finish(&mut entry, &mut keyword, &mut entries);
+                }
+
+                let marker = line.get(..2).unwrap_or(line);
+                let comment = line.get(2..).unwrap_or_default();
+                let comment = comment.strip_prefix(' ').unwrap_or(comment);
+                match marker {
+                    "#|" => entry.previous.push(comment.to_string()),
+                    "#:" => entry.references.push(comment.to_string()),
+                    "#." => entry.extracted_comments.push(comment.to_string()),
+                    "#," => entry.flags.extend(
+                        comment
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|flag| !flag.is_empty())
+                            .map(String::from),
+                    ),
+                    _ => {
+                        let comment = after_hash.strip_prefix(' ').unwrap_or(after_hash);
+                        entry.translator_comments.push(comment.to_string());
+                    }
+                }
+                continue;
+            }
+
+            if line.starts_with('"') {
+                let text = quoted(line, number)?;
+                let target = match keyword {
+                    Some(Keyword::Msgctxt) => entry.msgctxt.get_or_insert_with(String::new),
+                    Some(Keyword::Msgid) => &mut entry.msgid,
+                    Some(Keyword::MsgidPlural) => {
+                        entry.msgid_plural.get_or_insert_with(String::new)
+                    }
+                    Some(Keyword::Msgstr(index)) => &mut entry.msgstr[index],
+                    None => {
+                        return Err(Error::Parse {
+                            line: number,
+                            message: "string without keyword".into(),
+                        })
+                    }
+                };
+                target.push_str(&text);
+                continue;
+            }
+
+            let (name, value) = line.split_once([' ', '\t']).unwrap_or((line, ""));
+            let next = match name {
+                "msgctxt" => Keyword::Msgctxt,
+                "msgid" => Keyword::Msgid,
+                "msgid_plural" => Keyword::MsgidPlural,
+                "msgstr" => Keyword::Msgstr(0),
+                _ => match name
+                    .strip_prefix("msgstr[")
+                    .and_then(|index| index.strip_suffix(']'))
+                    .and_then(|index| index.parse().ok())
+                {
+                    Some(index) => Keyword::Msgstr(index),
+                    None => {
+                        return Err(Error::Parse {
+                            line: number,
+                            message: format!("unknown keyword `{name}`"),
+                        })
+                    }
+                },
+            };
+
+            // A new message starts without a blank line before it
+            if matches!(next, Keyword::Msgctxt | Keyword::Msgid)
+                && matches!(keyword, Some(Keyword::Msgstr(..)))
+            {
+                finish(&mut entry, &mut keyword, &mut entries);
+            }
+
+            let text = quoted(value, number)?;
+            entry.obsolete |= obsolete;
+            match next {
+                Keyword::Msgctxt => entry.msgctxt = Some(text),
+                Keyword::Msgid => entry.msgid = text,
+                Keyword::MsgidPlural => entry.msgid_plural = Some(text),
+                Keyword::Msgstr(index) => {
+                    if entry.msgstr.len() <= index {
+                        entry.msgstr.resize(index + 1, String::new());
+                    }
+                    entry.msgstr[index] = text;
+                }
+            }
+            keyword = Some(next);
+        }
+
+        finish(&mut entry, &mut keyword, &mut entries);
+        Ok(Catalog { entries })
+    }
+}
+
+/// Write `keyword "text"`, texts with several lines are split after each new line
+fn write_string(
+    f: &mut fmt::Formatter<'_>,
+    prefix: &str,
+    keyword: &str,
+    text: &str,
+) -> fmt::Result {
+    let lines = text.split_inclusive('\n').collect::<Vec<_>>();
+    if lines.len() <= 1 {
+        return writeln!(f, "{prefix}{keyword} \"{}\"", escape(text));
+    }
+
+    writeln!(f, "{prefix}{keyword} \"\"")?;
+    for line in lines {
+        writeln!(f, "{prefix}\"{}\"", escape(line))?;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Entry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for comment in &self.translator_comments {
+            match comment.is_empty() {
+                true => writeln!(f, "#")?,
+                false => writeln!(f, "# {comment}")?,
+            }
+        }
+
+        for comment in &self.extracted_comments {
+            writeln!(f, "#. {comment}")?;
+        }
+
+        for reference in &self.references {
+            writeln!(f, "#: {reference}")?;
+        }
+
+        if !self.flags.is_empty() {
+            writeln!(f, "#, {}", self.flags.join(", "))?;
+        }
+
+        for previous in &self.previous {
+            writeln!(f, "#| {previous}")?;
+        }
+
+        let prefix = if self.obsolete { "#~ " } else { "" };
+        if let Some(msgctxt) = &self.msgctxt {
+            write_string(f, prefix, "msgctxt", msgctxt)?;
+        }
+
+        write_string(f, prefix, "msgid", &self.msgid)?;
+
+        match &self.msgid_plural {
+            Some(msgid_plural) => {
+                write_string(f, prefix, "msgid_plural", msgid_plural)?;
+                for (index, msgstr) in self.msgstr.iter().enumerate() {
+                    write_string(f, prefix, &format!("msgstr[{index}]"), msgstr)?;
+                }
+                if self.msgstr.is_empty() {
+                    write_string(f, prefix, "msgstr[0]", "")?;
+                }
+            }
+            None => write_string(f, prefix, "msgstr", self.msgstr.first().map_or("", |s| s))?,
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, entry) in self.entries.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            entry.fmt(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    #[tokio::test]
+    async fn surrounding_whitespace_is_kept() {
+        let po = "msgid \"\"\n\"  Hello\\n\"\nmsgstr \"\"\n";
+        let mut catalog = po.parse::<Catalog>().unwrap();
+
+        let report = catalog.translate(&translator(&[("Hello", "Hallo")])).await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(catalog.entries[1].msgstr, ["  Hallo\n"]);
+        assert!(catalog.entries[1].is_fuzzy());
+        assert_eq!(catalog.header("Language"), Some("de"));
+    }
+
+    #[tokio::test]
+    async fn plural_forms() {
+        let po = "msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+        let mut catalog = po.parse::<Catalog>().unwrap();
+        let translator = translator(&[("%d file", "%d Datei"), ("%d files", "%d Dateien")]);
+
+        catalog.translate(&translator).await;
+        assert_eq!(catalog.entries[1].msgstr, ["%d Datei", "%d Dateien"]);
+    }
+
+    #[tokio::test]
+    async fn plural_forms_of_the_target() {
+        let pot = "msgid \"\"\nmsgstr \"\"\n\"Plural-Forms: nplurals=INTEGER; plural=EXPRESSION;\\n\"\n\n\
+            msgid \"%d file\"\nmsgid_plural \"%d files\"\nmsgstr[0] \"\"\nmsgstr[1] \"\"\n";
+        let mut catalog = pot.parse::<Catalog>().unwrap();
+        let mut translator = Translator::new("en", "ru");
+        translator.memory.insert("en", "ru", "%d file", "%d файл");
+        translator
+            .memory
+            .insert("en", "ru", "%d files", "%d файлов");
+
+        catalog.translate(&translator).await;
+        assert_eq!(catalog.nplurals(), 3);
+        assert!(catalog
+            .header("Plural-Forms")
+            .unwrap()
+            .starts_with("nplurals=3; plural=(n%10==1"));
+        assert_eq!(
+            catalog.entries[1].msgstr,
+            ["%d файл", "%d файлов", "%d файлов"]
+        );
+
+        assert_eq!(super::plural_forms("ja"), Some("nplurals=1; plural=0;"));
+        assert_eq!(
+            super::plural_forms("pt_BR"),
+            Some("nplurals=2; plural=(n > 1);")
+        );
+        assert_eq!(
+            super::plural_forms("pt-PT"),
+            Some("nplurals=2; plural=(n != 1);")
+        );
+        assert_eq!(super::plural_forms("xx"), None);
+    }
+
+    #[test]
+    fn format_flags_protect_placeholders() {
+        let po = "#, c-format\nmsgid \"%d files\"\nmsgstr \"\"\n\n\
+            #, python-format, fuzzy\nmsgid \"%(count)s files\"\nmsgstr \"\"\n\n\
+            msgid \"100% sure\"\nmsgstr \"\"\n";
+        let catalog = po.parse::<Catalog>().unwrap();
+        let translator = Translator::new("en", "de");
+
+        let placeholders = catalog
+            .entries
+            .iter()
+            .map(|entry| entry.placeholders(&translator))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            placeholders,
+            [
+                vec![PlaceholderSyntax::Printf],
+                vec![PlaceholderSyntax::Python],
+                vec![]
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let po = "# comment\n#: src/main.rs:1\n#, fuzzy, c-format\nmsgctxt \"menu\"\nmsgid \"Open\"\nmsgstr \"Öffnen\"\n\n#~ msgid \"Old\"\n#~ msgstr \"Alt\"\n";
+        let catalog = po.parse::<Catalog>().unwrap();
+
+        assert_eq!(catalog.entries[0].msgctxt.as_deref(), Some("menu"));
+        assert!(catalog.entries[1].obsolete);
+        assert_eq!(catalog.to_string(), po);
+    }
+}
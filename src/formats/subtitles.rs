@@ -0,0 +1,597 @@
+//! SubRip `.srt` and WebVTT `.vtt` subtitles
+//!
+//! The cue numbers, identifiers, timings and settings are kept, as well as the styling tags,
+//! `<i>`, `<font color="...">`, `<v Speaker>` and `{\an8}`. The lines of a cue are
+//! translated together and wrapped again to [`SubtitleOptions::max_line_length`], except the
+//! dialogue lines that start with `-`, that are translated one by one.
+//!
+//!```rust,no_run
+//! use deeptranslator::{
+//!     formats::subtitles::{SubtitleOptions, Subtitles},
+//!     Engine, Translator,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let subtitles = Subtitles::open("talk.en.srt")?;
+//!     let options = SubtitleOptions {
+//!         merge_sentences: true,
+//!         ..Default::default()
+//!     };
+//!
+//!     let (translation, report) = subtitles.translate(&translator, &options).await;
+//!     println!("{} cues translated", report.translated);
+//!
+//!     translation.save("talk.de.srt")
+//! }
+//!```
+
+use super::Report;
+use crate::{translator::mask::Masked, Error, Translator};
+use futures::StreamExt;
+use std::{fmt, path::Path, str::FromStr};
+
+/// The most cues merged into a sentence
+const MAX_MERGED_CUES: usize = 5;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum SubtitleFormat {
+    Srt,
+    Vtt,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct SubtitleOptions {
+    /// Translate together the cues that split a sentence, so the engine sees it whole, the
+    /// translation is split again between the cues in proportion to their length
+    pub merge_sentences: bool,
+    /// Most characters of a line of a cue, without the tags. No limit when it is `None`
+    pub max_line_length: Option<usize>,
+}
+
+impl Default for SubtitleOptions {
+    #[inline(always)]
+    fn default() -> Self {
+        Self {
+            merge_sentences: false,
+            max_line_length: Some(42),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Cue {
+    /// The number of a SubRip cue, or the optional identifier of a WebVTT one
+    pub identifier: Option<String>,
+    /// `00:00:01,000 --> 00:00:02,500`, with the settings of WebVTT
+    pub timing: String,
+    pub lines: Vec<String>,
+}
+
+impl Cue {
+    /// Dialogues have a line for each speaker, starting with a dash
+    #[inline(always)]
+    fn is_dialogue(&self) -> bool {
+        self.lines.len() > 1 && self.lines.iter().all(|line| line.starts_with('-'))
+    }
+
+    #[inline(always)]
+    fn text(&self) -> String {
+        self.lines.join(" ")
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Block {
+    Cue(Cue),
+    /// Header, comments, styles and regions of WebVTT
+    Verbatim(String),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Subtitles {
+    pub format: SubtitleFormat,
+    pub blocks: Vec<Block>,
+    /// `\r\n` when the file uses it
+    newline: &'static str,
+}
+
+/// Length of the styling tag at the start of `text`
+fn tag(text: &str) -> Option<usize> {
+    let close = match text.chars().next()? {
+        '<' => '>',
+        '{' if text.starts_with("{\\") => '}',
+        _ => return None,
+    };
+
+    text.find(close).map(|end| end + 1)
+}
+
+/// Protect the styling tags of the text
+fn mask(text: &str, translator: &Translator) -> Masked {
+    let mut masked = Masked::new(&translator.placeholders);
+    let mut start = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        match tag(&text[index..]) {
+            Some(len) => {
+                masked.push_text(&text[start..index]);
+                masked.push_protected(&text[index..index + len]);
+                index += len;
+                start = index;
+            }
+            None => index += text[index..].chars().next().map_or(1, char::len_utf8),
+        }
+    }
+
+    masked.push_text(&text[start..]);
+    masked
+}
+
+/// The words of the text, a tag is never split
+fn words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        if let Some(len) = tag(&text[index..]) {
+            index += len;
+            continue;
+        }
+
+        let c = text[index..].chars().next().unwrap();
+        if c.is_whitespace() {
+            if start < index {
+                words.push(&text[start..index]);
+            }
+            start = index + c.len_utf8();
+        }
+        index += c.len_utf8();
+    }
+
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+
+    words
+}
+
+/// Number of characters that are shown, without the tags
+fn visible_len(text: &str) -> usize {
+    let mut len = 0;
+    let mut index = 0;
+
+    while index < text.len() {
+        match tag(&text[index..]) {
+            Some(tag_len) => index += tag_len,
+            None => {
+                len += 1;
+                index += text[index..].chars().next().map_or(1, char::len_utf8);
+            }
+        }
+    }
+
+    len
+}
+
+/// Break the text in lines of at most `max` characters, of similar length
+fn wrap(text: &str, max: Option<usize>) -> Vec<String> {
+    let words = words(text);
+    let total = visible_len(&words.join(" "));
+    let max = match max {
+        Some(max) if total > max && max > 0 => max,
+        _ => return vec![words.join(" ")],
+    };
+
+    // The width that balances the lines, it is never over the maximum
+    let count = total.div_ceil(max);
+    let width = total.div_ceil(count).max(1);
+    let mut lines = Vec::<String>::new();
+    let mut line = String::new();
+
+    for word in words {
+        let len = visible_len(&line) + 1 + visible_len(word);
+        if !line.is_empty() && (len > max || (len > width && lines.len() + 1 < count)) {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(word);
+    }
+
+    lines.push(line);
+    lines
+}
+
+/// Split the translation of merged cues in `weights.len()` parts, each proportional to the
+/// length of the original cue
+fn split(text: &str, weights: &[usize]) -> Vec<String> {
+    let words = words(text);
+    let total = weights.iter().sum::<usize>().max(1);
+    let text_len = visible_len(&words.join(" "));
+    let mut parts = vec![String::new(); weights.len()];
+    let mut part = 0;
+    let mut done = 0;
+    let mut goal = weights[0] * text_len / total;
+
+    for (index, word) in words.iter().enumerate() {
+        // Every cue keeps at least a word, when there are enough of them
+        let remaining_words = words.len() - index;
+        let remaining_parts = weights.len() - part - 1;
+        let full = done >= goal && !parts[part].is_empty();
+
+        if part + 1 < weights.len() && (full || remaining_words <= remaining_parts) {
+            part += 1;
+            goal += weights[part] * text_len / total;
+        }
+
+        if !parts[part].is_empty() {
+            parts[part].push(' ');
+            done += 1;
+        }
+        parts[part].push_str(word);
+        done += visible_len(word);
+    }
+
+    parts
+}
+
+/// Whether the text ends a sentence
+fn ends_sentence(text: &str) -> bool {
+    let text = text.trim_end_matches(|c: char| c.is_whitespace() || "\"'”’»)]".contains(c));
+    let mut end = text.len();
+    while let Some(len) = text[..end]
+        .rfind('<')
+        .filter(|_| text[..end].ends_with('>'))
+    {
+        end = len;
+    }
+
+    text[..end]
+        .trim_end()
+        .ends_with(['.', '!', '?', '…', '。', '！', '？', ':', ';', '♪'])
+}
+
+/// Parse a timing line, `start --> end`
+#[inline(always)]
+fn is_timing(line: &str) -> bool {
+    line.contains("-->")
+}
+
+impl Subtitles {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+
+    pub fn cues(&self) -> impl Iterator<Item = &Cue> {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Cue(cue) => Some(cue),
+            Block::Verbatim(_) => None,
+        })
+    }
+
+    /// Translate the text of the cues, the cues that fail are kept in the source language
+    pub async fn translate(
+        &self,
+        translator: &Translator,
+        options: &SubtitleOptions,
+    ) -> (Self, Report) {
+        let cues = self
+            .blocks
+            .iter()
+            .enumerate()
+            .filter_map(|(index, block)| match block {
+                Block::Cue(cue) if !cue.lines.is_empty() => Some((index, cue)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // The groups of cues translated together, and the line of a dialogue
+        let mut units = Vec::<(Vec<usize>, Option<usize>)>::new();
+        let mut group = Vec::new();
+        for (index, cue) in &cues {
+            if cue.is_dialogue() {
+                if !group.is_empty() {
+                    units.push((std::mem::take(&mut group), None));
+                }
+                units.extend((0..cue.lines.len()).map(|line| (vec![*index], Some(line))));
+                continue;
+            }
+
+            group.push(*index);
+            if !options.merge_sentences
+                || ends_sentence(&cue.text())
+                || group.len() == MAX_MERGED_CUES
+            {
+                units.push((std::mem::take(&mut group), None));
+            }
+        }
+        if !group.is_empty() {
+            units.push((group, None));
+        }
+
+        let cue = |index: usize| match &self.blocks[index] {
+            Block::Cue(cue) => cue,
+            Block::Verbatim(_) => unreachable!("the units only have cues"),
+        };
+        let texts = units
+            .iter()
+            .map(|(indices, line)| match line {
+                Some(line) => cue(indices[0]).lines[*line].clone(),
+                None => indices
+                    .iter()
+                    .map(|index| cue(*index).text())
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            })
+            .collect::<Vec<_>>();
+
+        let translations = futures::stream::iter(texts.iter().map(|text| async move {
+            let masked = mask(text, translator);
+            match masked.has_text() {
+                true => translator.translate_masked_text(&masked).await,
+                false => Ok(text.clone()),
+            }
+        }))
+        .buffered(translator.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        let mut translation = self.clone();
+        let mut report = Report::default();
+        let mut dialogues = std::collections::HashMap::<usize, Vec<String>>::new();
+
+        for (((indices, line), text), result) in units.iter().zip(&texts).zip(translations) {
+            let Some(translated) = report.record(text, result) else {
+                continue;
+            };
+
+            if let Some(line) = line {
+                let lines = dialogues
+                    .entry(indices[0])
+                    .or_insert_with(|| cue(indices[0]).lines.clone());
+                lines[*line] = words(&translated).join(" ");
+                continue;
+            }
+
+            let weights = indices
+                .iter()
+                .map(|index| visible_len(&cue(*index).text()))
+                .collect::<Vec<_>>();
+            let parts = match indices.len() {
+                1 => vec![translated],
+                _ => split(&translated, &weights),
+            };
+
+            for (index, part) in indices.iter().zip(parts) {
+                if let Block::Cue(cue) = &mut translation.blocks[*index] {
+                    cue.lines = wrap(&part, options.max_line_length);
+                }
+            }
+        }
+
+        for (index, lines) in dialogues {
+            if let Block::Cue(cue) = &mut translation.blocks[index] {
+                cue.lines = lines;
+            }
+        }
+
+        (translation, report)
+    }
+}
+
+impl FromStr for Subtitles {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim_start_matches('\u{feff}');
+        let format = match s.starts_with("WEBVTT") {
+            true => SubtitleFormat::Vtt,
+            false => SubtitleFormat::Srt,
+        };
+
+        let mut blocks = Vec::new();
+        let mut lines = s.lines().enumerate().peekable();
+
+        while lines.peek().is_some() {
+            let block = std::iter::from_fn(|| lines.next_if(|(_, line)| !line.trim().is_empty()))
+                .collect::<Vec<_>>();
+            while lines.next_if(|(_, line)| line.trim().is_empty()).is_some() {}
+
+            let Some((number, first)) = block.first() else {
+                continue;
+            };
+
+            let is_metadata = format == SubtitleFormat::Vtt
+                && (*number == 0
+                    || ["NOTE", "STYLE", "REGION"]
+                        .iter()
+                        .any(|k| first.starts_with(k)));
+            if is_metadata {
+                let text = block.iter().map(|(_, line)| *line).collect::<Vec<_>>();
+                blocks.push(Block::Verbatim(text.join("\n")));
+                continue;
+            }
+
+            let Some(timing) = block.iter().take(2).position(|(_, line)| is_timing(line)) else {
+                return Err(Error::Parse {
+                    line: number + 1,
+                    message: format!("expected the timing of a cue, found `{first}`"),
+                });
+            };
+
+            blocks.push(Block::Cue(Cue {
+                identifier: (timing == 1).then(|| first.trim().to_string()),
+                timing: block[timing].1.trim().to_string(),
+                lines: block[timing + 1..]
+                    .iter()
+                    .map(|(_, line)| line.trim_end().to_string())
+                    .collect(),
+            }));
+        }
+
+        Ok(Self {
+            format,
+            blocks,
+            newline: match s.contains("\r\n") {
+                true => "\r\n",
+                false => "\n",
+            },
+        })
+    }
+}
+
+impl fmt::Display for Subtitles {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let newline = self.newline;
+
+        for block in &self.blocks {
+            match block {
+                Block::Verbatim(text) => write!(f, "{}{newline}", text.replace('\n', newline))?,
+                Block::Cue(cue) => {
+                    if let Some(identifier) = &cue.identifier {
+                        write!(f, "{identifier}{newline}")?;
+                    }
+
+                    write!(f, "{}{newline}", cue.timing)?;
+                    for line in &cue.lines {
+                        write!(f, "{line}{newline}")?;
+                    }
+                }
+            }
+
+            f.write_str(newline)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    const SRT: &str = "1\r\n00:00:01,000 --> 00:00:02,500\r\n<i>Hello my</i>\r\n\r\n\
+        2\r\n00:00:02,500 --> 00:00:04,000\r\ngood old friend.\r\n\r\n\
+        3\r\n00:00:04,000 --> 00:00:05,000\r\n- Yes?\r\n- No!\r\n\r\n";
+
+    const VTT: &str = "WEBVTT\n\nNOTE a comment\nof two lines\n\n\
+        intro\n00:01.000 --> 00:02.000 align:start\n<v Anna>Hi {\\an8}there</v>\n\n\
+        00:02.000 --> 00:03.000\nBye\n\n";
+
+    #[test]
+    fn lines_are_wrapped() {
+        assert_eq!(wrap("Short line", Some(42)), ["Short line"]);
+        assert_eq!(wrap("No   limit at  all", None), ["No limit at all"]);
+        assert_eq!(
+            wrap("The quick brown fox jumps over the lazy dog", Some(20)),
+            ["The quick brown", "fox jumps over", "the lazy dog"]
+        );
+        // The tags are not counted
+        assert_eq!(
+            wrap("<font color=\"red\">Ein rotes Wort</font>", Some(14)),
+            ["<font color=\"red\">Ein rotes Wort</font>"]
+        );
+        assert_eq!(
+            wrap("<i>Ein rotes</i> Wort", Some(12)),
+            ["<i>Ein", "rotes</i> Wort"]
+        );
+    }
+
+    #[test]
+    fn merged_translations_are_split() {
+        assert_eq!(
+            split("Hallo mein guter alter Freund.", &[8, 16]),
+            ["Hallo mein", "guter alter Freund."]
+        );
+        // Every cue keeps a word, even when the weights are uneven
+        assert_eq!(split("a b c", &[100, 1, 1]), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn sentence_ends() {
+        assert!(ends_sentence("Hello."));
+        assert!(ends_sentence("Really?\" "));
+        assert!(ends_sentence("<i>Stop!</i>"));
+        assert!(ends_sentence("♪ la la ♪"));
+        assert!(!ends_sentence("Hello my"));
+        assert!(!ends_sentence("<i>and then</i>"));
+    }
+
+    #[test]
+    fn srt_is_parsed_and_written() {
+        let subtitles: Subtitles = SRT.parse().unwrap();
+
+        assert_eq!(subtitles.format, SubtitleFormat::Srt);
+        assert_eq!(
+            subtitles.cues().next(),
+            Some(&Cue {
+                identifier: Some("1".into()),
+                timing: "00:00:01,000 --> 00:00:02,500".into(),
+                lines: vec!["<i>Hello my</i>".into()],
+            })
+        );
+        assert_eq!(subtitles.cues().count(), 3);
+        assert_eq!(subtitles.to_string(), SRT);
+        assert!(matches!(
+            "1\nHello\n".parse::<Subtitles>(),
+            Err(Error::Parse { line: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn vtt_is_parsed_and_written() {
+        let subtitles: Subtitles = VTT.parse().unwrap();
+
+        assert_eq!(subtitles.format, SubtitleFormat::Vtt);
+        assert_eq!(
+            subtitles.blocks[1],
+            Block::Verbatim("NOTE a comment\nof two lines".into())
+        );
+        let cues = subtitles.cues().collect::<Vec<_>>();
+        assert_eq!(cues[0].identifier.as_deref(), Some("intro"));
+        assert_eq!(cues[0].timing, "00:01.000 --> 00:02.000 align:start");
+        assert_eq!(cues[1].identifier, None);
+        assert_eq!(subtitles.to_string(), VTT);
+    }
+
+    #[tokio::test]
+    async fn sentences_are_merged() {
+        let translator = translator(&[
+            (
+                "<i>Hello my</i> good old friend.",
+                "<i>Hallo mein</i> guter alter Freund.",
+            ),
+            ("- Yes?", "- Ja?"),
+            ("- No!", "- Nein!"),
+        ]);
+        let options = SubtitleOptions {
+            merge_sentences: true,
+            ..Default::default()
+        };
+
+        let subtitles: Subtitles = SRT.parse().unwrap();
+        let (translation, report) = subtitles.translate(&translator, &options).await;
+
+        assert_eq!(report.translated, 3);
+        assert_eq!(
+            translation.to_string(),
+            SRT.replace("<i>Hello my</i>", "<i>Hallo mein</i>")
+                .replace("good old friend.", "guter alter Freund.")
+                .replace("- Yes?", "- Ja?")
+                .replace("- No!", "- Nein!")
+        );
+    }
+}
@@ -0,0 +1,285 @@
+//! Incremental translation of JSON locale files
+//!
+//! A lockfile records, for each target language and key, a hash of the source string and
+//! of its machine translation. A sync only translates the keys that are new or whose
+//! source changed, and keeps the entries edited by people: when the translation in the
+//! locale file is not the one recorded, it is marked as human edited and never replaced.
+//! The keys whose translation failed are recorded as pending and retried in the next sync.
+//! The keys removed from the source are dropped and reported as stale.
+//!
+//!```rust,no_run
+//! use deeptranslator::{
+//!     formats::{json::Resource, sync::{sync_json, Lockfile}},
+//!     Engine, Translator,
+//! };
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let source = Resource::open("locales/en.json")?;
+//!     let previous = Resource::open("locales/de.json").ok();
+//!     let mut lock = Lockfile::open("locales/deep-translator.lock").unwrap_or_default();
+//!
+//!     let (translation, report) = sync_json(&translator, &source, previous.as_ref(), &mut lock).await;
+//!     println!("{} translated, {} stale", report.translated.len(), report.stale.len());
+//!
+//!     translation.save("locales/de.json")?;
+//!     lock.save("locales/deep-translator.lock")
+//! }
+//!```
+
+use super::json::Resource;
+use crate::{Error, Translator};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::Path,
+};
+
+/// FNV-1a hash of the text, 16 hexadecimal digits
+pub fn hash(text: &str) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let hash = text.bytes().fold(OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    });
+
+    format!("{hash:016x}")
+}
+
+#[inline(always)]
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+/// What the lockfile knows about a key of a target language
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct LockEntry {
+    /// Hash of the source string that was translated
+    pub source: String,
+    /// Hash of the machine translation
+    pub translation: String,
+    /// The translation was changed by a person, the sync keeps it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub human_edited: bool,
+    /// The translation failed, `translation` is the hash of the text written instead of it
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pending: bool,
+}
+
+/// The entries of every target language, by the JSON pointer of the key
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Lockfile {
+    pub targets: BTreeMap<String, BTreeMap<String, LockEntry>>,
+}
+
+impl Lockfile {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Ok(serde_json::from_str(&std::fs::read_to_string(path)?)?)
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let text = serde_json::to_string_pretty(self)?;
+        Ok(std::fs::write(path, format!("{text}\n"))?)
+    }
+}
+
+/// The keys of a sync by what happened to them, the keys are JSON pointers
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    /// New keys and keys whose source changed, translated now
+    pub translated: Vec<String>,
+    /// Keys whose source and translation did not change
+    pub unchanged: Vec<String>,
+    /// Keys with a translation made by a person, they are kept
+    pub human_edited: Vec<String>,
+    /// Keys edited by a person whose source changed since, they need a review
+    pub outdated: Vec<String>,
+    /// Keys removed from the source, they are dropped from the translation
+    pub stale: Vec<String>,
+    /// Translations that failed, their keys keep the previous translation, or the source
+    /// text when there is none, and are pending in the lockfile to be retried in the next
+    /// sync
+    pub failed: Vec<(String, Error)>,
+}
+
+/// Sync the translation of `source` into the target language of the translator. The
+/// `previous` translation is the locale file as it is now, if there is one
+pub async fn sync_json(
+    translator: &Translator,
+    source: &Resource,
+    previous: Option<&Resource>,
+    lock: &mut Lockfile,
+) -> (Resource, SyncReport) {
+    let entries = lock.targets.entry(translator.target.clone()).or_default();
+    let mut report = SyncReport::default();
+    let mut translation = source.clone();
+    let mut pending = Vec::new();
+    let selected = source.selected();
+
+    for (pointer, text) in &selected {
+        let current = previous
+            .and_then(|previous| previous.value.pointer(pointer))
+            .and_then(Value::as_str);
+        let entry = entries.get_mut(pointer);
+
+        match (current, entry) {
+            (Some(current), Some(entry))
+                if entry.human_edited || hash(current) != entry.translation =>
+            {
+                entry.human_edited = true;
+                entry.pending = false;
+                match entry.source == hash(text) {
+                    true => report.human_edited.push(pointer.clone()),
+                    false => report.outdated.push(pointer.clone()),
+                }
+            }
+            (Some(_), Some(entry)) if !entry.pending && entry.source == hash(text) => {
+                report.unchanged.push(pointer.clone())
+            }
+            // Translations made before the lockfile are kept as the ones of people
+            (Some(current), None) => {
+                entries.insert(
+                    pointer.clone(),
+                    LockEntry {
+                        source: hash(text),
+                        translation: hash(current),
+                        human_edited: true,
+                        pending: false,
+                    },
+                );
+                report.human_edited.push(pointer.clone());
+            }
+            _ => {
+                pending.push((pointer, *text));
+                continue;
+            }
+        }
+
+        if let Some(value) = translation.value.pointer_mut(pointer) {
+            *value = Value::String(current.unwrap_or_default().to_string());
+        }
+    }
+
+    let texts = pending.iter().map(|(_, text)| *text).collect::<Vec<_>>();
+    let translations = translator.translate_strings(&texts).await;
+
+    for ((pointer, text), result) in pending.into_iter().zip(translations) {
+        let (translated, pending) = match result {
+            Ok(translated) => {
+                report.translated.push(pointer.clone());
+                (translated, false)
+            }
+            Err(err) => {
+                report.failed.push((pointer.clone(), err));
+                let current = previous
+                    .and_then(|previous| previous.value.pointer(pointer))
+                    .and_then(Value::as_str)
+                    .unwrap_or(text);
+
+                (current.to_string(), true)
+            }
+        };
+
+        entries.insert(
+            pointer.clone(),
+            LockEntry {
+                source: hash(text),
+                translation: hash(&translated),
+                human_edited: false,
+                pending,
+            },
+        );
+
+        if let Some(value) = translation.value.pointer_mut(pointer) {
+            *value = Value::String(translated);
+        }
+    }
+
+    // Keys of the lockfile or of the previous translation that the source no longer has
+    let selected = selected
+        .into_iter()
+        .map(|(pointer, _)| pointer)
+        .collect::<BTreeSet<_>>();
+    let mut stale = entries
+        .keys()
+        .cloned()
+        .chain(
+            previous
+                .into_iter()
+                .flat_map(|previous| previous.selected().into_iter().map(|(pointer, _)| pointer)),
+        )
+        .filter(|pointer| !selected.contains(pointer))
+        .collect::<Vec<_>>();
+    stale.sort_unstable();
+    stale.dedup();
+
+    entries.retain(|pointer, _| selected.contains(pointer));
+    report.stale = stale;
+
+    (translation, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    fn resource(json: &str) -> Resource {
+        json.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn failed_translations_are_retried() {
+        let source = resource(r#"{"greeting": "Hello", "farewell": "Goodbye"}"#);
+        let mut lock = Lockfile::default();
+
+        let translator = translator(&[("Goodbye", "Tschüss")]);
+        let (first, report) = sync_json(&translator, &source, None, &mut lock).await;
+        assert_eq!(report.translated, ["/farewell"]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(first.value["greeting"], "Hello");
+        assert!(lock.targets["de"]["/greeting"].pending);
+
+        let translator = self::translator(&[("Hello", "Hallo"), ("Goodbye", "Tschüss")]);
+        let (second, report) = sync_json(&translator, &source, Some(&first), &mut lock).await;
+        assert_eq!(report.translated, ["/greeting"]);
+        assert_eq!(report.unchanged, ["/farewell"]);
+        assert!(report.human_edited.is_empty() && report.failed.is_empty());
+        assert_eq!(second.value["greeting"], "Hallo");
+        assert!(!lock.targets["de"]["/greeting"].pending);
+    }
+
+    #[tokio::test]
+    async fn failed_translation_edited_by_a_person() {
+        let source = resource(r#"{"greeting": "Hello"}"#);
+        let mut lock = Lockfile::default();
+
+        let (mut first, report) = sync_json(&translator(&[]), &source, None, &mut lock).await;
+        assert_eq!(report.failed.len(), 1);
+
+        first.value["greeting"] = "Servus".into();
+        let translator = translator(&[("Hello", "Hallo")]);
+        let (second, report) = sync_json(&translator, &source, Some(&first), &mut lock).await;
+        assert_eq!(report.human_edited, ["/greeting"]);
+        assert_eq!(second.value["greeting"], "Servus");
+    }
+
+    #[tokio::test]
+    async fn human_edits_are_kept() {
+        let source = resource(r#"{"greeting": "Hello"}"#);
+        let mut lock = Lockfile::default();
+        let translator = translator(&[("Hello", "Hallo")]);
+
+        let (mut first, _) = sync_json(&translator, &source, None, &mut lock).await;
+        first.value["greeting"] = "Grüß Gott".into();
+
+        let (second, report) = sync_json(&translator, &source, Some(&first), &mut lock).await;
+        assert_eq!(report.human_edited, ["/greeting"]);
+        assert_eq!(second.value["greeting"], "Grüß Gott");
+        assert!(lock.targets["de"]["/greeting"].human_edited);
+    }
+}
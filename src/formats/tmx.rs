@@ -0,0 +1,432 @@
+//! TMX 1.4 translation memories
+//!
+//! The translations made with a [`Translator`] can be exported to be reused in CAT tools,
+//! each unit has the engine and the time of the translation as properties. A TMX file,
+//! with the segments approved by translators, seeds the [`TranslationMemory`] of a
+//! translator, those texts are not sent to the engine.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::tmx::Tmx, Engine, Translator};
+//! use std::time::SystemTime;
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let mut translator = Translator::with_engine("en", "de", Engine::Google);
+//!     translator.memory = Tmx::open("approved.tmx")?.memory();
+//!
+//!     let mut tmx = Tmx::new("en");
+//!     for text in ["Save", "Cancel"] {
+//!         let translation = translator.translate(text).await?;
+//!         let translation = translation.as_str().unwrap_or_default();
+//!         tmx.push(&translator, text, translation, SystemTime::now());
+//!     }
+//!
+//!     tmx.save("machine.tmx")
+//! }
+//!```
+
+use super::xml::{attribute, closing_tag, escape, next_tag, unescape};
+use crate::{Error, TranslationMemory, Translator};
+use std::{
+    fmt,
+    path::Path,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Property with the name of the engine that translated the unit
+pub const ENGINE_PROPERTY: &str = "x-engine";
+/// Property with the time of the translation, as in `creationdate`
+pub const TIMESTAMP_PROPERTY: &str = "x-timestamp";
+
+/// Inline elements whose content is the escaped native code of the segment
+const NATIVE_CODE_ELEMENTS: [&str; 4] = ["bpt", "ept", "it", "ph"];
+
+/// A translation unit, with the text in the source language and its translation
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Unit {
+    pub source_language: String,
+    pub source: String,
+    pub target_language: String,
+    pub target: String,
+    /// `<prop>` elements, by their type
+    pub properties: Vec<(String, String)>,
+    /// In the TMX format, `20240131T094500Z`
+    pub creation_date: Option<String>,
+}
+
+impl Unit {
+    #[inline(always)]
+    pub fn property(&self, kind: &str) -> Option<&str> {
+        self.properties
+            .iter()
+            .find_map(|(name, value)| (name == kind).then_some(value.as_str()))
+    }
+}
+
+/// A TMX document
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct Tmx {
+    /// `srclang` of the header, `*all*` when the units have different source languages
+    pub source_language: String,
+    pub units: Vec<Unit>,
+}
+
+/// Date and time in UTC, as TMX writes them, `20240131T094500Z`
+pub fn timestamp(time: SystemTime) -> String {
+    let seconds = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+
+    // Civil date from the days since 1970-01-01, proleptic Gregorian calendar
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = match month_index < 10 {
+        true => month_index + 3,
+        false => month_index - 9,
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Plain text of a `<seg>`, the inline elements are replaced by the native code they hold
+fn segment_text(content: &str) -> String {
+    let mut text = String::with_capacity(content.len());
+    let mut from = 0;
+
+    while let Some(tag) = next_tag(content, from) {
+        text.push_str(&unescape(&content[from..tag.range.start]));
+        from = tag.range.end;
+
+        if NATIVE_CODE_ELEMENTS.contains(&tag.name) && !tag.closing && !tag.empty {
+            if let Some(close) = closing_tag(content, &tag) {
+                let code = &content[tag.range.end..close.range.start];
+                text.push_str(&segment_text(code));
+                from = close.range.end;
+            }
+        }
+    }
+
+    text.push_str(&unescape(&content[from..]));
+    text
+}
+
+impl Tmx {
+    #[inline(always)]
+    pub fn new(source_language: &str) -> Self {
+        Self {
+            source_language: source_language.to_string(),
+            units: Vec::new(),
+        }
+    }
+
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, self.to_string())?)
+    }
+
+    /// Add the translation of a text made by the translator at the given time. The caller
+    /// gives it, [`SystemTime::now`] panics on `wasm32-unknown-unknown`
+    pub fn push(&mut self, translator: &Translator, source: &str, target: &str, time: SystemTime) {
+        let now = timestamp(time);
+
+        self.units.push(Unit {
+            source_language: translator.source.clone(),
+            source: source.to_string(),
+            target_language: translator.target.clone(),
+            target: target.to_string(),
+            properties: vec![
+                (ENGINE_PROPERTY.into(), translator.engine.name().into()),
+                (TIMESTAMP_PROPERTY.into(), now.clone()),
+            ],
+            creation_date: Some(now),
+        });
+    }
+
+    /// Translation memory with every unit, the last unit wins when a text has several
+    /// translations between the same languages
+    pub fn memory(&self) -> TranslationMemory {
+        let mut memory = TranslationMemory::new();
+        self.extend_memory(&mut memory);
+        memory
+    }
+
+    #[inline(always)]
+    pub fn extend_memory(&self, memory: &mut TranslationMemory) {
+        for unit in &self.units {
+            memory.insert(
+                &unit.source_language,
+                &unit.target_language,
+                &unit.source,
+                &unit.target,
+            );
+        }
+    }
+}
+
+impl FromStr for Tmx {
+    type Err = Error;
+
+    /// A `<tu>` with several `<tuv>` gives a unit for each language that is not the source
+    /// one. When the source language is `*all*` every pair of languages is a unit
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = |position: usize| s[..position].lines().count().max(1);
+        let unclosed = |tag: &super::xml::Tag| Error::Parse {
+            line: line(tag.range.start),
+            message: format!("unclosed <{}>", tag.name),
+        };
+
+        let header = std::iter::successors(next_tag(s, 0), |tag| next_tag(s, tag.range.end))
+            .find(|tag| tag.name == "header" && !tag.closing)
+            .ok_or(Error::Parse {
+                line: 1,
+                message: "missing <header>".into(),
+            })?;
+        let mut tmx = Self::new(attribute(header.raw(s), "srclang").unwrap_or("*all*"));
+        let mut from = header.range.end;
+
+        while let Some(tu) = next_tag(s, from) {
+            from = tu.range.end;
+            if tu.name != "tu" || tu.closing || tu.empty {
+                continue;
+            }
+
+            let close = closing_tag(s, &tu).ok_or_else(|| unclosed(&tu))?;
+            let source_language = attribute(tu.raw(s), "srclang").unwrap_or(&tmx.source_language);
+            let mut unit = Unit {
+                creation_date: attribute(tu.raw(s), "creationdate").map(String::from),
+                ..Unit::default()
+            };
+            let mut variants = Vec::new();
+            let body = &s[..close.range.start];
+            let mut inner_from = tu.range.end;
+
+            while let Some(tag) = next_tag(body, inner_from) {
+                inner_from = tag.range.end;
+                if tag.closing || tag.empty {
+                    continue;
+                }
+
+                let tag_close = closing_tag(body, &tag).ok_or_else(|| unclosed(&tag))?;
+                let content = &s[tag.range.end..tag_close.range.start];
+                match tag.name {
+                    "prop" => {
+                        let kind = attribute(tag.raw(s), "type").unwrap_or_default();
+                        unit.properties.push((kind.to_string(), unescape(content)));
+                    }
+                    "tuv" => {
+                        let language = attribute(tag.raw(s), "xml:lang")
+                            .or_else(|| attribute(tag.raw(s), "lang"))
+                            .unwrap_or_default();
+                        let seg = next_tag(content, 0)
+                            .filter(|seg| seg.name == "seg")
+                            .and_then(|seg| {
+                                let seg_close = closing_tag(content, &seg)?;
+                                Some(&content[seg.range.end..seg_close.range.start])
+                            });
+
+                        if let Some(seg) = seg {
+                            variants.push((language.to_string(), segment_text(seg)));
+                        }
+                    }
+                    _ => {}
+                }
+
+                inner_from = tag_close.range.end;
+            }
+
+            for (source_language, source) in variants.iter().filter(|(language, _)| {
+                source_language == "*all*" || language.eq_ignore_ascii_case(source_language)
+            }) {
+                for (target_language, target) in &variants {
+                    if target_language == source_language {
+                        continue;
+                    }
+
+                    tmx.units.push(Unit {
+                        source_language: source_language.clone(),
+                        source: source.clone(),
+                        target_language: target_language.clone(),
+                        target: target.clone(),
+                        ..unit.clone()
+                    });
+                }
+            }
+
+            from = close.range.end;
+        }
+
+        Ok(tmx)
+    }
+}
+
+impl fmt::Display for Tmx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attribute = |value: &str| escape(value).replace('"', "&quot;");
+
+        writeln!(f, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(f, r#"<tmx version="1.4">"#)?;
+        writeln!(
+            f,
+            r#"  <header creationtool="deep-translator" creationtoolversion="{}" segtype="sentence" o-tmf="deep-translator" adminlang="en" srclang="{}" datatype="plaintext"/>"#,
+            env!("CARGO_PKG_VERSION"),
+            attribute(&self.source_language)
+        )?;
+        writeln!(f, "  <body>")?;
+
+        for unit in &self.units {
+            match &unit.creation_date {
+                Some(date) => writeln!(f, r#"    <tu creationdate="{}">"#, attribute(date))?,
+                None => writeln!(f, "    <tu>")?,
+            }
+
+            for (kind, value) in &unit.properties {
+                writeln!(
+                    f,
+                    r#"      <prop type="{}">{}</prop>"#,
+                    attribute(kind),
+                    escape(value)
+                )?;
+            }
+
+            for (language, text) in [
+                (&unit.source_language, &unit.source),
+                (&unit.target_language, &unit.target),
+            ] {
+                writeln!(
+                    f,
+                    r#"      <tuv xml:lang="{}"><seg>{}</seg></tuv>"#,
+                    attribute(language),
+                    escape(text)
+                )?;
+            }
+
+            writeln!(f, "    </tu>")?;
+        }
+
+        writeln!(f, "  </body>")?;
+        writeln!(f, "</tmx>")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header creationtool="cat" segtype="sentence" o-tmf="cat" adminlang="en" srclang="en-US" datatype="html"/>
+  <body>
+    <tu creationdate="20240131T094500Z">
+      <prop type="x-client">Acme</prop>
+      <tuv xml:lang="en-US"><seg>Click <bpt i="1">&lt;b&gt;</bpt>Save<ept i="1">&lt;/b&gt;</ept> &amp; quit</seg></tuv>
+      <tuv xml:lang="de-DE"><seg>Klicke <bpt i="1">&lt;b&gt;</bpt>Speichern<ept i="1">&lt;/b&gt;</ept> &amp; beende</seg></tuv>
+      <tuv xml:lang="fr-FR"><seg>Clique <ph>&lt;br/&gt;</ph>ici</seg></tuv>
+    </tu>
+    <tu srclang="*all*">
+      <tuv lang="en"><seg>Yes</seg></tuv>
+      <tuv lang="it"><seg>Sì</seg></tuv>
+    </tu>
+  </body>
+</tmx>"#;
+
+    #[test]
+    fn timestamps() {
+        assert_eq!(timestamp(UNIX_EPOCH), "19700101T000000Z");
+        let time = UNIX_EPOCH + Duration::from_secs(1_706_694_300);
+        assert_eq!(timestamp(time), "20240131T094500Z");
+        let leap_day = UNIX_EPOCH + Duration::from_secs(951_782_399);
+        assert_eq!(timestamp(leap_day), "20000228T235959Z");
+        assert_eq!(
+            timestamp(leap_day + Duration::from_secs(1)),
+            "20000229T000000Z"
+        );
+    }
+
+    #[test]
+    fn units_are_imported() {
+        let tmx: Tmx = TMX.parse().unwrap();
+        let pairs = tmx
+            .units
+            .iter()
+            .map(|unit| (unit.source_language.as_str(), unit.target_language.as_str()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(tmx.source_language, "en-US");
+        assert_eq!(
+            pairs,
+            [
+                ("en-US", "de-DE"),
+                ("en-US", "fr-FR"),
+                ("en", "it"),
+                ("it", "en")
+            ]
+        );
+        assert_eq!(tmx.units[0].source, "Click <b>Save</b> & quit");
+        assert_eq!(tmx.units[0].target, "Klicke <b>Speichern</b> & beende");
+        assert_eq!(tmx.units[1].target, "Clique <br/>ici");
+        assert_eq!(tmx.units[0].property("x-client"), Some("Acme"));
+        assert_eq!(
+            tmx.units[0].creation_date.as_deref(),
+            Some("20240131T094500Z")
+        );
+        assert_eq!(tmx.units[2].creation_date, None);
+
+        let memory = tmx.memory();
+        assert_eq!(memory.len(), 4);
+        assert_eq!(
+            memory.get("en", "de", "Click <b>Save</b> & quit"),
+            Some("Klicke <b>Speichern</b> & beende")
+        );
+        assert_eq!(memory.get("it", "en", "Sì"), Some("Yes"));
+
+        assert!(matches!(
+            "<tmx></tmx>".parse::<Tmx>(),
+            Err(Error::Parse { line: 1, .. })
+        ));
+        let unclosed = TMX.replace("    </tu>\n    <tu srclang", "    <tu srclang");
+        assert!(matches!(
+            unclosed.parse::<Tmx>(),
+            Err(Error::Parse { line: 5, message }) if message == "unclosed <tu>"
+        ));
+    }
+
+    #[test]
+    fn units_are_exported() {
+        let translator = Translator::with_engine("en", "de", crate::Engine::Google);
+        let mut tmx = Tmx::new("en");
+        let time = UNIX_EPOCH + Duration::from_secs(1_706_694_300);
+        tmx.push(&translator, "Fish & \"chips\"", "Fisch & Pommes", time);
+
+        let unit = &tmx.units[0];
+        assert_eq!(
+            unit.property(ENGINE_PROPERTY),
+            Some(translator.engine.name())
+        );
+        assert_eq!(unit.property(TIMESTAMP_PROPERTY), Some("20240131T094500Z"));
+
+        let exported = tmx.to_string();
+        assert!(exported.contains(r#"<tuv xml:lang="en"><seg>Fish &amp; "chips"</seg></tuv>"#));
+        assert_eq!(exported.parse::<Tmx>().unwrap(), tmx);
+    }
+}
@@ -0,0 +1,450 @@
+//! XLIFF 1.2 and 2.0 files
+//!
+//! The `<source>` of each unit is translated and written in a `<target>` that is marked for
+//! review: `state="needs-review-translation"` in XLIFF 1.2, and a `translated` segment with
+//! that sub-state in XLIFF 2.0. The rest of the file is kept byte by byte. Inline tags are
+//! kept in the translation, the text inside `<g>`, `<mrk>` and `<pc>` is translated but the
+//! native code of `<ph>`, `<bpt>`, `<ept>` and `<it>` is not.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::xliff::Document, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let mut document = Document::open("messages.de.xlf")?;
+//!
+//!     let report = document.translate(&translator, false).await;
+//!     println!("{} units translated", report.translated);
+//!
+//!     document.save("messages.de.xlf")
+//! }
+//!```
+
+use super::{
+    xml::{attribute, closing_tag, escape, mask, next_tag, set_attribute, unescape, unmask},
+    Report,
+};
+use crate::{Error, Translator};
+use futures::StreamExt;
+use std::{fmt, ops::Range, path::Path, str::FromStr};
+
+/// State of the targets written by the translator
+pub const REVIEW_STATE: &str = "needs-review-translation";
+
+/// Elements whose content is native code, it is never translated
+const NATIVE_CODE_ELEMENTS: [&str; 4] = ["ph", "bpt", "ept", "it"];
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub enum Version {
+    V1_2,
+    V2_0,
+}
+
+/// A translation unit, the source and target are the inner XML of the elements
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Unit {
+    pub id: String,
+    pub source: String,
+    pub target: Option<String>,
+    /// Units marked with `translate="no"`
+    pub translate: bool,
+}
+
+/// Position of a unit in the file
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Span {
+    unit: Unit,
+    /// Opening tag of the `<trans-unit>` or `<segment>`
+    open: Range<usize>,
+    /// Content of the `<source>`
+    source: Range<usize>,
+    /// Where a new target is inserted, after `</source>`
+    source_end: usize,
+    /// The whole `<target>` element
+    target: Option<Range<usize>>,
+}
+
+/// An XLIFF file
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Document {
+    text: String,
+    version: Version,
+    spans: Vec<Span>,
+}
+
+impl Document {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    #[inline(always)]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        Ok(std::fs::write(path, &self.text)?)
+    }
+
+    #[inline(always)]
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// The translation units, the segments in XLIFF 2.0
+    pub fn units(&self) -> impl Iterator<Item = &Unit> {
+        self.spans.iter().map(|span| &span.unit)
+    }
+
+    /// Translate the sources of the units and write the targets for review. The units
+    /// that already have a target are left as they are, unless `overwrite` is set
+    pub async fn translate(&mut self, translator: &Translator, overwrite: bool) -> Report {
+        let pending = self
+            .spans
+            .iter()
+            .filter(|span| span.unit.translate)
+            .filter(|span| {
+                overwrite
+                    || span
+                        .unit
+                        .target
+                        .as_ref()
+                        .is_none_or(|t| t.trim().is_empty())
+            })
+            .map(|span| {
+                let content = &self.text[span.source.clone()];
+                let inline = mask(
+                    content,
+                    &translator.placeholders,
+                    &NATIVE_CODE_ELEMENTS,
+                    unescape,
+                );
+                (span, inline)
+            })
+            .filter(|(_, (masked, _))| masked.has_text())
+            .collect::<Vec<_>>();
+
+        let translations = futures::stream::iter(
+            pending
+                .iter()
+                .map(|(_, (masked, _))| translator.translate_masked_text(masked)),
+        )
+        .buffered(translator.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        let newline = match self.text.contains("\r\n") {
+            true => "\r\n",
+            false => "\n",
+        };
+
+        let mut report = Report::default();
+        let mut edits = Vec::new();
+        let mut translated = Vec::new();
+
+        for ((span, (_, tags)), translation) in pending.iter().zip(translations) {
+            let Some(translation) = report.record(&span.unit.source, translation) else {
+                continue;
+            };
+            translated.push(&span.unit.source);
+
+            let content = unmask(&translation, tags, escape);
+
+            let open = match &span.target {
+                Some(target) => next_tag(&self.text, target.start)
+                    .unwrap()
+                    .raw(&self.text)
+                    .to_string(),
+                None => "<target>".to_string(),
+            };
+            let open = match self.version {
+                Version::V1_2 => set_attribute(&open, "state", REVIEW_STATE),
+                Version::V2_0 => open,
+            };
+            let open = match open.strip_suffix("/>") {
+                Some(head) => format!("{}>", head.trim_end()),
+                None => open,
+            };
+            let target = format!("{open}{content}</target>");
+
+            match &span.target {
+                Some(range) => edits.push((range.clone(), target)),
+                None => {
+                    // The target goes on its own line, with the indentation of the source
+                    let line_start = self.text[..span.source.start]
+                        .rfind('\n')
+                        .map_or(0, |i| i + 1);
+                    let source_open = self.text[..span.source.start]
+                        .rfind("<source")
+                        .unwrap_or(line_start);
+                    let indent = &self.text[line_start..source_open];
+                    let target = match indent.trim().is_empty() && line_start > 0 {
+                        true => format!("{newline}{indent}{target}"),
+                        false => target,
+                    };
+                    edits.push((span.source_end..span.source_end, target));
+                }
+            }
+
+            if self.version == Version::V2_0 {
+                let open = &self.text[span.open.clone()];
+                let open = set_attribute(open, "state", "translated");
+                let open = set_attribute(
+                    &open,
+                    "subState",
+                    &format!("deep-translator:{REVIEW_STATE}"),
+                );
+                edits.push((span.open.clone(), open));
+            }
+        }
+
+        // The language of the targets, when the file does not have it
+        let (element, name) = match self.version {
+            Version::V1_2 => ("file", "target-language"),
+            Version::V2_0 => ("xliff", "trgLang"),
+        };
+        let mut from = 0;
+        while let Some(tag) = next_tag(&self.text, from) {
+            from = tag.range.end;
+            if tag.name == element && !tag.closing && attribute(tag.raw(&self.text), name).is_none()
+            {
+                edits.push((
+                    tag.range.clone(),
+                    set_attribute(tag.raw(&self.text), name, &translator.target),
+                ));
+            }
+        }
+
+        // From the end, so the positions of the other edits stay valid
+        edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+        let mut text = self.text.clone();
+        for (range, replacement) in edits {
+            text.replace_range(range, &replacement);
+        }
+
+        // The file is left as it was when the edits break it, the units are then failures
+        match text.parse() {
+            Ok(document) => *self = document,
+            Err(err) => {
+                let (line, message) = match err {
+                    Error::Parse { line, message } => (line, message),
+                    err => (1, err.to_string()),
+                };
+                report.translated -= translated.len();
+                report.failed.extend(translated.into_iter().map(|source| {
+                    let message = format!("the translated file is not valid: {message}");
+                    (source.clone(), Error::Parse { line, message })
+                }));
+            }
+        }
+
+        report
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let line = |position: usize| s[..position].lines().count().max(1);
+        let mut version = None;
+        let mut spans = Vec::new();
+        // The unit of XLIFF 2.0, the segments take its id and `translate`
+        let mut unit = (String::new(), true);
+        let mut from = 0;
+
+        while let Some(tag) = next_tag(s, from) {
+            from = tag.range.end;
+            if tag.closing {
+                continue;
+            }
+
+            let raw = tag.raw(s);
+            match (tag.name, version) {
+                ("xliff", _) => {
+                    version = match attribute(raw, "version") {
+                        Some(v) if v.starts_with('1') => Some(Version::V1_2),
+                        Some(v) if v.starts_with('2') => Some(Version::V2_0),
+                        v => {
+                            return Err(Error::Parse {
+                                line: line(tag.range.start),
+                                message: format!("unsupported XLIFF version {v:?}"),
+                            })
+                        }
+                    }
+                }
+                ("unit", Some(Version::V2_0)) => {
+                    let id = attribute(raw, "id").unwrap_or_default().to_string();
+                    unit = (id, attribute(raw, "translate") != Some("no"));
+                }
+                ("trans-unit", Some(Version::V1_2)) | ("segment", Some(Version::V2_0)) => {
+                    let Some(close) = closing_tag(s, &tag) else {
+                        return Err(Error::Parse {
+                            line: line(tag.range.start),
+                            message: format!("unclosed <{}>", tag.name),
+                        });
+                    };
+
+                    let (id, translate) = match version {
+                        Some(Version::V1_2) => (
+                            attribute(raw, "id").unwrap_or_default().to_string(),
+                            attribute(raw, "translate") != Some("no"),
+                        ),
+                        _ => unit.clone(),
+                    };
+
+                    // The source and target of the unit, not the ones of `<alt-trans>`
+                    let mut source = None;
+                    let mut target = None;
+                    let mut child_from = tag.range.end;
+                    while let Some(child) = next_tag(&s[..close.range.start], child_from) {
+                        child_from = child.range.end;
+                        if child.closing || (child.empty && child.name != "target") {
+                            continue;
+                        }
+
+                        let end = match child.empty {
+                            true => None,
+                            false => closing_tag(s, &child),
+                        };
+                        let content = end
+                            .as_ref()
+                            .map_or(child.range.end..child.range.end, |end| {
+                                child.range.end..end.range.start
+                            });
+                        let element_end = end.as_ref().map_or(child.range.end, |end| end.range.end);
+                        child_from = element_end;
+
+                        match child.name {
+                            "source" => source = Some((content, element_end)),
+                            "target" => target = Some((child.range.start..element_end, content)),
+                            _ => {}
+                        }
+                    }
+
+                    let Some((source, source_end)) = source else {
+                        return Err(Error::Parse {
+                            line: line(tag.range.start),
+                            message: format!("<{}> without <source>", tag.name),
+                        });
+                    };
+
+                    spans.push(Span {
+                        unit: Unit {
+                            id,
+                            source: s[source.clone()].to_string(),
+                            target: target
+                                .as_ref()
+                                .map(|(_, content)| s[content.clone()].to_string()),
+                            translate,
+                        },
+                        open: tag.range.clone(),
+                        source,
+                        source_end,
+                        target: target.map(|(element, _)| element),
+                    });
+                    from = close.range.end;
+                }
+                _ => {}
+            }
+        }
+
+        let Some(version) = version else {
+            return Err(Error::Parse {
+                line: 1,
+                message: "not an XLIFF file, <xliff> is missing".into(),
+            });
+        };
+
+        Ok(Self {
+            text: s.to_string(),
+            version,
+            spans,
+        })
+    }
+}
+
+impl fmt::Display for Document {
+    #[inline(always)]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.text.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    #[tokio::test]
+    async fn targets_are_inserted_for_review() {
+        let xliff = "<xliff version=\"1.2\">\n<file source-language=\"en\">\n<body>\n\
+            <trans-unit id=\"1\">\n  <source>Hello</source>\n</trans-unit>\n\
+            <trans-unit id=\"2\">\n  <source>Goodbye</source>\n  <target>Tschau</target>\n</trans-unit>\n\
+            <trans-unit id=\"3\" translate=\"no\">\n  <source>Hello</source>\n</trans-unit>\n\
+            </body>\n</file>\n</xliff>\n";
+        let mut document = xliff.parse::<Document>().unwrap();
+        let translator = translator(&[("Hello", "Hallo"), ("Goodbye", "Tschüss")]);
+
+        let report = document.translate(&translator, false).await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(
+            document.to_string(),
+            "<xliff version=\"1.2\">\n<file source-language=\"en\" target-language=\"de\">\n<body>\n\
+            <trans-unit id=\"1\">\n  <source>Hello</source>\n  \
+            <target state=\"needs-review-translation\">Hallo</target>\n</trans-unit>\n\
+            <trans-unit id=\"2\">\n  <source>Goodbye</source>\n  <target>Tschau</target>\n</trans-unit>\n\
+            <trans-unit id=\"3\" translate=\"no\">\n  <source>Hello</source>\n</trans-unit>\n\
+            </body>\n</file>\n</xliff>\n"
+        );
+
+        let report = document.translate(&translator, true).await;
+        assert_eq!(report.translated, 2);
+        let targets = document.units().map(|unit| unit.target.as_deref());
+        assert_eq!(
+            targets.collect::<Vec<_>>(),
+            [Some("Hallo"), Some("Tschüss"), None]
+        );
+    }
+
+    #[tokio::test]
+    async fn inline_tags_are_kept() {
+        let xliff = "<xliff version=\"1.2\"><file><body><trans-unit id=\"1\">\
+            <source>Fish &amp; <g id=\"1\">chips</g><x id=\"2\"/> <ph id=\"3\">&lt;br&gt;</ph></source>\
+            </trans-unit></body></file></xliff>";
+        let mut document = xliff.parse::<Document>().unwrap();
+        let translator = translator(&[(
+            "Fish & \u{e000}chips\u{e001}\u{e002} \u{e003}",
+            "\u{e000}Pommes\u{e001} & Fisch\u{e002} \u{e003}",
+        )]);
+
+        let report = document.translate(&translator, false).await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(
+            document.units().next().unwrap().target.as_deref(),
+            Some("<g id=\"1\">Pommes</g> &amp; Fisch<x id=\"2\"/> <ph id=\"3\">&lt;br&gt;</ph>")
+        );
+    }
+
+    #[tokio::test]
+    async fn segments_of_xliff_2() {
+        let xliff = "<xliff version=\"2.0\" srcLang=\"en\"><file id=\"f\">\
+            <unit id=\"u1\"><segment><source>Hello</source></segment></unit>\
+            <unit id=\"u2\" translate=\"no\"><segment><source>Hello</source></segment></unit>\
+            </file></xliff>";
+        let mut document = xliff.parse::<Document>().unwrap();
+        assert_eq!(document.version(), Version::V2_0);
+
+        let report = document
+            .translate(&translator(&[("Hello", "Hallo")]), false)
+            .await;
+        assert_eq!(report.translated, 1);
+        assert_eq!(
+            document.to_string(),
+            "<xliff version=\"2.0\" srcLang=\"en\" trgLang=\"de\"><file id=\"f\">\
+            <unit id=\"u1\"><segment state=\"translated\" \
+            subState=\"deep-translator:needs-review-translation\">\
+            <source>Hello</source><target>Hallo</target></segment></unit>\
+            <unit id=\"u2\" translate=\"no\"><segment><source>Hello</source></segment></unit>\
+            </file></xliff>"
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! A minimal XML scanner, the files are edited in place so everything that is not
+//! translated is kept byte by byte
+
+use crate::{translator::mask::Masked, PlaceholderSyntax};
+use std::ops::Range;
+
+/// A tag of the file, comments, processing instructions and CDATA sections are skipped
+pub(crate) struct Tag<'a> {
+    pub range: Range<usize>,
+    pub name: &'a str,
+    pub closing: bool,
+    pub empty: bool,
+}
+
+impl Tag<'_> {
+    #[inline(always)]
+    pub fn raw<'t>(&self, text: &'t str) -> &'t str {
+        &text[self.range.clone()]
+    }
+}
+
+/// Length of the comment, processing instruction, CDATA section or doctype at the start
+/// of `text`
+fn markup_declaration(text: &str) -> Option<usize> {
+    let (open, close) = [
+        ("<!--", "-->"),
+        ("<![CDATA[", "]]>"),
+        ("<?", "?>"),
+        ("<!", ">"),
+    ]
+    .into_iter()
+    .find(|(open, _)| text.starts_with(open))?;
+
+    let len = text[open.len()..]
+        .find(close)
+        .map_or(text.len(), |end| open.len() + end + close.len());
+    Some(len)
+}
+
+/// The next tag from `from`
+pub(crate) fn next_tag(text: &str, mut from: usize) -> Option<Tag<'_>> {
+    loop {
+        let start = from + text[from..].find('<')?;
+        let rest = &text[start..];
+
+        if let Some(len) = markup_declaration(rest) {
+            from = start + len;
+            continue;
+        }
+
+        // Quoted attributes may contain `>`
+        let mut quote = None;
+        let len = rest.char_indices().find_map(|(index, c)| {
+            match (quote, c) {
+                (None, '"' | '\'') => quote = Some(c),
+                (Some(q), c) if q == c => quote = None,
+                (None, '>') => return Some(index + 1),
+                _ => {}
+            }
+            None
+        })?;
+
+        let inner = rest[1..len - 1].trim_end();
+        let closing = inner.starts_with('/');
+        let empty = inner.ends_with('/');
+        let inner = inner.trim_start_matches('/');
+        let name_len = inner
+            .find(|c: char| c.is_whitespace() || c == '/')
+            .unwrap_or(inner.len());
+
+        return Some(Tag {
+            range: start..start + len,
+            name: &inner[..name_len],
+            closing,
+            empty,
+        });
+    }
+}
+
+/// Closing tag of the element opened by `open`, elements with the same name can be nested
+pub(crate) fn closing_tag<'t>(text: &'t str, open: &Tag) -> Option<Tag<'t>> {
+    let mut depth = 0;
+    let mut from = open.range.end;
+
+    while let Some(tag) = next_tag(text, from) {
+        from = tag.range.end;
+        if tag.name != open.name || tag.empty {
+            continue;
+        }
+
+        if !tag.closing {
+            depth += 1;
+        } else if depth == 0 {
+            return Some(tag);
+        } else {
+            depth -= 1;
+        }
+    }
+
+    None
+}
+
+/// Value of an attribute of a tag
+pub(crate) fn attribute<'t>(tag: &'t str, name: &str) -> Option<&'t str> {
+    let mut from = 0;
+
+    while let Some(index) = tag[from..].find(name).map(|index| from + index) {
+        from = index + name.len();
+        let before = tag[..index].chars().next_back();
+        let after = tag[from..].trim_start();
+
+        if !before.is_some_and(char::is_whitespace) || !after.starts_with('=') {
+            continue;
+        }
+
+        let value = after[1..].trim_start();
+        let quote = value.chars().next()?;
+        return value[1..].find(quote).map(|end| &value[1..end + 1]);
+    }
+
+    None
+}
+
+/// Set the value of an attribute, it is added when it is missing
+pub(crate) fn set_attribute(tag: &str, name: &str, value: &str) -> String {
+    if let Some(old) = attribute(tag, name) {
+        let start = old.as_ptr() as usize - tag.as_ptr() as usize;
+        return format!("{}{value}{}", &tag[..start], &tag[start + old.len()..]);
+    }
+
+    let end = match tag.ends_with("/>") {
+        true => tag.len() - 2,
+        false => tag.len() - 1,
+    };
+    let head = tag[..end].trim_end();
+
+    format!("{head} {name}=\"{value}\"{}", &tag[head.len()..])
+}
+
+pub(crate) fn unescape(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let entity = rest.find(';').map(|end| &rest[1..end]);
+        let c = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "amp" => Some('&'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+
+        match (c, entity) {
+            (Some(c), Some(entity)) => {
+                unescaped.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    unescaped.push_str(rest);
+    unescaped
+}
+
+#[inline(always)]
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Character that stands for an inline tag while the text is translated, so the escaping
+/// of the translation does not change the tag
+#[inline(always)]
+fn sentinel(index: usize) -> String {
+    char::from_u32(0xE000 + index as u32)
+        .unwrap_or(char::REPLACEMENT_CHARACTER)
+        .to_string()
+}
+
+/// Split the content of an element in text and inline tags. The content of the elements
+/// in `native_code`, or with `translate="no"`, is kept as it is. `decode` turns the text
+/// between the tags into plain text
+pub(crate) fn mask(
+    content: &str,
+    placeholders: &[PlaceholderSyntax],
+    native_code: &[&str],
+    decode: impl Fn(&str) -> String,
+) -> (Masked, Vec<String>) {
+    let mut masked = Masked::new(placeholders);
+    let mut tags = Vec::new();
+    let mut from = 0;
+
+    while let Some(start) = content[from..].find('<').map(|start| from + start) {
+        masked.push_text(&decode(&content[from..start]));
+
+        // Comments and CDATA sections are kept as they are too
+        let end = match next_tag(content, start) {
+            _ if content[start..].starts_with("<!") || content[start..].starts_with("<?") => {
+                start + markup_declaration(&content[start..]).unwrap_or(1)
+            }
+            Some(tag) if tag.range.start == start => {
+                let untranslatable = native_code.contains(&tag.name)
+                    || attribute(tag.raw(content), "translate") == Some("no")
+                    || attribute(tag.raw(content), "mtype") == Some("protected");
+
+                match closing_tag(content, &tag) {
+                    Some(close) if untranslatable && !tag.closing && !tag.empty => close.range.end,
+                    _ => tag.range.end,
+                }
+            }
+            _ => content.len(),
+        };
+
+        masked.push_protected(&sentinel(tags.len()));
+        tags.push(content[start..end].to_string());
+        from = end;
+    }
+
+    masked.push_text(&decode(&content[from..]));
+    (masked, tags)
+}
+
+/// Put the inline tags back in the translation, `encode` escapes the text between them
+pub(crate) fn unmask(
+    translation: &str,
+    tags: &[String],
+    encode: impl Fn(&str) -> String,
+) -> String {
+    let mut content = encode(translation);
+    for (index, tag) in tags.iter().enumerate() {
+        content = content.replace(&sentinel(index), tag);
+    }
+
+    content
+}
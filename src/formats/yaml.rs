@@ -0,0 +1,583 @@
+//! YAML locale files, as used by Rails and Symfony
+//!
+//! The file is rewritten line by line: the comments, anchors, aliases, tags and the layout
+//! of the keys are kept, only the string values are translated. The top-level key that is
+//! the source language, `en:`, is renamed to the target language. Block scalars (`|` and
+//! `>`) stay block scalars, the lines of a literal one are translated one by one and the
+//! paragraphs of a folded one as a whole. Set [`Translator::placeholders`] to protect the
+//! interpolations, e.g. [`crate::PlaceholderSyntax::Ruby`] for `%{count}`.
+//!
+//!```rust,no_run
+//! use deeptranslator::{formats::yaml::Document, Engine, Translator};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), deeptranslator::Error> {
+//!     let translator = Translator::with_engine("en", "de", Engine::Google);
+//!     let document = Document::open("config/locales/en.yml")?;
+//!
+//!     let (translation, report) = document.translate(&translator).await;
+//!     println!("{} strings translated", report.translated);
+//!
+//!     Ok(std::fs::write("config/locales/de.yml", translation)?)
+//! }
+//!```
+
+use super::Report;
+use crate::{translator::html::first_text, Error, Translator};
+use std::{fmt, path::Path, str::FromStr};
+
+/// A YAML file split in the parts that are kept and the scalars that are translated
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Document {
+    parts: Vec<Part>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Part {
+    Verbatim(String),
+    /// A top-level key, it may be the locale of the file
+    RootKey(String),
+    Scalar {
+        /// The scalar as it is written in the file
+        raw: String,
+        /// The value of the scalar
+        text: String,
+        style: Style,
+    },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum Style {
+    Plain,
+    SingleQuoted,
+    DoubleQuoted,
+    /// A line or paragraph of a block scalar
+    Block,
+}
+
+#[inline(always)]
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+/// Remove the line break of a line
+#[inline(always)]
+fn content(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+/// Plain scalars that are not text: numbers, booleans and nulls
+fn is_literal(value: &str) -> bool {
+    let keywords = ["true", "false", "yes", "no", "on", "off", "null", "~"];
+    keywords.contains(&value.to_lowercase().as_str())
+        || value.parse::<f64>().is_ok()
+        || !value.chars().any(char::is_alphabetic)
+}
+
+/// End of the quoted scalar that starts at the beginning of `text`
+fn closing_quote(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut chars = text.char_indices().skip(1).peekable();
+
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '\\' if quote == '"' => {
+                chars.next();
+            }
+            '\'' if quote == '\'' && chars.peek().is_some_and(|(_, next)| *next == '\'') => {
+                chars.next();
+            }
+            c if c == quote => return Some(index),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Join the lines of a multi-line flow scalar, an empty line is a line break
+fn fold(text: &str) -> String {
+    let mut folded = String::new();
+    let mut lines = text.split('\n').map(str::trim).peekable();
+
+    while let Some(line) = lines.next() {
+        folded.push_str(line);
+        if lines.peek().is_some() {
+            match line.is_empty() {
+                true => folded.push('\n'),
+                false if lines.peek().is_some_and(|next| !next.is_empty()) => folded.push(' '),
+                false => {}
+            }
+        }
+    }
+
+    folded
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('0') => unescaped.push('\0'),
+            Some('u') => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                match u32::from_str_radix(&code, 16).ok().and_then(char::from_u32) {
+                    Some(c) => unescaped.push(c),
+                    None => unescaped.push_str(&format!("\\u{code}")),
+                }
+            }
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+
+    unescaped
+}
+
+fn double_quoted(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+
+    format!("\"{escaped}\"")
+}
+
+/// Whether the text can be written as a plain scalar
+fn is_plain(text: &str) -> bool {
+    !text.is_empty()
+        && !text.starts_with(|c: char| c.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`".contains(c))
+        && !text.ends_with(|c: char| c.is_whitespace() || c == ':')
+        && !text.contains(": ")
+        && !text.contains(" #")
+        && !text.contains(['\n', '\t'])
+        && !is_literal(text)
+}
+
+impl Style {
+    /// Write a translation in the style of the original scalar
+    fn encode(&self, text: &str) -> String {
+        match self {
+            Style::Plain if is_plain(text) => text.to_string(),
+            Style::Plain | Style::DoubleQuoted => double_quoted(text),
+            Style::SingleQuoted if text.contains('\n') => double_quoted(text),
+            Style::SingleQuoted => format!("'{}'", text.replace('\'', "''")),
+            Style::Block => text.replace('\n', " "),
+        }
+    }
+}
+
+/// Where the key and the value of a line start, after the indentation and the `- ` of
+/// the sequences, and the column of the node of the line: the one of its key, or of its
+/// last `-`. The key is `None` for the items of a sequence
+fn entry(content: &str) -> (Option<(usize, usize)>, usize, usize) {
+    let mut start = indentation(content);
+    let mut column = start;
+    while content[start..] == *"-" || content[start..].starts_with("- ") {
+        column = start;
+        start += 1 + indentation(&content[start + 1..]);
+    }
+
+    let rest = &content[start..];
+    let key_len = match rest.chars().next() {
+        Some('"' | '\'') => closing_quote(rest)
+            .map(|end| end + 1)
+            .filter(|end| rest[*end..].trim_start().starts_with(':')),
+        Some('[' | '{' | '&' | '*' | '!' | '|' | '>') | None => None,
+        Some(_) => rest
+            .match_indices(':')
+            .map(|(index, _)| index)
+            .find(|index| rest[index + 1..].is_empty() || rest[index + 1..].starts_with(' ')),
+    };
+
+    let Some(key_len) = key_len else {
+        return (None, start, column);
+    };
+
+    let colon = start + key_len + rest[key_len..].find(':').unwrap_or(0) + 1;
+    let value = colon + indentation(&content[colon..]);
+    (Some((start, start + key_len)), value, start)
+}
+
+/// Whether the line starts a node, a `key:` or an item of a sequence, instead of continuing
+/// a plain scalar
+fn starts_node(line: &str) -> bool {
+    let text = content(line).trim_start();
+    text == "-" || text.starts_with("- ") || text.starts_with('#') || entry(text).0.is_some()
+}
+
+/// Length of the anchors and tags before a value, `&default !!str `
+fn properties(value: &str) -> usize {
+    let mut len = 0;
+
+    while value[len..].starts_with(['&', '!']) {
+        let token = value[len..].find(' ').unwrap_or(value.len() - len);
+        len += token + indentation(&value[len + token..]);
+    }
+
+    len
+}
+
+impl Document {
+    #[inline(always)]
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Translate the string values, the result is the file for the target language of the
+    /// translator. The strings are sent with [`Translator::translate_batch`]
+    pub async fn translate(&self, translator: &Translator) -> (String, Report) {
+        let texts = self
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Scalar { text, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut translations = translator.translate_batch(&texts).await.into_iter();
+        let mut report = Report::default();
+        let mut translation = String::new();
+
+        for part in &self.parts {
+            match part {
+                Part::Verbatim(text) => translation.push_str(text),
+                Part::RootKey(key) if key.trim_matches(['"', '\'']) == translator.source => {
+                    translation.push_str(&key.replace(&translator.source, &translator.target))
+                }
+                Part::RootKey(key) => translation.push_str(key),
+                Part::Scalar { raw, text, style } => {
+                    let result = translations
+                        .next()
+                        .unwrap()
+                        .and_then(|value| first_text(value).ok_or(Error::TranslationNotFound));
+
+                    match report.record(text, result) {
+                        Some(translated) => translation.push_str(&style.encode(&translated)),
+                        None => translation.push_str(raw),
+                    }
+                }
+            }
+        }
+
+        (translation, report)
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.split_inclusive('\n').collect::<Vec<_>>();
+        let offsets = lines
+            .iter()
+            .scan(0, |offset, line| {
+                let start = *offset;
+                *offset += line.len();
+                Some(start)
+            })
+            .collect::<Vec<_>>();
+
+        let mut parts = Vec::new();
+        let mut index = 0;
+
+        while index < lines.len() {
+            let line = lines[index];
+            let text = content(line);
+            let indent = indentation(text);
+            let trimmed = text.trim_start();
+            index += 1;
+
+            let is_directive = ["---", "...", "%"]
+                .iter()
+                .any(|marker| text.starts_with(marker));
+            if trimmed.is_empty() || trimmed.starts_with('#') || is_directive {
+                parts.push(Part::Verbatim(line.to_string()));
+                continue;
+            }
+
+            let (key, mut value_start, column) = entry(text);
+            match key {
+                Some((start, end)) if indent == 0 && start == 0 => {
+                    parts.push(Part::RootKey(text[..end].to_string()));
+                    parts.push(Part::Verbatim(text[end..value_start].to_string()));
+                }
+                _ => parts.push(Part::Verbatim(text[..value_start].to_string())),
+            }
+
+            let properties = properties(&text[value_start..]);
+            parts.push(Part::Verbatim(
+                text[value_start..value_start + properties].to_string(),
+            ));
+            value_start += properties;
+            let value = &text[value_start..];
+
+            // The lines more indented than the key, or the `-`, of this line continue its
+            // value
+            let mut end = index;
+            while end < lines.len()
+                && (lines[end].trim().is_empty() || indentation(lines[end]) > column)
+            {
+                end += 1;
+            }
+            while end > index && lines[end - 1].trim().is_empty() {
+                end -= 1;
+            }
+
+            let eol = &line[text.len()..];
+            match value.chars().next() {
+                None | Some('#') => parts.push(Part::Verbatim(format!("{value}{eol}"))),
+                Some('*' | '[' | '{') => {
+                    parts.push(Part::Verbatim(format!(
+                        "{value}{eol}{}",
+                        lines[index..end].concat()
+                    )));
+                    index = end;
+                }
+                Some(indicator @ ('|' | '>')) => {
+                    parts.push(Part::Verbatim(format!("{value}{eol}")));
+                    let mut block = index;
+
+                    while block < end {
+                        let line = lines[block];
+                        let text = content(line);
+                        block += 1;
+
+                        if text.trim().is_empty() {
+                            parts.push(Part::Verbatim(line.to_string()));
+                            continue;
+                        }
+
+                        // A paragraph of a folded scalar, the lines with the same indentation
+                        let first = block - 1;
+                        let mut last = first;
+                        if indicator == '>' {
+                            while block < end
+                                && !lines[block].trim().is_empty()
+                                && indentation(lines[block]) == indentation(text)
+                            {
+                                last = block;
+                                block += 1;
+                            }
+                        }
+
+                        let start = offsets[first] + indentation(text);
+                        let raw = &s[start..offsets[last] + content(lines[last]).len()];
+                        parts.push(Part::Verbatim(text[..indentation(text)].to_string()));
+                        parts.push(Part::Scalar {
+                            raw: raw.to_string(),
+                            text: raw.split('\n').map(str::trim).collect::<Vec<_>>().join(" "),
+                            style: Style::Block,
+                        });
+                        parts.push(Part::Verbatim(
+                            lines[last][content(lines[last]).len()..].to_string(),
+                        ));
+                    }
+
+                    index = end;
+                }
+                Some(quote @ ('"' | '\'')) => {
+                    let start = offsets[index - 1] + value_start;
+                    let region = &s[start..offsets.get(end).copied().unwrap_or(s.len())];
+                    let Some(close) = closing_quote(region) else {
+                        return Err(Error::Parse {
+                            line: index,
+                            message: "unterminated quoted scalar".into(),
+                        });
+                    };
+
+                    let raw = &region[..=close];
+                    let inner = fold(&raw[1..raw.len() - 1]);
+                    let text = match quote {
+                        '"' => unescape(&inner),
+                        _ => inner.replace("''", "'"),
+                    };
+
+                    // The rest of the line where the scalar ends, e.g. a comment
+                    let last = offsets.partition_point(|offset| *offset <= start + close) - 1;
+                    let rest_end = offsets.get(last + 1).copied().unwrap_or(s.len());
+
+                    if text.chars().any(char::is_alphabetic) {
+                        parts.push(Part::Scalar {
+                            raw: raw.to_string(),
+                            text,
+                            style: match quote {
+                                '"' => Style::DoubleQuoted,
+                                _ => Style::SingleQuoted,
+                            },
+                        });
+                    } else {
+                        parts.push(Part::Verbatim(raw.to_string()));
+                    }
+                    parts.push(Part::Verbatim(s[start + close + 1..rest_end].to_string()));
+                    index = last + 1;
+                }
+                Some(_) => {
+                    // A plain scalar does not continue in a line that starts a node
+                    let end = (index..end)
+                        .find(|line| !lines[*line].trim().is_empty() && starts_node(lines[*line]))
+                        .unwrap_or(end);
+                    let end = (index..end)
+                        .rev()
+                        .find(|line| !lines[*line].trim().is_empty())
+                        .map_or(index, |line| line + 1);
+
+                    // A comment ends the scalar, it can not continue in the next lines
+                    let (raw, rest, last) = match value.find(" #") {
+                        Some(comment) => {
+                            let raw = value[..comment].trim_end();
+                            (raw, &value[raw.len()..], index - 1)
+                        }
+                        None => {
+                            let start = offsets[index - 1] + value_start;
+                            let last = end - 1;
+                            (
+                                &s[start..offsets[last] + content(lines[last]).len()],
+                                "",
+                                last,
+                            )
+                        }
+                    };
+
+                    let text = fold(raw);
+                    if is_literal(&text) {
+                        parts.push(Part::Verbatim(raw.to_string()));
+                    } else {
+                        parts.push(Part::Scalar {
+                            raw: raw.to_string(),
+                            text,
+                            style: Style::Plain,
+                        });
+                    }
+
+                    parts.push(Part::Verbatim(format!(
+                        "{rest}{}",
+                        &lines[last][content(lines[last]).len()..]
+                    )));
+                    index = last + 1;
+                }
+            }
+        }
+
+        Ok(Self { parts })
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                Part::Verbatim(text) | Part::RootKey(text) | Part::Scalar { raw: text, .. } => {
+                    text.fmt(f)?
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    fn scalars(document: &Document) -> Vec<&str> {
+        document
+            .parts
+            .iter()
+            .filter_map(|part| match part {
+                Part::Scalar { text, .. } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sequence_of_mappings() {
+        let yaml = "items:\n  - name: Foo\n    desc: Bar\n  - name: Baz\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(scalars(&document), ["Foo", "Bar", "Baz"]);
+        assert_eq!(document.to_string(), yaml);
+    }
+
+    #[tokio::test]
+    async fn translate_sequence_of_mappings() {
+        let yaml = "en:\n  items:\n    - name: Foo\n      desc: Bar\n";
+        let document = yaml.parse::<Document>().unwrap();
+        let translator = translator(&[("Foo", "Fu"), ("Bar", "Bär")]);
+
+        let (translation, report) = document.translate(&translator).await;
+        assert_eq!(
+            translation,
+            "de:\n  items:\n    - name: Fu\n      desc: Bär\n"
+        );
+        assert_eq!(report.translated, 2);
+    }
+
+    #[test]
+    fn plain_scalar_continuation() {
+        let yaml = "key: first\n  second line\nother: Value\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(scalars(&document), ["first second line", "Value"]);
+        assert_eq!(document.to_string(), yaml);
+    }
+
+    #[test]
+    fn anchors_aliases_and_tags() {
+        let yaml = "defaults: &defaults\n  title: Hello\nother:\n  <<: *defaults\n  name: &name !!str World\n  copy: *name\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(scalars(&document), ["Hello", "World"]);
+        assert_eq!(document.to_string(), yaml);
+    }
+
+    #[test]
+    fn block_scalars() {
+        let yaml = "literal: |\n  First line\n  Second line\nfolded: >-\n  One long\n  paragraph\n\n  Another one\nnext: Text\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(
+            scalars(&document),
+            [
+                "First line",
+                "Second line",
+                "One long paragraph",
+                "Another one",
+                "Text"
+            ]
+        );
+        assert_eq!(document.to_string(), yaml);
+    }
+
+    #[test]
+    fn block_scalar_in_sequence() {
+        let yaml = "- desc: |\n    Some text\n  name: Foo\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(scalars(&document), ["Some text", "Foo"]);
+        assert_eq!(document.to_string(), yaml);
+    }
+
+    #[test]
+    fn flow_values_and_literals() {
+        let yaml = "list: [one, two]\nmap: {a: b,\n  c: d}\ncount: 3\nenabled: true\nquoted: \"Hi \\\"you\\\"\"\n";
+        let document = yaml.parse::<Document>().unwrap();
+
+        assert_eq!(scalars(&document), ["Hi \"you\""]);
+        assert_eq!(document.to_string(), yaml);
+    }
+}
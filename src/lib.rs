@@ -1,4 +1,7 @@
+#[cfg(not(target_arch = "wasm32"))]
+pub mod config;
 pub mod detection;
+pub mod formats;
 mod translator;
 
 use std::{error::Error as StdError, fmt};
@@ -17,14 +20,30 @@ pub enum Error {
     NotValidLength { min: usize, max: usize },
     /// The engine not is in [`crate::translator::engine::Engine`]
     EngineNotSupported(String),
+    /// The engine does not translate from or into the language with this code.
+    LanguageNotSupported(String),
     /// Translation was found for the text provided by the user.
     TranslationNotFound,
+    /// The language of the text could not be detected.
+    DetectionNotFound,
     /// Any reqwest crate error.
     Reqwest(reqwest::Error),
     /// Any cssparser crate error.
     CssParser(String),
     /// Any input and output crate error. Note that it is a placeholder.
     InputOutput(std::io::Error),
+    /// The translator lost or duplicated parts of the text that must be kept as they are,
+    /// e.g. inline code or placeholders.
+    PlaceholderMismatch {
+        missing: Vec<String>,
+        duplicated: Vec<String>,
+    },
+    /// The content of a file is not valid for its format.
+    Parse { line: usize, message: String },
+    /// The configuration is not complete, e.g. a missing profile or api key.
+    Config(String),
+    /// The engine or the language detection needs a key that is not configured.
+    MissingCredentials(String),
 }
 
 impl StdError for Error {}
@@ -44,6 +63,7 @@ impl fmt::Display for Error {
                 "No translation was found using the current translator. Try another translator?"
                     .into()
             }
+            DetectionNotFound => "No language was detected for the text.".into(),
             NotValidLength { min, max } => format!(
                 "Text length need to be between {min} and {max} characters"
             ),
@@ -51,9 +71,20 @@ impl fmt::Display for Error {
                 "Translator {engine} is not supported.\n\
                 Supported translators: `deepl`, `google`, `libre`, `linguee`, `microsoft`, `mymemory`, `papago`, `pons`, `qcri`, `yandex`.",
             ),
+            LanguageNotSupported(code) => format!(
+                "The language `{code}` is not supported by the translator, \
+                run `deep-translator --languages` to see the ones that are."
+            ),
             Reqwest(err) => err.to_string(),
             CssParser(err) => err.clone(),
             InputOutput(err) => err.to_string(),
+            PlaceholderMismatch { missing, duplicated } => format!(
+                "The translator changed the protected parts of the text, \
+                missing: {missing:?}, duplicated: {duplicated:?}"
+            ),
+            Parse { line, message } => format!("Parse error at line {line}: {message}"),
+            Config(message) => format!("Configuration error: {message}"),
+            MissingCredentials(message) => format!("Missing credentials: {message}"),
         }
         .fmt(f)
     }
@@ -70,3 +101,12 @@ impl From<std::io::Error> for Error {
         Error::InputOutput(err)
     }
 }
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Parse {
+            line: err.line(),
+            message: err.to_string(),
+        }
+    }
+}
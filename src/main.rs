@@ -11,232 +11,1761 @@
 //!
 //! Note that
 //!
-use clap::{Arg, ArgAction, Command};
-use deeptranslator::*;
+mod repl;
+mod serve;
+
+use clap::{error::ErrorKind, parser::ValueSource, Arg, ArgAction, ArgMatches, Command};
+use deeptranslator::{config::Config, *};
+use futures::StreamExt;
+use serde_json::Value;
+use std::{
+    io::{IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+/// Exit codes by the kind of the error, clap exits with 2 when the arguments are wrong
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_NETWORK: u8 = 3;
+const EXIT_AUTHENTICATION: u8 = 4;
+const EXIT_QUOTA: u8 = 5;
+const EXIT_LANGUAGE: u8 = 6;
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
-    let matches = clap::Command::new("deep-translator")
-        .bin_name("deep-translator")
-        .about("Official CLI for Deep Translator")
-        .long_about(
-            "Does really amazing things to great people. Now let's talk a little
+async fn main() -> ExitCode {
+    let matches = command().get_matches();
+
+    match run(&matches).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            Stderr::new(&matches).error(&[], &err);
+            ExitCode::from(exit_code(&err))
+        }
+    }
+}
+
+async fn run(matches: &ArgMatches) -> Result<(), Error> {
+    let stderr = Stderr::new(matches);
+    let config = match matches.get_one::<String>("config") {
+        Some(path) => Config::open(path)?,
+        None => Config::load()?,
+    };
+    let profile = config.profile(matches.get_one::<String>("profile").map(String::as_str))?;
+
+    if let Some(("detect", detect_m)) = matches.subcommand() {
+        return detect(matches, detect_m, &profile).await;
+    }
+
+    if let Some(("languages", languages_m)) = matches.subcommand() {
+        return languages(matches, languages_m, &profile);
+    }
+
+    // The languages of the profile replace the defaults of the arguments
+    let from_profile = |id| matches.value_source(id) == Some(ValueSource::DefaultValue);
+    let source = match (&profile.source, from_profile("source")) {
+        (Some(source), true) => source.clone(),
+        _ => matches.get_one::<String>("source").unwrap().clone(),
+    };
+    let targets = match (&profile.target, from_profile("target")) {
+        (Some(target), true) => target
+            .split(',')
+            .map(|target| target.trim().to_string())
+            .collect(),
+        _ => matches
+            .get_many::<String>("target")
+            .unwrap()
+            .cloned()
+            .collect::<Vec<_>>(),
+    };
+
+    let mut translator = Translator::new(&source, &targets[0]);
+
+    translator.text_type = matches
+        .get_one::<String>("text-type")
+        .unwrap()
+        .parse()
+        .unwrap();
+
+    if let Some(placeholders) = matches.get_many::<String>("placeholders") {
+        translator.placeholders = placeholders.map(|syntax| syntax.parse().unwrap()).collect();
+    }
+
+    if let Some(path) = matches.get_one::<String>("glossary") {
+        translator.glossary = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    }
+
+    if let Some(path) = matches.get_one::<String>("tmx") {
+        translator.memory = formats::tmx::Tmx::open(path)?.memory();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        let many = matches.try_get_many::<String>("proxy").ok().flatten();
+        translator.proxies = profile
+            .proxies
+            .iter()
+            .chain(many.into_iter().flatten())
+            .map(reqwest::Proxy::http)
+            .collect::<Result<_, _>>()?;
+    }
+
+    let (engine_matches, file_matches) = match matches.subcommand() {
+        Some((
+            name @ ("po" | "json" | "yaml" | "xliff" | "android" | "apple" | "subtitles" | "csv"
+            | "sync" | "repl" | "serve"),
+            sub_m,
+        )) => (sub_m.subcommand(), Some((name, sub_m))),
+        subcommand => (subcommand, None),
+    };
+
+    let languages = *matches.get_one::<bool>("languages").unwrap();
+
+    // The subcommand wins over --engine, and --engine over the engine of the profile. The
+    // languages are listed without the keys
+    translator.engine = match engine_matches {
+        Some((name, sub_m)) => engine(name, sub_m, &profile)?,
+        None => match matches
+            .get_one::<String>("engine")
+            .or(profile.engine.as_ref())
+        {
+            Some(name) if languages => name.parse()?,
+            Some(name) => profile.engine(name)?,
+            None => Engine::Google,
+        },
+    };
+    for target in targets.iter().filter(|_| !languages) {
+        Translator {
+            target: target.to_string(),
+            ..translator.clone()
+        }
+        .check_languages()?;
+    }
+
+    if let Some(("repl", repl_m)) = file_matches {
+        let history = match repl_m.get_one::<String>("history") {
+            Some(history) => Some(history.into()),
+            None => repl::history_path(),
+        };
+
+        return repl::run(translator, targets, profile, history).await;
+    }
+
+    if let Some(("serve", serve_m)) = file_matches {
+        let options = serve::ServeOptions {
+            address: format!(
+                "{}:{}",
+                serve_m.get_one::<String>("host").unwrap(),
+                serve_m.get_one::<u16>("port").unwrap()
+            ),
+            api_keys: serve_m
+                .get_many::<String>("api-keys")
+                .map(|keys| keys.cloned().collect())
+                .unwrap_or_default(),
+            req_limit: *serve_m.get_one::<usize>("req-limit").unwrap(),
+            cache_size: *serve_m.get_one::<usize>("cache-size").unwrap(),
+            detection_api_key: profile.detection_api_key(),
+        };
+
+        return serve::run(translator, options).await;
+    }
+
+    if let Some(("json", json_m)) = file_matches {
+        let mut resource =
+            formats::json::Resource::open(json_m.get_one::<String>("input").unwrap())?;
+        let key_paths = |name| {
+            json_m
+                .get_many::<String>(name)
+                .map(|paths| paths.cloned().collect())
+                .unwrap_or_default()
+        };
+
+        resource.include = key_paths("include");
+        resource.exclude = key_paths("exclude");
+
+        for (target, (translation, report)) in
+            resource.translate_to_many(&translator, &targets).await
+        {
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", &target), ("text", text)], err);
+            }
+
+            match json_m.get_one::<String>("output") {
+                Some(output) => translation.save(output.replace("{target}", &target))?,
+                None if targets.len() > 1 => println!("{target}: {translation}"),
+                None => println!("{translation}"),
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("sync", sync_m)) = file_matches {
+        let input = sync_m.get_one::<String>("input").unwrap();
+        let mut source = formats::json::Resource::open(input)?;
+        let key_paths = |name| {
+            sync_m
+                .get_many::<String>(name)
+                .map(|paths| paths.cloned().collect())
+                .unwrap_or_default()
+        };
+
+        source.include = key_paths("include");
+        source.exclude = key_paths("exclude");
+
+        let lockfile = match sync_m.get_one::<String>("lockfile") {
+            Some(lockfile) => std::path::PathBuf::from(lockfile),
+            None => std::path::Path::new(input).with_file_name("deep-translator.lock"),
+        };
+        let mut lock = match lockfile.exists() {
+            true => formats::sync::Lockfile::open(&lockfile)?,
+            false => formats::sync::Lockfile::default(),
+        };
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let output = sync_m
+                .get_one::<String>("output")
+                .unwrap()
+                .replace("{target}", target);
+            let previous = match std::path::Path::new(&output).exists() {
+                true => Some(formats::json::Resource::open(&output)?),
+                false => None,
+            };
+
+            let (translation, report) =
+                formats::sync::sync_json(&translator, &source, previous.as_ref(), &mut lock).await;
+
+            for (pointer, err) in &report.failed {
+                stderr.error(&[("target", target), ("pointer", pointer)], err);
+            }
+            for pointer in &report.outdated {
+                stderr.warning(
+                    &[("target", target), ("pointer", pointer)],
+                    "the source changed since it was edited",
+                );
+            }
+            for pointer in &report.stale {
+                stderr.warning(
+                    &[("target", target), ("pointer", pointer)],
+                    "removed from the source",
+                );
+            }
+            stderr.write(
+                "summary",
+                &[("target", target)],
+                None,
+                &format!(
+                    "{} translated, {} unchanged, {} edited by people, {} stale",
+                    report.translated.len(),
+                    report.unchanged.len(),
+                    report.human_edited.len() + report.outdated.len(),
+                    report.stale.len()
+                ),
+            );
+
+            translation.save(output)?;
+        }
+
+        lock.save(lockfile)?;
+        return Ok(());
+    }
+
+    if let Some(("yaml", yaml_m)) = file_matches {
+        let document = formats::yaml::Document::open(yaml_m.get_one::<String>("input").unwrap())?;
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let (translation, report) = document.translate(&translator).await;
+
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            match yaml_m.get_one::<String>("output") {
+                Some(output) => std::fs::write(output.replace("{target}", target), translation)?,
+                None => print!("{translation}"),
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("xliff", xliff_m)) = file_matches {
+        let source = formats::xliff::Document::open(xliff_m.get_one::<String>("input").unwrap())?;
+        let overwrite = *xliff_m.get_one::<bool>("overwrite").unwrap();
+        let output = xliff_m.get_one::<String>("output");
+        if targets.len() > 1 && !output.is_some_and(|output| output.contains("{target}")) {
+            stderr.usage("--output needs `{target}` in it to translate into several languages");
+        }
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let mut document = source.clone();
+            let report = document.translate(&translator, overwrite).await;
+
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            match output {
+                Some(output) => document.save(output.replace("{target}", target))?,
+                None => print!("{document}"),
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("android", android_m)) = file_matches {
+        let input = std::path::Path::new(android_m.get_one::<String>("input").unwrap());
+        let resources = formats::android::Resources::open(input)?;
+        let res = match android_m.get_one::<String>("res") {
+            Some(res) => res.into(),
+            None => input
+                .parent()
+                .and_then(|values| values.parent())
+                .unwrap_or(std::path::Path::new("."))
+                .to_path_buf(),
+        };
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let (translation, report) = resources.translate(&translator).await;
+
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            let folder = formats::android::values_dir(&res, target);
+            std::fs::create_dir_all(&folder)?;
+            std::fs::write(
+                folder.join(input.file_name().unwrap_or_default()),
+                translation,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("apple", apple_m)) = file_matches {
+        let input = std::path::Path::new(apple_m.get_one::<String>("input").unwrap());
+
+        if input
+            .extension()
+            .is_some_and(|extension| extension == "xcstrings")
+        {
+            let mut catalog = formats::apple::StringCatalog::open(input)?;
+
+            for target in &targets {
+                translator.target = target.to_string();
+                for (text, err) in &catalog.translate(&translator).await.failed {
+                    stderr.error(&[("target", target), ("text", text)], err);
+                }
+            }
+
+            return catalog.save(input);
+        }
+
+        let strings = formats::apple::Strings::open(input)?;
+        let dir = match apple_m.get_one::<String>("output-dir") {
+            Some(dir) => dir.into(),
+            None => input
+                .parent()
+                .and_then(|lproj| lproj.parent())
+                .unwrap_or(std::path::Path::new("."))
+                .to_path_buf(),
+        };
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let (translation, report) = strings.translate(&translator).await;
+
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            let folder = formats::apple::lproj_dir(&dir, target);
+            std::fs::create_dir_all(&folder)?;
+            std::fs::write(
+                folder.join(input.file_name().unwrap_or_default()),
+                translation,
+            )?;
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("subtitles", subtitles_m)) = file_matches {
+        let subtitles =
+            formats::subtitles::Subtitles::open(subtitles_m.get_one::<String>("input").unwrap())?;
+        let max_line_length = *subtitles_m.get_one::<usize>("max-line-length").unwrap();
+        let options = formats::subtitles::SubtitleOptions {
+            merge_sentences: *subtitles_m.get_one::<bool>("merge-sentences").unwrap(),
+            max_line_length: (max_line_length > 0).then_some(max_line_length),
+        };
+
+        let output = subtitles_m.get_one::<String>("output");
+        if targets.len() > 1 && !output.is_some_and(|output| output.contains("{target}")) {
+            stderr.usage("--output needs `{target}` in it to translate into several languages");
+        }
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let (translation, report) = subtitles.translate(&translator, &options).await;
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            match output {
+                Some(output) => translation.save(output.replace("{target}", target))?,
+                None => print!("{translation}"),
+            }
+        }
+
+        return Ok(());
+    }
+
+    if let Some(("csv", csv_m)) = file_matches {
+        let input = csv_m.get_one::<String>("input").unwrap();
+        let delimiter = match csv_m.get_one::<String>("delimiter").map(String::as_str) {
+            Some("tab" | "\\t") => '\t',
+            Some(delimiter) => delimiter.chars().next().unwrap_or(','),
+            None if input.ends_with(".tsv") || input.ends_with(".tab") => '\t',
+            None => ',',
+        };
+        let columns = csv_m
+            .get_many::<String>("columns")
+            .unwrap()
+            .filter_map(|column| column.parse().ok())
+            .collect();
+        let options = formats::csv::CsvOptions {
+            delimiter,
+            has_header: !*csv_m.get_one::<bool>("no-header").unwrap(),
+            columns,
+            new_columns: *csv_m.get_one::<bool>("new-columns").unwrap(),
+            targets: targets.iter().map(|target| target.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let reader = std::io::BufReader::new(std::fs::File::open(input)?);
+        let report = match csv_m.get_one::<String>("output") {
+            Some(output) => {
+                let writer = std::io::BufWriter::new(std::fs::File::create(output)?);
+                formats::csv::translate_csv(reader, writer, &translator, &options).await?
+            }
+            None => {
+                let writer = std::io::stdout().lock();
+                formats::csv::translate_csv(reader, writer, &translator, &options).await?
+            }
+        };
+
+        for (text, err) in &report.failed {
+            stderr.error(&[("text", text)], err);
+        }
+
+        return Ok(());
+    }
+
+    if let Some((_, po_m)) = file_matches {
+        let template = formats::po::Catalog::open(po_m.get_one::<String>("input").unwrap())?;
+        let output = po_m.get_one::<String>("output");
+        if targets.len() > 1 && !output.is_some_and(|output| output.contains("{target}")) {
+            stderr.usage("--output needs `{target}` in it to translate into several languages");
+        }
+
+        for target in &targets {
+            translator.target = target.to_string();
+            let mut catalog = template.clone();
+            let report = catalog.translate(&translator).await;
+
+            for (text, err) in &report.failed {
+                stderr.error(&[("target", target), ("text", text)], err);
+            }
+
+            match output {
+                Some(output) => catalog.save(output.replace("{target}", target))?,
+                None => print!("{catalog}"),
+            }
+        }
+
+        return Ok(());
+    }
+
+    if languages {
+        let rows = sorted_languages(&translator.engine)
+            .into_iter()
+            .map(|(name, code)| vec![name, code])
+            .collect::<Vec<_>>();
+        print!("{}", table(&rows));
+        return Ok(());
+    }
+
+    let line_mode = matches.get_one::<String>("mode").unwrap() == "line";
+    let format = matches.get_one::<String>("format").unwrap().as_str();
+    let output = matches.get_one::<String>("output");
+    if targets.len() > 1 && output.is_some_and(|output| !output.contains("{target}")) {
+        stderr.usage("--output needs `{target}` in it to translate into several languages");
+    }
+
+    // The lines, the files and the targets share the requests in flight
+    translator.limit = Some(std::sync::Arc::new(tokio::sync::Semaphore::new(
+        translator.max_concurrency.max(1),
+    )));
+
+    let input = matches.get_one::<String>("input");
+    if let Some(input) = input.filter(|input| Path::new(input).is_dir()) {
+        let Some(output) = output else {
+            stderr.usage("--output is needed when --input is a folder");
+        };
+
+        let mut failed = None;
+        for path in files(Path::new(input))? {
+            let text = std::fs::read_to_string(&path)?;
+            let relative = path.strip_prefix(input).unwrap_or(&path);
+
+            let results =
+                translate_text(&translator, &targets, &text, line_mode, stderr, &mut failed)
+                    .await?;
+            for (target, translations) in results {
+                translator.target = target;
+                let translation = render(&translator, &translations, format, line_mode);
+                let destination =
+                    Path::new(&output.replace("{target}", &translator.target)).join(relative);
+
+                if let Some(folder) = destination.parent() {
+                    std::fs::create_dir_all(folder)?;
+                }
+                std::fs::write(destination, translation)?;
+            }
+        }
+
+        exit_on_failure(failed);
+        return Ok(());
+    }
+
+    let text = match (matches.get_one::<String>("text"), input) {
+        (Some(text), _) => text.clone(),
+        (None, Some(input)) => std::fs::read_to_string(input)?,
+        (None, None) if std::io::stdin().is_terminal() => {
+            stderr.usage("there is nothing to translate, use --text, --input or pipe it to stdin")
+        }
+        (None, None) => std::io::read_to_string(std::io::stdin())?,
+    };
+
+    // The JSON of every target is a single array
+    let mut records = Vec::new();
+    let mut failed = None;
+    let results =
+        translate_text(&translator, &targets, &text, line_mode, stderr, &mut failed).await?;
+    for (target, translations) in results {
+        translator.target = target;
+        let target = &translator.target;
+
+        if let (None, "json") = (output, format) {
+            records.extend(
+                translations
+                    .iter()
+                    .map(|(text, translation)| record(&translator, text, translation)),
+            );
+            continue;
+        }
+
+        let translation = render(&translator, &translations, format, line_mode);
+        match output {
+            Some(output) => std::fs::write(output.replace("{target}", target), translation)?,
+            None if targets.len() > 1 && format == "plain" => print!("{target}: {translation}"),
+            None => print!("{translation}"),
+        }
+    }
+
+    if let (None, "json") = (output, format) {
+        println!("{}", serde_json::to_string_pretty(&records)?);
+    }
+
+    exit_on_failure(failed);
+    Ok(())
+}
+
+/// Writes the errors and warnings on stderr, as JSON objects when the output format is JSON
+#[derive(Clone, Copy)]
+struct Stderr {
+    json: bool,
+}
+
+impl Stderr {
+    fn new(matches: &ArgMatches) -> Self {
+        let format = matches.get_one::<String>("format").map(String::as_str);
+        Stderr {
+            json: matches!(format, Some("json" | "jsonl")),
+        }
+    }
+
+    /// The error of an entry, the fields tell which one, e.g. the target and the text
+    fn error(self, fields: &[(&str, &str)], err: &Error) {
+        self.write("error", fields, Some(exit_code(err)), &err.to_string());
+    }
+
+    fn warning(self, fields: &[(&str, &str)], message: &str) {
+        self.write("warning", fields, None, message);
+    }
+
+    /// Print the error of the arguments and exit like clap does
+    fn usage(self, message: &str) -> ! {
+        if !self.json {
+            clap::Error::raw(ErrorKind::ArgumentConflict, format!("{message}\n")).exit()
+        }
+
+        self.write("error", &[], Some(EXIT_USAGE), message);
+        std::process::exit(EXIT_USAGE.into())
+    }
+
+    /// A line of plain text with the fields before the message, the text quoted. In JSON an
+    /// object with the level as key
+    fn write(self, level: &str, fields: &[(&str, &str)], code: Option<u8>, message: &str) {
+        if self.json {
+            let mut object = serde_json::Map::new();
+            if let Some(code) = code {
+                object.insert("kind".into(), error_kind(code).into());
+                object.insert("exit_code".into(), code.into());
+            }
+            object.insert("message".into(), message.into());
+            for (name, value) in fields {
+                object.insert(name.to_string(), (*value).into());
+            }
+
+            return eprintln!("{}", serde_json::json!({ level: object }));
+        }
+
+        let mut line = match (level, fields) {
+            ("error" | "warning", []) | ("warning", _) => format!("{level}: "),
+            _ => String::new(),
+        };
+        for (name, value) in fields {
+            match *name {
+                "text" => line.push_str(&format!("{value:?}: ")),
+                _ => line.push_str(&format!("{value}: ")),
+            }
+        }
+        eprintln!("{line}{message}");
+    }
+}
+
+/// Exit with the code of the failure once the output is written, when some lines were not
+/// translated
+fn exit_on_failure(failed: Option<u8>) {
+    if let Some(code) = failed {
+        let _ = std::io::stdout().flush();
+        std::process::exit(code.into());
+    }
+}
+
+/// Exit code for the kind of the error
+fn exit_code(err: &Error) -> u8 {
+    let status = match err {
+        Error::TooManyRequests => return EXIT_QUOTA,
+        Error::LanguageNotSupported(_) => return EXIT_LANGUAGE,
+        Error::MissingCredentials(_) => return EXIT_AUTHENTICATION,
+        Error::Reqwest(err) if err.is_connect() || err.is_timeout() || err.is_request() => {
+            return EXIT_NETWORK
+        }
+        Error::Reqwest(err) => err.status(),
+        Error::Response(status) => Some(*status),
+        _ => None,
+    };
+
+    match status.map(|status| status.as_u16()) {
+        Some(401 | 403) => EXIT_AUTHENTICATION,
+        // DeepL answers 456 when the quota is exceeded
+        Some(429 | 456) => EXIT_QUOTA,
+        Some(500..) => EXIT_NETWORK,
+        _ => EXIT_FAILURE,
+    }
+}
+
+/// Name of the kind of error of an exit code, for the JSON errors
+fn error_kind(code: u8) -> &'static str {
+    match code {
+        EXIT_USAGE => "usage",
+        EXIT_NETWORK => "network",
+        EXIT_AUTHENTICATION => "authentication",
+        EXIT_QUOTA => "quota",
+        EXIT_LANGUAGE => "language",
+        _ => "other",
+    }
+}
+
+/// Files of the folder and of its subfolders, sorted
+fn files(folder: &Path) -> Result<Vec<PathBuf>, Error> {
+    let mut found = Vec::new();
+
+    for entry in std::fs::read_dir(folder)? {
+        let path = entry?.path();
+        match path.is_dir() {
+            true => found.extend(files(&path)?),
+            false => found.push(path),
+        }
+    }
+
+    found.sort();
+    Ok(found)
+}
+
+/// Name of the language of the code, as Google names it
+fn language_name(code: &str) -> Option<String> {
+    Engine::Google
+        .supported_languages()
+        .into_iter()
+        .find_map(|(name, known)| known.eq_ignore_ascii_case(code).then_some(name))
+}
+
+/// Detect the language of the text, of the files or of stdin, each line apart with `--lines`
+async fn detect(
+    matches: &ArgMatches,
+    detect_m: &ArgMatches,
+    profile: &config::Profile,
+) -> Result<(), Error> {
+    let stderr = Stderr::new(matches);
+    let api_key = detect_m
+        .get_one::<String>("api-key")
+        .cloned()
+        .or_else(|| profile.detection_api_key())
+        .ok_or_else(|| {
+            Error::MissingCredentials(
+                "detect needs an api key of detectlanguage.com, get one for free here: \
+                https://detectlanguage.com/documentation and use --api-key, \
+                `detection_api_key` in the profile or the DETECTLANGUAGE_API_KEY environment \
+                variable"
+                    .into(),
+            )
+        })?;
+
+    // Each text with what the output shows of it, the text itself, its file or `-` for stdin
+    let inputs = match (
+        matches.get_one::<String>("text"),
+        matches.get_one::<String>("input"),
+    ) {
+        (Some(text), _) => vec![(text.clone(), text.clone())],
+        (None, Some(input)) if Path::new(input).is_dir() => files(Path::new(input))?
+            .into_iter()
+            .map(|path| Ok((path.display().to_string(), std::fs::read_to_string(&path)?)))
+            .collect::<Result<_, Error>>()?,
+        (None, Some(input)) => vec![(input.clone(), std::fs::read_to_string(input)?)],
+        (None, None) if std::io::stdin().is_terminal() => {
+            stderr.usage("there is nothing to detect, use --text, --input or pipe it to stdin")
+        }
+        (None, None) => vec![("-".into(), std::io::read_to_string(std::io::stdin())?)],
+    };
+
+    let texts = match *detect_m.get_one::<bool>("lines").unwrap() {
+        true => inputs
+            .iter()
+            .flat_map(|(_, text)| text.lines())
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| (line.to_string(), line.to_string()))
+            .collect(),
+        false => inputs
+            .into_iter()
+            .map(|(input, text)| (input, text.trim_end().to_string()))
+            .collect::<Vec<_>>(),
+    };
+
+    // A single request for all the texts
+    let detections = match texts.as_slice() {
+        [] => Vec::new(),
+        [(_, text)] => vec![detection::single(text, &api_key, true).await?],
+        texts => {
+            let texts = texts.iter().map(|(_, text)| text.clone()).collect();
+            detection::batch(texts, &api_key, true).await?
+        }
+    };
+
+    let records = texts
+        .iter()
+        .zip(detections)
+        .map(|((input, _), detection)| {
+            let language = detection["language"].as_str().unwrap_or_default();
+            serde_json::json!({
+                "input": input,
+                "language": language,
+                "name": language_name(language),
+                "confidence": detection["confidence"],
+                "reliable": detection["isReliable"],
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let several = records.len() > 1;
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&records)?),
+        format => {
+            for record in &records {
+                let field = |name| match &record[name] {
+                    Value::String(value) => value.clone(),
+                    Value::Null => String::new(),
+                    value => value.to_string(),
+                };
+
+                match format {
+                    "jsonl" => println!("{record}"),
+                    "tsv" => println!(
+                        "{}\t{}\t{}\t{}",
+                        tsv_field(&field("input")),
+                        field("language"),
+                        field("name"),
+                        field("confidence")
+                    ),
+                    _ => {
+                        let detection = [field("language"), field("name"), field("confidence")]
+                            .into_iter()
+                            .filter(|field| !field.is_empty())
+                            .collect::<Vec<_>>()
+                            .join(" ");
+                        match several {
+                            true => println!("{}: {detection}", field("input")),
+                            false => println!("{detection}"),
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Languages of the engine with their codes, sorted by name
+fn sorted_languages(engine: &Engine) -> Vec<(String, String)> {
+    let mut languages = engine.supported_languages().into_iter().collect::<Vec<_>>();
+    languages.sort_by_cached_key(|(name, code)| (name.to_lowercase(), code.clone()));
+    languages
+}
+
+/// The rows with their columns aligned, the last column is not padded
+fn table(rows: &[Vec<String>]) -> String {
+    let columns = rows.iter().map(Vec::len).max().unwrap_or_default();
+    let widths = (0..columns)
+        .map(|column| {
+            rows.iter()
+                .filter_map(|row| row.get(column))
+                .map(|cell| cell.chars().count())
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+
+    rows.iter()
+        .map(|row| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
+
+/// List the languages of the engines, the engines of a language or the languages of all the
+/// engines side by side
+fn languages(
+    matches: &ArgMatches,
+    languages_m: &ArgMatches,
+    profile: &config::Profile,
+) -> Result<(), Error> {
+    let language = languages_m.get_one::<String>("language");
+    let compare = *languages_m.get_one::<bool>("compare").unwrap();
+    let names = match languages_m.get_many::<String>("engines") {
+        Some(engines) => engines.map(String::as_str).collect(),
+        None if compare || language.is_some() => config::ENGINES.to_vec(),
+        None => vec![matches
+            .get_one::<String>("engine")
+            .or(profile.engine.as_ref())
+            .map_or("google", String::as_str)],
+    };
+    let engines = names
+        .iter()
+        .map(|name| name.parse())
+        .collect::<Result<Vec<Engine>, _>>()?;
+
+    let filter = languages_m
+        .get_one::<String>("filter")
+        .map(|filter| filter.to_lowercase());
+    let selected = |name: &str, code: &str| {
+        filter.as_ref().is_none_or(|filter| {
+            name.to_lowercase().contains(filter) || code.to_lowercase().contains(filter)
+        })
+    };
+
+    // `pt` matches `pt-BR` and `pt-PT`
+    let primary = |code: &str| {
+        code.split(['-', '_'])
+            .next()
+            .unwrap_or_default()
+            .to_lowercase()
+    };
+
+    let mut records = Vec::new();
+    let mut rows = Vec::new();
+    let mut output = String::new();
+
+    if compare {
+        // The languages by their code, with the engines that support them
+        let mut languages =
+            std::collections::BTreeMap::<String, (String, String, Vec<&str>)>::new();
+        for engine in &engines {
+            for (name, code) in sorted_languages(engine) {
+                if selected(&name, &code) {
+                    let language = languages.entry(code.to_lowercase()).or_insert_with(|| {
+                        (language_name(&code).unwrap_or(name), code, Vec::new())
+                    });
+                    language.2.push(engine.name());
+                }
+            }
+        }
+
+        let mut languages = languages.into_values().collect::<Vec<_>>();
+        languages.sort_by_cached_key(|(name, code, _)| (name.to_lowercase(), code.clone()));
+
+        rows.push(
+            ["Language", "Code"]
+                .into_iter()
+                .chain(engines.iter().map(Engine::name))
+                .map(String::from)
+                .collect(),
+        );
+        for (name, code, supported) in &languages {
+            let marks = engines
+                .iter()
+                .map(|engine| match supported.contains(&engine.name()) {
+                    true => "x".to_string(),
+                    false => String::new(),
+                });
+            rows.push(
+                [name.clone(), code.clone()]
+                    .into_iter()
+                    .chain(marks)
+                    .collect(),
+            );
+            records.push(serde_json::json!({ "code": code, "name": name, "engines": supported }));
+        }
+        rows.push(
+            ["Total".to_string(), String::new()]
+                .into_iter()
+                .chain(engines.iter().map(|engine| {
+                    let total = languages
+                        .iter()
+                        .filter(|(_, _, supported)| supported.contains(&engine.name()))
+                        .count();
+                    total.to_string()
+                }))
+                .collect(),
+        );
+    } else {
+        for engine in &engines {
+            let languages = sorted_languages(engine)
+                .into_iter()
+                .filter(|(name, code)| selected(name, code))
+                .filter(|(name, code)| {
+                    language.is_none_or(|language| {
+                        name.eq_ignore_ascii_case(language) || primary(code) == primary(language)
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // A table for each engine
+            if engines.len() > 1 && language.is_none() && !languages.is_empty() {
+                if !rows.is_empty() {
+                    output += &format!("{}\n", table(&std::mem::take(&mut rows)));
+                }
+                output += &format!("{} ({})\n", engine.name(), languages.len());
+            }
+
+            for (name, code) in languages {
+                rows.push(match language {
+                    Some(_) => vec![engine.name().to_string(), name.clone(), code.clone()],
+                    None => vec![name.clone(), code.clone()],
+                });
+                records.push(serde_json::json!({
+                    "engine": engine.name(),
+                    "name": name,
+                    "code": code,
+                }));
+            }
+        }
+
+        if let (Some(language), true) = (language, records.is_empty()) {
+            return Err(Error::LanguageNotSupported(language.clone()));
+        }
+    }
+
+    match matches.get_one::<String>("format").unwrap().as_str() {
+        "json" => println!("{}", serde_json::to_string_pretty(&records)?),
+        "jsonl" => records.iter().for_each(|record| println!("{record}")),
+        "tsv" => {
+            for record in &records {
+                let fields =
+                    record
+                        .as_object()
+                        .into_iter()
+                        .flatten()
+                        .map(|(_, field)| match field {
+                            Value::String(field) => tsv_field(field),
+                            Value::Array(fields) => fields
+                                .iter()
+                                .filter_map(Value::as_str)
+                                .collect::<Vec<_>>()
+                                .join(","),
+                            field => field.to_string(),
+                        });
+                println!("{}", fields.collect::<Vec<_>>().join("\t"));
+            }
+        }
+        _ => print!("{output}{}", table(&rows)),
+    }
+
+    Ok(())
+}
+
+/// Translate the text as a whole, or each line apart, into every target. The result has,
+/// for each target, the text of each translation with it. The lines that fail are reported
+/// and left out, the exit code of the first one is kept in `failed`
+async fn translate_text(
+    translator: &Translator,
+    targets: &[String],
+    text: &str,
+    line_mode: bool,
+    stderr: Stderr,
+    failed: &mut Option<u8>,
+) -> Result<Vec<(String, Vec<(String, Translation)>)>, Error> {
+    let texts = match line_mode {
+        true => text.lines().map(String::from).collect(),
+        false => vec![text.trim_end_matches('\n').to_string()],
+    };
+
+    let mut translations = futures::stream::iter(
+        texts
+            .iter()
+            .map(|text| translator.translate_detailed_to_many(text, targets)),
+    )
+    .buffered(translator.max_concurrency.max(1))
+    .collect::<Vec<_>>()
+    .await;
+
+    let mut results = Vec::with_capacity(targets.len());
+    for target in targets {
+        let mut translated = Vec::with_capacity(texts.len());
+
+        for (text, translations) in texts.iter().zip(&mut translations) {
+            // A target given twice was translated once
+            let Some(translation) = translations.remove(target) else {
+                continue;
+            };
+            let translation = match translation {
+                Ok(translation) => translation,
+                Err(err) if line_mode => {
+                    stderr.error(&[("target", target), ("text", text)], &err);
+                    failed.get_or_insert(exit_code(&err));
+                    continue;
+                }
+                Err(err) => return Err(err),
+            };
+            if !translation.glossary_violations.is_empty() {
+                stderr.warning(
+                    &[("target", target), ("text", text)],
+                    &format!(
+                        "the glossary terms {:?} were not kept in the translation",
+                        translation.glossary_violations
+                    ),
+                );
+            }
+
+            translated.push((text.clone(), translation));
+        }
+
+        results.push((target.clone(), translated));
+    }
+
+    Ok(results)
+}
+
+/// An object of the JSON output
+fn record(translator: &Translator, text: &str, translation: &Translation) -> Value {
+    serde_json::json!({
+        "engine": translator.engine.name(),
+        "source": translator.source,
+        "target": translator.target,
+        "detected_language": translation.detected_language,
+        "text": text,
+        "translation": translation.text,
+        "alternatives": translation.alternatives,
+        "glossary_violations": translation.glossary_violations,
+        "transliteration": translation.transliteration,
+        "alignment": translation.alignment,
+    })
+}
+
+/// Escape the backslashes, tabs and line breaks of a field of tsv
+fn tsv_field(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+/// The translations in the output format, in plain text the whole text is followed by its
+/// alternatives, if there are some
+fn render(
+    translator: &Translator,
+    translations: &[(String, Translation)],
+    format: &str,
+    line_mode: bool,
+) -> String {
+    let records = translations
+        .iter()
+        .map(|(text, translation)| record(translator, text, translation));
+
+    match format {
+        "json" => format!("{:#}\n", Value::Array(records.collect())),
+        "jsonl" => records.map(|record| format!("{record}\n")).collect(),
+        "tsv" => translations
+            .iter()
+            .map(|(text, translation)| {
+                format!(
+                    "{}\t{}\t{}\n",
+                    translator.target,
+                    tsv_field(text),
+                    tsv_field(&translation.text)
+                )
+            })
+            .collect(),
+        _ => {
+            let mut plain = translations
+                .iter()
+                .flat_map(|(_, translation)| match line_mode {
+                    true => vec![translation.text.as_str()],
+                    false => std::iter::once(translation.text.as_str())
+                        .chain(translation.alternatives.iter().map(String::as_str))
+                        .collect(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            if !plain.ends_with('\n') {
+                plain.push('\n');
+            }
+            plain
+        }
+    }
+}
+
+/// The arguments of the CLI
+fn command() -> Command {
+    clap::Command::new("deep-translator")
+    .bin_name("deep-translator")
+    .about("Official CLI for Deep Translator")
+    .long_about(
+        "Does really amazing things to great people. Now let's talk a little
       more in depth about how this subcommand really works. It may take about
       a few lines of text, but that's ok!",
-        )
-        .subcommand_required(false)
-        .arg_required_else_help(false)
-        .version(env!("CARGO_PKG_VERSION"))
-        .subcommands(vec![
-            Command::new("deepl").about("Use Deepl as engine").args(&[
-                Arg::new("api-key")
-                    .long("--api-key")
-                    .value_name("API-KEY")
+    )
+    .subcommand_required(false)
+    .arg_required_else_help(false)
+    .version(env!("CARGO_PKG_VERSION"))
+    .after_help(
+        "The text is read from --text, --input or stdin. Exit codes: 1 any other error, \
+        2 wrong arguments, 3 network, 4 authentication or missing key, 5 quota or too \
+        many requests, 6 language not supported",
+    )
+    .subcommands(engine_commands())
+    .subcommand(
+        Command::new("po")
+            .about("Translate the untranslated messages of a gettext .po or .pot file")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .po or .pot file to translate"),
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FILE")
+                    .help(
+                        "where the translated .po file is written, `{target}` is replaced by \
+                        the target language. stdout by default",
+                    ),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("json")
+            .about("Translate the strings of a nested JSON locale file")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
                     .required(true)
-                    .help("user api key"),
-                Arg::new("version")
-                    .long("version")
-                    .value_parser(["v1", "v2"])
-                    .default_value("v2")
-                    .value_name("VERSION")
-                    .help("use api version"),
-                Arg::new("free")
-                    .long("--free")
+                    .help("the .json file to translate"),
+                Arg::new("output").long("output").value_name("FILE").help(
+                    "where the translated file is written, `{target}` is replaced by the \
+                        target language. stdout by default",
+                ),
+                Arg::new("include")
+                    .long("include")
+                    .value_name("KEY-PATH")
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("key paths of the strings to translate, e.g. `nav.*` or `**.title`"),
+                Arg::new("exclude")
+                    .long("exclude")
+                    .value_name("KEY-PATH")
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("key paths of the strings kept as they are"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("yaml")
+            .about(
+                "Translate the strings of a YAML locale file, the root locale key is renamed",
+            )
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .yml file to translate"),
+                Arg::new("output").long("output").value_name("FILE").help(
+                    "where the translated file is written, `{target}` is replaced by the \
+                        target language. stdout by default",
+                ),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("xliff")
+            .about("Pre-translate the units of a XLIFF 1.2 or 2.0 file, the targets are marked for review")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .xlf file to translate"),
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FILE")
+                    .help(
+                        "where the translated file is written, `{target}` is replaced by the \
+                        target language. stdout by default",
+                    ),
+                Arg::new("overwrite")
+                    .long("overwrite")
                     .action(ArgAction::SetTrue)
-                    .help("use free api"),
+                    .help("translate again the units that already have a target"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("android")
+            .about("Translate an Android strings.xml into the values-<target> folders")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the strings.xml of the default values/ folder"),
+                Arg::new("res")
+                    .long("res")
+                    .value_name("DIR")
+                    .help("the res/ folder where the translations are written, the parent of values/ by default"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("apple")
+            .about("Translate a .strings file into the <target>.lproj folders, or add the translations to a .xcstrings catalog")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .strings file of the source language or the .xcstrings catalog, that is updated in place"),
+                Arg::new("output-dir")
+                    .long("output-dir")
+                    .value_name("DIR")
+                    .help("the folder of the .lproj folders, the parent of the source .lproj by default"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("subtitles")
+            .about("Translate the cues of a .srt or .vtt file, the timings and tags are kept")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .srt or .vtt file to translate"),
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FILE")
+                    .help(
+                        "where the translated file is written, `{target}` is replaced by the \
+                        target language. stdout by default",
+                    ),
+                Arg::new("merge-sentences")
+                    .long("merge-sentences")
+                    .action(ArgAction::SetTrue)
+                    .help("translate together the cues that split a sentence"),
+                Arg::new("max-line-length")
+                    .long("max-line-length")
+                    .value_name("CHARACTERS")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("42")
+                    .help("most characters of a line of a cue, 0 for no limit"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("csv")
+            .about("Translate columns of a CSV or TSV file, by header name or index")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the CSV or TSV file to translate"),
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FILE")
+                    .help("where the translated file is written, stdout by default"),
+                Arg::new("columns")
+                    .long("columns")
+                    .value_name("COLUMNS")
+                    .value_delimiter(',')
+                    .required(true)
+                    .help("names or indices, from 0, of the columns to translate"),
+                Arg::new("new-columns")
+                    .long("new-columns")
+                    .action(ArgAction::SetTrue)
+                    .help("add a column for each target instead of replacing the text"),
+                Arg::new("delimiter")
+                    .long("delimiter")
+                    .value_name("CHARACTER")
+                    .help("`tab` or a character, guessed from the file extension by default"),
+                Arg::new("no-header")
+                    .long("no-header")
+                    .action(ArgAction::SetTrue)
+                    .help("the first row is not the names of the columns"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("sync")
+            .about("Translate only the new and changed strings of a JSON locale file")
+            .args(&[
+                Arg::new("input")
+                    .long("input")
+                    .value_name("FILE")
+                    .required(true)
+                    .help("the .json file in the source language"),
+                Arg::new("output")
+                    .long("output")
+                    .value_name("FILE")
+                    .required(true)
+                    .help(
+                        "the translated file, `{target}` is replaced by the target \
+                        language. It is read first to keep the translations of people",
+                    ),
+                Arg::new("lockfile")
+                    .long("lockfile")
+                    .value_name("FILE")
+                    .help("hashes of the translated strings, next to the input by default"),
+                Arg::new("include")
+                    .long("include")
+                    .value_name("KEY-PATH")
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("key paths of the strings to translate, e.g. `nav.*` or `**.title`"),
+                Arg::new("exclude")
+                    .long("exclude")
+                    .value_name("KEY-PATH")
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("key paths of the strings kept as they are"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("languages")
+            .about("List the languages of the engines, sorted by name")
+            .args(&[
+                Arg::new("engines")
+                    .long("engines")
+                    .value_parser(config::ENGINES)
+                    .value_delimiter(',')
+                    .value_name("ENGINES")
+                    .help(
+                        "engines whose languages are listed, the one of --engine by default, \
+                        or all of them with --language and --compare",
+                    ),
+                Arg::new("filter")
+                    .long("filter")
+                    .value_name("TEXT")
+                    .help("only the languages whose name or code has the text"),
+                Arg::new("language")
+                    .long("language")
+                    .value_name("LANGUAGE")
+                    .help("the engines that support the language, by name or code"),
+                Arg::new("compare")
+                    .long("compare")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("language")
+                    .help("the languages of the engines side by side"),
             ]),
-            Command::new("google").about("Use Google as engine"),
-            Command::new("libre").about("Use Libre as engine").args(&[
+    )
+    .subcommand(
+        Command::new("detect")
+            .about("Detect the language of the text, of the --input files or of stdin")
+            .args(&[
+                Arg::new("lines")
+                    .long("lines")
+                    .action(ArgAction::SetTrue)
+                    .help("detect the language of each line apart, all in a single request"),
                 Arg::new("api-key")
-                    .long("--api-key")
-                    .default_value("")
+                    .long("api-key")
                     .value_name("API-KEY")
-                    .help("user api key"),
-                Arg::new("custom")
-                    .long("--custom")
-                    .value_name("EMAIL")
-                    .help("you can use a custom endpoint"),
-                Arg::new("default")
-                    .long("--default")
-                    .action(ArgAction::SetTrue)
-                    .help("you want to not use the free api"),
-                Arg::new("alternatives")
-                    .long("--alternatives")
-                    .default_value("1")
-                    .value_name("ALT")
-                    .help("the maximum number of alternatives you want to see"),
+                    .help("key of detectlanguage.com, DETECTLANGUAGE_API_KEY by default"),
             ]),
-            Command::new("linguee").about("Use Linguee as engine").arg(
-                Arg::new("synonym")
-                    .long("--synonym")
-                    .action(ArgAction::SetTrue)
-                    .help("return all synonym of the translated word"),
+    )
+    .subcommand(
+        Command::new("serve")
+            .about("Serve the translations over HTTP with the API of LibreTranslate")
+            .args(&[
+                Arg::new("host")
+                    .long("host")
+                    .default_value("127.0.0.1")
+                    .value_name("HOST")
+                    .help("address the server listens on"),
+                Arg::new("port")
+                    .long("port")
+                    .value_parser(clap::value_parser!(u16))
+                    .default_value("5000")
+                    .value_name("PORT")
+                    .help("port the server listens on"),
+                Arg::new("api-keys")
+                    .long("api-keys")
+                    .value_name("KEYS")
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+                    .help("keys the clients must send as `api_key`, any client is served without them"),
+                Arg::new("req-limit")
+                    .long("req-limit")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("0")
+                    .value_name("NUMBER")
+                    .help("requests of a client in a minute, by its key or address, 0 for no limit"),
+                Arg::new("cache-size")
+                    .long("cache-size")
+                    .value_parser(clap::value_parser!(usize))
+                    .default_value("10000")
+                    .value_name("NUMBER")
+                    .help("translations kept in memory, 0 to not keep them"),
+            ])
+            .subcommands(engine_commands()),
+    )
+    .subcommand(
+        Command::new("repl")
+            .about("Translate line after line, the lines starting with `:` are commands")
+            .arg(
+                Arg::new("history")
+                    .long("history")
+                    .value_name("FILE")
+                    .help(
+                        "where the history is kept, \
+                        ~/.local/state/deep-translator/history by default",
+                    ),
+            )
+            .subcommands(engine_commands()),
+    )
+    .args(&[
+        Arg::new("source")
+            .default_value("auto")
+            .long("source")
+            .value_name("SOURCE")
+            .help("source language to translate from"),
+        Arg::new("target")
+            .long("target")
+            .default_value("en")
+            .value_name("TARGET")
+            .value_delimiter(',')
+            .help("target language to translate to, several can be separated by commas"),
+        Arg::new("text")
+            .long("text")
+            .value_name("TEXT")
+            .conflicts_with("input")
+            .help("text you want to translate, stdin is read when there is no --input"),
+        Arg::new("input")
+            .long("input")
+            .value_name("PATH")
+            .help("file to translate, or folder whose files are all translated"),
+        Arg::new("output")
+            .long("output")
+            .value_name("PATH")
+            .help(
+                "where the translation is written, a folder when the input is one. \
+                `{target}` is replaced by the target language. stdout by default",
             ),
-            Command::new("microsoft")
-                .about("Use Microsoft as engine")
-                .args(&[
-                    Arg::new("key")
-                        .long("--key")
-                        .value_name("KEY")
-                        .help("user api key"),
-                    Arg::new("region")
-                        .long("--region")
-                        .value_name("REGION")
-                        .default_value("")
-                        .help("region where user is"),
-                ]),
-            Command::new("mymemory")
-                .about("Use MyMemory as engine")
-                .args(&[
-                    Arg::new("email")
-                        .long("--email")
-                        .value_name("EMAIL")
-                        .default_value("")
-                        .help("user email"),
-                    Arg::new("synonym")
-                        .long("--synonym")
-                        .action(ArgAction::SetTrue)
-                        .help("show all synonym/similars of the translated text"),
-                ]),
-            Command::new("papago").about("Use Papago as engine").args(&[
-                Arg::new("id")
-                    .long("--id")
-                    .value_name("ID")
-                    .required(true)
-                    .help("user id"),
+        Arg::new("format")
+            .long("format")
+            .value_parser(["plain", "json", "jsonl", "tsv"])
+            .default_value("plain")
+            .global(true)
+            .value_name("FORMAT")
+            .help(
+                "plain text, or JSON with the engine, languages and alternatives of each \
+                translation. tsv has the target, text and translation",
+            ),
+        Arg::new("mode")
+            .long("mode")
+            .value_parser(["document", "line"])
+            .default_value("document")
+            .value_name("MODE")
+            .help("translate the whole text at once or each line apart"),
+        Arg::new("text-type")
+            .long("text-type")
+            .value_parser(["plain", "html", "markdown"])
+            .default_value("plain")
+            .value_name("TEXT-TYPE")
+            .help("whether the text is plain, html or markdown, the markup is preserved"),
+        Arg::new("placeholders")
+            .long("placeholders")
+            .value_parser(["icu", "printf", "python", "ruby", "laravel", "html"])
+            .value_delimiter(',')
+            .value_name("SYNTAX")
+            .help("placeholders kept as they are, several can be separated by commas"),
+        Arg::new("engine")
+            .long("engine")
+            .value_parser(config::ENGINES)
+            .value_name("ENGINE")
+            .help(
+                "engine to translate with, instead of a subcommand. Its keys are read from \
+                the environment, e.g. DEEPL_API_KEY, or from the profile",
+            ),
+        Arg::new("profile")
+            .long("profile")
+            .value_name("NAME")
+            .help("profile of the configuration file, `default_profile` by default"),
+        Arg::new("config")
+            .long("config")
+            .value_name("FILE")
+            .help(
+                "configuration file with the profiles, \
+                ~/.config/deep-translator/config.toml by default",
+            ),
+        Arg::new("languages")
+            .long("languages")
+            .action(ArgAction::SetTrue)
+            .help(
+                "all the languages available with the translator. \
+                Run the command deep_translator --engine <translator service> --languages",
+            ),
+        Arg::new("glossary")
+            .long("glossary")
+            .value_name("FILE")
+            .help("JSON glossary with `do_not_translate` terms and preferred `terms`"),
+        Arg::new("tmx")
+            .long("tmx")
+            .value_name("FILE")
+            .help("TMX translation memory, its segments are used instead of the engine"),
+        #[cfg(not(target_arch = "wasm32"))]
+        Arg::new("proxy")
+            .long("proxy")
+            .value_name("PROXY")
+            .action(ArgAction::Append)
+            .help("append proxy to proxies list"),
+    ])
+}
+
+/// Subcommands that select the engine
+fn engine_commands() -> Vec<Command> {
+    vec![
+        Command::new("deepl").about("Use Deepl as engine").args(&[
+            Arg::new("api-key")
+                .long("api-key")
+                .value_name("API-KEY")
+                .help("user api key, DEEPL_API_KEY by default"),
+            Arg::new("version")
+                .long("version")
+                .value_parser(["v1", "v2"])
+                .default_value("v2")
+                .value_name("VERSION")
+                .help("use api version"),
+            Arg::new("free")
+                .long("free")
+                .action(ArgAction::SetTrue)
+                .help("use free api"),
+        ]),
+        Command::new("google").about("Use Google as engine"),
+        Command::new("libre").about("Use Libre as engine").args(&[
+            Arg::new("api-key")
+                .long("api-key")
+                .value_name("API-KEY")
+                .help("user api key, LIBRE_API_KEY by default"),
+            Arg::new("custom")
+                .long("custom")
+                .value_name("EMAIL")
+                .help("you can use a custom endpoint"),
+            Arg::new("default")
+                .long("default")
+                .action(ArgAction::SetTrue)
+                .help("you want to not use the free api"),
+            Arg::new("alternatives")
+                .long("alternatives")
+                .value_parser(clap::value_parser!(usize))
+                .default_value("1")
+                .value_name("ALT")
+                .help("the maximum number of alternatives you want to see"),
+        ]),
+        Command::new("linguee").about("Use Linguee as engine").arg(
+            Arg::new("synonym")
+                .long("synonym")
+                .action(ArgAction::SetTrue)
+                .help("return all synonym of the translated word"),
+        ),
+        Command::new("microsoft")
+            .about("Use Microsoft as engine")
+            .args(&[
                 Arg::new("key")
-                    .long("--key")
+                    .long("key")
                     .value_name("KEY")
-                    .required(true)
-                    .help("user uniq key"),
+                    .help("user api key, MICROSOFT_TRANSLATOR_KEY by default"),
+                Arg::new("region")
+                    .long("region")
+                    .value_name("REGION")
+                    .help("region where user is, MICROSOFT_TRANSLATOR_REGION by default"),
+                Arg::new("profanity")
+                    .long("profanity")
+                    .value_parser(["none", "marked", "deleted"])
+                    .default_value("none")
+                    .value_name("ACTION")
+                    .help("how profanities should be treated"),
+                Arg::new("profanity-marker")
+                    .long("profanity-marker")
+                    .value_parser(["asterisk", "tag"])
+                    .default_value("asterisk")
+                    .value_name("MARKER")
+                    .help("how profanities are marked"),
+                Arg::new("alignment")
+                    .long("alignment")
+                    .action(ArgAction::SetTrue)
+                    .help(
+                        "include the alignment between source and translation in the JSON output",
+                    ),
+                Arg::new("to-script")
+                    .long("to-script")
+                    .value_name("SCRIPT")
+                    .default_value("")
+                    .help(
+                        "also transliterate the translation into the script, e.g. Latn, it is \
+                        in the JSON output",
+                    ),
             ]),
-            Command::new("pons").about("Use Pons as engine").arg(
+        Command::new("mymemory")
+            .about("Use MyMemory as engine")
+            .args(&[
+                Arg::new("email")
+                    .long("email")
+                    .value_name("EMAIL")
+                    .help("user email, MYMEMORY_EMAIL by default"),
                 Arg::new("synonym")
-                    .long("--synonym")
+                    .long("synonym")
                     .action(ArgAction::SetTrue)
-                    .help("return all synonym of the translated word"),
-            ),
-            Command::new("qcri").about("Use Qcri as engine").args(&[
-                Arg::new("key")
-                    .long("--key")
-                    .value_name("KEY")
-                    .required(true)
-                    .help("user uniq key"),
-                Arg::new("domain")
-                    .long("--domain")
-                    .value_name("DOMAIN")
-                    .help("a qcri domain"),
+                    .help("show all synonym/similars of the translated text"),
             ]),
-            Command::new("yandex").about("Use Yandex as engine").arg(
-                Arg::new("key")
-                    .long("--key")
-                    .value_name("KEY")
-                    .required(true)
-                    .help("user uniq key"),
-            ),
-        ])
-        .args(&[
-            Arg::new("source")
-                .default_value("it")
-                .long("--source")
-                .value_name("SOURCE")
-                .help("source language to translate from"),
-            Arg::new("target")
-                .long("--target")
-                .default_value("en")
-                .value_name("TARGET")
-                .help("target language to translate to"),
-            Arg::new("text")
-                .long("--text")
-                .default_value("Ciao")
-                .value_name("TEXT")
-                .help("text you want to translate"),
-            Arg::new("languages")
-                .long("--languages")
+        Command::new("papago").about("Use Papago as engine").args(&[
+            Arg::new("id")
+                .long("id")
+                .value_name("ID")
+                .help("user id, PAPAGO_CLIENT_ID by default"),
+            Arg::new("key")
+                .long("key")
+                .value_name("KEY")
+                .help("user uniq key, PAPAGO_SECRET_KEY by default"),
+        ]),
+        Command::new("pons").about("Use Pons as engine").arg(
+            Arg::new("synonym")
+                .long("synonym")
                 .action(ArgAction::SetTrue)
-                .help(
-                    "all the languages available with the translator. \
-                    Run the command deep_translator --engine <translator service> --languages",
-                ),
-            #[cfg(not(target_arch = "wasm32"))]
-            Arg::new("proxy")
-                .long("--proxy")
-                .value_name("PROXY")
-                .action(ArgAction::Append)
-                .help("append proxy to proxies list"),
-        ])
-        .get_matches();
-
-    let mut translator = Translator::new(
-        matches.get_one::<String>("source").unwrap(),
-        matches.get_one::<String>("target").unwrap(),
-    );
+                .help("return all synonym of the translated word"),
+        ),
+        Command::new("qcri").about("Use Qcri as engine").args(&[
+            Arg::new("key")
+                .long("key")
+                .value_name("KEY")
+                .help("user uniq key, QCRI_API_KEY by default"),
+            Arg::new("domain")
+                .long("domain")
+                .value_name("DOMAIN")
+                .help("a qcri domain"),
+        ]),
+        Command::new("yandex").about("Use Yandex as engine").arg(
+            Arg::new("key")
+                .long("key")
+                .value_name("KEY")
+                .help("user uniq key, YANDEX_API_KEY by default"),
+        ),
+    ]
+}
 
-    #[cfg(not(target_arch = "wasm32"))]
-    if let Ok(Some(many)) = matches.try_get_many::<String>("proxy") {
-        translator.proxies = many.map(reqwest::Proxy::http).collect::<Result<_, _>>()?;
-    }
-
-    translator.engine = match matches.subcommand_name() {
-        Some(engine) => {
-            let sub_m = matches.subcommand_matches(engine).unwrap();
-
-            match engine {
-                "deepl" => Engine::Deepl {
-                    api_key: sub_m.get_one::<String>("api-key").cloned().unwrap(),
-                    version: sub_m.get_one::<String>("version").unwrap().parse().unwrap(),
-                    use_free_api: *sub_m.get_one::<bool>("free").unwrap(),
-                },
-                "libre" => Engine::Libre(Libre {
-                    api_key: sub_m.get_one::<String>("api-key").cloned().unwrap(),
-                    alternatives: sub_m.get_one::<usize>("alternatives").cloned().unwrap(),
-                    url: if *sub_m.get_one::<bool>("default").unwrap() {
-                        "https://libretranslate.com/".into()
-                    } else if let Some(custom) = sub_m.get_one::<String>("custom") {
-                        custom.clone()
-                    } else {
-                        "https://libretranslate.de/".into()
-                    },
-                }),
-                "linguee" => Engine::Linguee {
-                    return_all: *sub_m.get_one::<bool>("synonym").unwrap(),
-                },
-                "microsoft" => Engine::Microsoft {
-                    api_key: sub_m.get_one::<String>("key").cloned().unwrap(),
-                    region: sub_m.get_one::<String>("region").cloned().unwrap(),
-                },
-                "google" => Engine::Google,
-                "mymemory" => Engine::MyMemory {
-                    email: sub_m.get_one::<String>("email").cloned().unwrap(),
-                    return_all: *sub_m.get_one::<bool>("synonym").unwrap(),
-                },
-                "papago" => Engine::Papago {
-                    client_id: sub_m.get_one::<String>("id").cloned().unwrap(),
-                    secret_key: sub_m.get_one::<String>("key").cloned().unwrap(),
-                },
-                "pons" => Engine::Pons,
-                "qcri" => Engine::Qcri(Qcri {
-                    api_key: sub_m.get_one::<String>("key").cloned().unwrap(),
-                    domain: sub_m.get_one::<String>("domain").cloned().unwrap(),
-                }),
-                "yandex" => Engine::Yandex {
-                    api_key: sub_m.get_one::<String>("key").cloned().unwrap(),
-                },
-                _ => unreachable!(),
-            }
-        }
-        None => Engine::Google,
-    };
+/// Build the engine selected by the subcommand `name`, the keys of the arguments win over
+/// the environment, which wins over the credentials of the engine in the profile
+fn engine(name: &str, sub_m: &ArgMatches, profile: &config::Profile) -> Result<Engine, Error> {
+    let arg = |id| sub_m.try_get_one::<String>(id).ok().flatten().cloned();
+    let flag = |id| sub_m.try_get_one::<bool>(id).ok().flatten() == Some(&true);
+    let mut explicit = config::Credentials::default();
 
-    if *matches.get_one::<bool>("languages").unwrap() {
-        println!(
-            "{:?}",
-            translator.supported_languages().keys().collect::<Vec<_>>()
-        );
-    } else {
-        let text = matches.get_one::<String>("text").unwrap();
-        println!("{:?}", translator.translate(text).await);
+    match name {
+        "papago" => {
+            explicit.client_id = arg("id");
+            explicit.secret_key = arg("key");
+        }
+        _ => explicit.api_key = arg("api-key").or_else(|| arg("key")),
     }
+    explicit.region = arg("region");
+    explicit.url = arg("custom");
+    explicit.email = arg("email");
+    explicit.domain = arg("domain");
 
-    Ok(())
+    let mut engine = profile.credentials(name).engine_with(name, &explicit)?;
+    match &mut engine {
+        Engine::Deepl {
+            version,
+            use_free_api,
+            ..
+        } => {
+            *version = sub_m.get_one::<String>("version").unwrap().parse().unwrap();
+            *use_free_api |= flag("free");
+        }
+        Engine::Libre(libre) => {
+            libre.alternatives = sub_m.get_one::<usize>("alternatives").cloned().unwrap();
+            if flag("default") {
+                libre.url = "https://libretranslate.com/".into();
+            }
+        }
+        Engine::Linguee { return_all } | Engine::MyMemory { return_all, .. } => {
+            *return_all = flag("synonym");
+        }
+        Engine::Microsoft(microsoft) => {
+            microsoft.profanity_action = arg("profanity").unwrap().parse().unwrap();
+            microsoft.profanity_marker = arg("profanity-marker").unwrap().parse().unwrap();
+            microsoft.include_alignment = flag("alignment");
+            microsoft.to_script = arg("to-script").unwrap();
+        }
+        _ => {}
+    }
+
+    Ok(engine)
 }
@@ -0,0 +1,290 @@
+//! Interactive mode of the CLI, each line is translated as it is entered and the lines
+//! starting with `:` are commands
+
+use deeptranslator::{
+    config::{self, Profile},
+    detection, Error, Translation, Translator,
+};
+use rustyline::{error::ReadlineError, DefaultEditor};
+use std::path::PathBuf;
+
+const HELP: &str = "\
+:source [LANG]    show or change the source language, `auto` detects it
+:target [LANGS]   show or change the target languages, separated by commas
+:engine [NAME]    show or change the engine
+:swap             swap the source and the target language
+:compare [TEXT]   translate the text, or the last one, with every engine configured
+:help             show this help
+:quit             exit, as Ctrl-D does";
+
+/// Path of the history, `$XDG_STATE_HOME/deep-translator/history` or
+/// `~/.local/state/deep-translator/history`
+pub fn history_path() -> Option<PathBuf> {
+    std::env::var_os("XDG_STATE_HOME")
+        .filter(|state| !state.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("state"))
+        })
+        .map(|state| state.join("deep-translator").join("history"))
+}
+
+#[inline(always)]
+fn readline_error(err: ReadlineError) -> Error {
+    match err {
+        ReadlineError::Io(err) => Error::InputOutput(err),
+        err => Error::InputOutput(std::io::Error::other(err)),
+    }
+}
+
+/// The languages and the engine, they change with the commands
+struct Session {
+    translator: Translator,
+    targets: Vec<String>,
+    /// Credentials of the engines selected with `:engine` and `:compare`
+    profile: Profile,
+    /// Last text translated, for `:compare`
+    last: Option<String>,
+    /// Language of the last text, when the source is `auto`
+    detected: Option<String>,
+}
+
+/// Read lines until `:quit` or the end of the input, the history is loaded from and
+/// saved to `history`
+pub async fn run(
+    translator: Translator,
+    targets: Vec<String>,
+    profile: Profile,
+    history: Option<PathBuf>,
+) -> Result<(), Error> {
+    let mut editor = DefaultEditor::new().map_err(readline_error)?;
+    if let Some(history) = history.as_ref().filter(|history| history.exists()) {
+        editor.load_history(history).map_err(readline_error)?;
+    }
+
+    let mut session = Session {
+        translator,
+        targets,
+        profile,
+        last: None,
+        detected: None,
+    };
+
+    loop {
+        let prompt = format!(
+            "{} -> {}> ",
+            session.translator.source,
+            session.targets.join(",")
+        );
+        let line = match editor.readline(&prompt) {
+            Ok(line) => line,
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(readline_error(err)),
+        };
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        editor.add_history_entry(line).map_err(readline_error)?;
+
+        match line.strip_prefix(':') {
+            Some(command) if !session.command(command.trim()).await => break,
+            Some(_) => {}
+            None => session.translate(line).await,
+        }
+    }
+
+    if let Some(history) = history {
+        if let Some(folder) = history.parent() {
+            std::fs::create_dir_all(folder)?;
+        }
+        editor.save_history(&history).map_err(readline_error)?;
+    }
+
+    Ok(())
+}
+
+impl Session {
+    /// Run the command, `false` when the session ends
+    async fn command(&mut self, command: &str) -> bool {
+        let (name, argument) = command
+            .split_once(char::is_whitespace)
+            .map_or((command, ""), |(name, argument)| (name, argument.trim()));
+
+        match (name, argument) {
+            ("quit" | "exit" | "q", _) => return false,
+            ("help" | "h", _) => println!("{HELP}"),
+            ("source", "") => println!("{}", self.translator.source),
+            ("source", source) => {
+                self.set_languages(source.to_string(), self.targets.clone());
+            }
+            ("target", "") => println!("{}", self.targets.join(",")),
+            ("target", targets) => {
+                let targets = targets
+                    .split(',')
+                    .map(|target| target.trim().to_string())
+                    .filter(|target| !target.is_empty())
+                    .collect();
+                self.set_languages(self.translator.source.clone(), targets);
+            }
+            ("engine", "") => println!(
+                "{} (available: {})",
+                self.translator.engine.name(),
+                config::ENGINES.join(", ")
+            ),
+            ("engine", name) => match self.profile.engine(name) {
+                Ok(engine) => {
+                    let previous = std::mem::replace(&mut self.translator.engine, engine);
+                    if let Err(err) = self.check_languages() {
+                        eprintln!("error: {err}");
+                        self.translator.engine = previous;
+                    }
+                }
+                Err(err) => eprintln!("error: {err}"),
+            },
+            ("swap", _) => {
+                let source = match self.translator.source.as_str() {
+                    "auto" => self.detected.clone(),
+                    source => Some(source.to_string()),
+                };
+
+                match source {
+                    Some(source) => self.set_languages(self.targets[0].clone(), vec![source]),
+                    None => eprintln!("error: the source language is not known yet"),
+                }
+            }
+            ("compare", text) => {
+                match Some(text)
+                    .filter(|text| !text.is_empty())
+                    .or(self.last.as_deref())
+                {
+                    Some(text) => self.compare(text).await,
+                    None => eprintln!("error: there is nothing to compare yet"),
+                }
+            }
+            _ => eprintln!("error: unknown command `:{name}`, run `:help` to see the commands"),
+        }
+
+        true
+    }
+
+    /// Check the languages with the engine
+    fn check_languages(&self) -> Result<(), Error> {
+        self.targets.iter().try_for_each(|target| {
+            Translator {
+                target: target.clone(),
+                ..self.translator.clone()
+            }
+            .check_languages()
+        })
+    }
+
+    /// Change the languages, they are kept as they are when the engine does not support them
+    fn set_languages(&mut self, source: String, targets: Vec<String>) {
+        if targets.is_empty() {
+            return eprintln!("error: there is no target language");
+        }
+
+        let previous = (
+            std::mem::replace(&mut self.translator.source, source),
+            std::mem::replace(&mut self.targets, targets),
+        );
+        if let Err(err) = self.check_languages() {
+            eprintln!("error: {err}");
+            (self.translator.source, self.targets) = previous;
+        }
+
+        self.translator.target = self.targets[0].clone();
+        self.detected = None;
+    }
+
+    /// Language of the text, from the engine or from detectlanguage.com when the engine does
+    /// not tell it and there is a key for it
+    async fn detect(&self, text: &str, translation: &Translation) -> Option<String> {
+        if let Some(language) = &translation.detected_language {
+            return Some(language.clone());
+        }
+
+        let api_key = self.profile.detection_api_key()?;
+        detection::single(text, &api_key, false)
+            .await
+            .ok()?
+            .as_str()
+            .map(String::from)
+    }
+
+    /// Translate the text into every target language
+    async fn translate(&mut self, text: &str) {
+        self.last = Some(text.to_string());
+        if self.translator.source == "auto" {
+            self.detected = None;
+        }
+
+        for target in self.targets.clone() {
+            self.translator.target = target.clone();
+            let translation = match self.translator.translate_detailed(text).await {
+                Ok(translation) => translation,
+                Err(err) => {
+                    eprintln!("error: {err}");
+                    continue;
+                }
+            };
+
+            if self.translator.source == "auto" && self.detected.is_none() {
+                self.detected = self.detect(text, &translation).await;
+                if let Some(language) = &self.detected {
+                    println!("(detected: {language})");
+                }
+            }
+
+            match self.targets.len() {
+                1 => println!("{}", translation.text),
+                _ => println!("{target}: {}", translation.text),
+            }
+            for alternative in &translation.alternatives {
+                println!("  {alternative}");
+            }
+        }
+
+        self.translator.target = self.targets[0].clone();
+    }
+
+    /// Translate the text into the first target language with the engines that support the
+    /// languages and have their own credentials, the current engine is used as it is. Libre
+    /// is only used with its url, not the public instance
+    async fn compare(&self, text: &str) {
+        let engines = config::ENGINES.iter().filter_map(|name| {
+            let engine = match self.translator.engine.name().eq_ignore_ascii_case(name) {
+                true => self.translator.engine.clone(),
+                false => {
+                    let credentials = self.profile.credentials(name);
+                    if !credentials.is_configured(name) {
+                        return None;
+                    }
+                    credentials.engine(name).ok()?
+                }
+            };
+            let translator = Translator {
+                engine,
+                ..self.translator.clone()
+            };
+
+            translator.check_languages().ok().map(|_| translator)
+        });
+
+        let translations = futures::future::join_all(engines.map(|translator| async move {
+            let translation = translator.translate_detailed(text).await;
+            (translator.engine.name().to_string(), translation)
+        }))
+        .await;
+
+        for (name, translation) in translations {
+            match translation {
+                Ok(translation) => println!("{name:<10} {}", translation.text),
+                Err(err) => println!("{name:<10} error: {err}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! HTTP server with the API of LibreTranslate, `/translate`, `/detect` and `/languages`,
+//! so the tools made for it can use any engine
+
+use axum::{
+    extract::{ConnectInfo, FromRequest, Request, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Form, Json, Router,
+};
+use deeptranslator::{detection, Engine, Error, Libre, TextType, Translation, Translator};
+use futures::StreamExt;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use std::{
+    collections::{HashMap, VecDeque},
+    future::Future,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// Window of the rate limit
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Options of the server
+#[derive(Debug, Clone, Default)]
+pub struct ServeOptions {
+    pub address: String,
+    /// Keys the clients must send as `api_key`, any client is served when it is empty
+    pub api_keys: Vec<String>,
+    /// Requests of a client in a minute, by its key or its address, `0` for no limit
+    pub req_limit: usize,
+    /// Translations kept in memory, the oldest are dropped first, `0` to not keep them
+    pub cache_size: usize,
+    /// Key of detectlanguage.com for `/detect`, it answers `501 Not Implemented` without it
+    pub detection_api_key: Option<String>,
+}
+
+/// Text, source, target, format and alternatives of a translation
+type CacheKey = (String, String, String, String, usize);
+
+#[derive(Default)]
+struct Cache {
+    translations: HashMap<CacheKey, Translation>,
+    order: VecDeque<CacheKey>,
+}
+
+/// A translation run by the worker of the server
+type Job = Box<dyn FnOnce() -> Pin<Box<dyn Future<Output = ()>>> + Send>;
+
+struct Server {
+    translator: Translator,
+    /// The futures of the translator are not `Send` because of the documents of scraper, they
+    /// run on a thread of their own with a [`tokio::task::LocalSet`]
+    worker: tokio::sync::mpsc::UnboundedSender<Job>,
+    options: ServeOptions,
+    cache: Mutex<Cache>,
+    /// Start of the window of each client, with its requests
+    requests: Mutex<HashMap<String, (Instant, usize)>>,
+}
+
+/// One text or several, `q` can be both
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Texts {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(serde::Deserialize)]
+struct TranslateParams {
+    q: Texts,
+    #[serde(default = "auto")]
+    source: String,
+    target: String,
+    /// `text` or `html`
+    #[serde(default)]
+    format: Option<String>,
+    #[serde(default)]
+    alternatives: usize,
+    #[serde(default)]
+    api_key: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct DetectParams {
+    q: String,
+    #[serde(default)]
+    api_key: Option<String>,
+}
+
+#[derive(serde::Deserialize)]
+struct KeyParams {
+    #[serde(default)]
+    api_key: Option<String>,
+}
+
+#[inline(always)]
+fn auto() -> String {
+    "auto".into()
+}
+
+/// An error in the format of LibreTranslate
+#[inline(always)]
+fn error(status: StatusCode, message: impl Into<String>) -> Response {
+    (status, Json(json!({ "error": message.into() }))).into_response()
+}
+
+/// Status of the response for an error of the translator, the errors of the engine with
+/// its own key are errors of the server
+fn status(err: &Error) -> StatusCode {
+    let status = match err {
+        Error::Reqwest(err) => err.status(),
+        Error::Response(status) => Some(*status),
+        _ => None,
+    };
+
+    match (err, status.map(|status| status.as_u16())) {
+        (Error::LanguageNotSupported(_) | Error::NotValidLength { .. }, _) => {
+            StatusCode::BAD_REQUEST
+        }
+        (Error::TooManyRequests, _) | (_, Some(429 | 456)) => StatusCode::TOO_MANY_REQUESTS,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// An error of the translator. The url of the errors of reqwest is left out, Deepl, Libre
+/// and Qcri have the key of the server in it, and the whole error is logged
+fn engine_error(err: Error) -> Response {
+    let status = status(&err);
+
+    match err {
+        Error::Reqwest(err) => {
+            eprintln!("error: {err}");
+            error(status, err.without_url().to_string())
+        }
+        err => error(status, err.to_string()),
+    }
+}
+
+/// The parameters of the request, from its JSON body, its form or its query
+async fn params<T: DeserializeOwned>(request: Request) -> Result<T, Response> {
+    let json = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+
+    match json {
+        true => Json::<T>::from_request(request, &())
+            .await
+            .map(|Json(params)| params)
+            .map_err(|rejection| error(StatusCode::BAD_REQUEST, rejection.body_text())),
+        false => Form::<T>::from_request(request, &())
+            .await
+            .map(|Form(params)| params)
+            .map_err(|rejection| error(StatusCode::BAD_REQUEST, rejection.body_text())),
+    }
+}
+
+/// Start the thread that runs the translations, concurrently, until the server is dropped
+fn worker() -> tokio::sync::mpsc::UnboundedSender<Job> {
+    let (sender, mut jobs) = tokio::sync::mpsc::unbounded_channel::<Job>();
+
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("the runtime of the worker could not be built");
+
+        tokio::task::LocalSet::new().block_on(&runtime, async move {
+            while let Some(job) = jobs.recv().await {
+                tokio::task::spawn_local(job());
+            }
+        });
+    });
+
+    sender
+}
+
+impl Server {
+    /// Run the future on the worker and wait for its output
+    async fn local<F, Fut, T>(&self, future: F) -> T
+    where
+        F: FnOnce() -> Fut + Send + 'static,
+        Fut: Future<Output = T> + 'static,
+        T: Send + 'static,
+    {
+        let (sender, receiver) = tokio::sync::oneshot::channel();
+        let job: Job = Box::new(move || {
+            Box::pin(async move {
+                sender.send(future().await).ok();
+            })
+        });
+
+        self.worker.send(job).expect("the worker stopped");
+        receiver.await.expect("the translation panicked")
+    }
+
+    /// Check the key of the client and its requests of the last minute
+    fn admit(
+        &self,
+        api_key: Option<&str>,
+        address: SocketAddr,
+    ) -> Result<(), (StatusCode, String)> {
+        let api_key = api_key.filter(|api_key| !api_key.is_empty());
+        if !self.options.api_keys.is_empty()
+            && api_key.is_none_or(|api_key| !self.options.api_keys.iter().any(|key| key == api_key))
+        {
+            return Err((StatusCode::FORBIDDEN, "Invalid API key".into()));
+        }
+
+        let limit = self.options.req_limit;
+        if limit == 0 {
+            return Ok(());
+        }
+
+        let now = Instant::now();
+        let mut requests = self.requests.lock().unwrap();
+        if requests.len() > 1024 {
+            requests.retain(|_, (start, _)| now.duration_since(*start) < MINUTE);
+        }
+
+        let client = api_key.map_or_else(|| address.ip().to_string(), String::from);
+        let (start, count) = requests.entry(client).or_insert((now, 0));
+        if now.duration_since(*start) >= MINUTE {
+            (*start, *count) = (now, 0);
+        }
+
+        *count += 1;
+        match *count > limit {
+            true => Err((
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Slowdown: {limit} per 1 minute"),
+            )),
+            false => Ok(()),
+        }
+    }
+
+    /// Translation of the text, from the cache when it was translated before
+    async fn translation(&self, translator: &Translator, text: &str) -> Result<Translation, Error> {
+        let alternatives = match &translator.engine {
+            Engine::Libre(libre) => libre.alternatives,
+            _ => 0,
+        };
+        let key = (
+            text.to_string(),
+            translator.source.clone(),
+            translator.target.clone(),
+            translator.text_type.to_string(),
+            alternatives,
+        );
+
+        if let Some(translation) = self.cache.lock().unwrap().translations.get(&key) {
+            return Ok(translation.clone());
+        }
+
+        let translation = translator.translate_detailed(text).await?;
+
+        if self.options.cache_size > 0 {
+            let mut cache = self.cache.lock().unwrap();
+            if cache.order.len() >= self.options.cache_size {
+                if let Some(oldest) = cache.order.pop_front() {
+                    cache.translations.remove(&oldest);
+                }
+            }
+            if cache
+                .translations
+                .insert(key.clone(), translation.clone())
+                .is_none()
+            {
+                cache.order.push_back(key);
+            }
+        }
+
+        Ok(translation)
+    }
+}
+
+async fn translate(
+    State(server): State<Arc<Server>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    request: Request,
+) -> Response {
+    let params = match params::<TranslateParams>(request).await {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+    if let Err((status, message)) = server.admit(params.api_key.as_deref(), address) {
+        return error(status, message);
+    }
+
+    let mut translator = Translator {
+        source: params.source.clone(),
+        target: params.target.clone(),
+        ..server.translator.clone()
+    };
+    match params.format.as_deref() {
+        Some("html") => translator.text_type = TextType::Html,
+        Some("text") => translator.text_type = TextType::Plain,
+        _ => {}
+    }
+    if let Engine::Libre(Libre { alternatives, .. }) = &mut translator.engine {
+        *alternatives = params.alternatives;
+    }
+    if let Err(err) = translator.check_languages() {
+        return error(StatusCode::BAD_REQUEST, err.to_string());
+    }
+
+    let texts = match &params.q {
+        Texts::One(text) => vec![text.clone()],
+        Texts::Many(texts) => texts.clone(),
+    };
+    let translations = server
+        .local({
+            let (server, translator) = (server.clone(), translator.clone());
+            move || async move {
+                futures::stream::iter(
+                    texts
+                        .iter()
+                        .map(|text| server.translation(&translator, text)),
+                )
+                .buffered(translator.max_concurrency.max(1))
+                .collect::<Vec<_>>()
+                .await
+                .into_iter()
+                .collect::<Result<Vec<_>, _>>()
+            }
+        })
+        .await;
+
+    let translations = match translations {
+        Ok(translations) => translations,
+        Err(err) => return engine_error(err),
+    };
+
+    // The engines do not tell how sure they are of the language
+    let detected = |translation: &Translation| {
+        json!({
+            "confidence": null,
+            "language": translation.detected_language.clone().unwrap_or_default(),
+        })
+    };
+    let alternatives = |translation: &Translation| {
+        json!(translation
+            .alternatives
+            .iter()
+            .take(params.alternatives)
+            .collect::<Vec<_>>())
+    };
+    let pick = |field: &dyn Fn(&Translation) -> Value| match params.q {
+        Texts::One(_) => field(&translations[0]),
+        Texts::Many(_) => translations.iter().map(field).collect(),
+    };
+
+    let mut body = json!({
+        "translatedText": pick(&|translation| json!(translation.text)),
+    });
+    if params.source == "auto" {
+        body["detectedLanguage"] = pick(&detected);
+    }
+    if params.alternatives > 0 {
+        body["alternatives"] = pick(&alternatives);
+    }
+
+    Json(body).into_response()
+}
+
+async fn detect(
+    State(server): State<Arc<Server>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    request: Request,
+) -> Response {
+    let params = match params::<DetectParams>(request).await {
+        Ok(params) => params,
+        Err(response) => return response,
+    };
+    if let Err((status, message)) = server.admit(params.api_key.as_deref(), address) {
+        return error(status, message);
+    }
+
+    let Some(api_key) = &server.options.detection_api_key else {
+        return error(
+            StatusCode::NOT_IMPLEMENTED,
+            "the language detection is not configured, start the server with a key of \
+            detectlanguage.com",
+        );
+    };
+    let detection = detection::single(&params.q, api_key, true)
+        .await
+        .map(|detection| {
+            (
+                detection["language"].clone(),
+                detection["confidence"].clone(),
+            )
+        });
+
+    match detection {
+        Ok((language, confidence)) => {
+            Json(json!([{ "confidence": confidence, "language": language }])).into_response()
+        }
+        Err(err) => engine_error(err),
+    }
+}
+
+async fn languages(
+    State(server): State<Arc<Server>>,
+    ConnectInfo(address): ConnectInfo<SocketAddr>,
+    request: Request,
+) -> Response {
+    let api_key = params::<KeyParams>(request)
+        .await
+        .ok()
+        .and_then(|params| params.api_key);
+    if let Err((status, message)) = server.admit(api_key.as_deref(), address) {
+        return error(status, message);
+    }
+
+    let languages = super::sorted_languages(&server.translator.engine);
+    let targets = languages
+        .iter()
+        .map(|(_, code)| code.as_str())
+        .collect::<Vec<_>>();
+
+    Json(
+        languages
+            .iter()
+            .map(|(name, code)| json!({ "code": code, "name": name, "targets": targets }))
+            .collect::<Vec<_>>(),
+    )
+    .into_response()
+}
+
+/// Serve the translations of the translator until Ctrl-C
+pub async fn run(translator: Translator, options: ServeOptions) -> Result<(), Error> {
+    let listener = tokio::net::TcpListener::bind(&options.address).await?;
+    eprintln!(
+        "Serving {} on http://{}",
+        translator.engine.name(),
+        listener.local_addr()?
+    );
+
+    let server = Arc::new(Server {
+        translator,
+        worker: worker(),
+        options,
+        cache: Mutex::default(),
+        requests: Mutex::default(),
+    });
+    let app = Router::new()
+        .route("/translate", post(translate))
+        .route("/detect", post(detect))
+        .route("/languages", get(languages).post(languages))
+        .with_state(server);
+
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .with_graceful_shutdown(async {
+        tokio::signal::ctrl_c().await.ok();
+    })
+    .await?;
+
+    Ok(())
+}
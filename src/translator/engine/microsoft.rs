@@ -0,0 +1,530 @@
+use crate::translator::{response_status, Translation, Translator};
+use crate::{Engine, Error};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::{collections::HashMap, fmt, str::FromStr};
+
+pub(crate) const API_URL: &str = "https://api.cognitive.microsofttranslator.com";
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+pub struct Microsoft {
+    /// Get one api key here: https://learn.microsoft.com/azure/ai-services/translator/create-translator-resource
+    pub api_key: String,
+    /// Required when a regional or multi-service resource is used
+    pub region: String,
+    pub profanity_action: ProfanityAction,
+    pub profanity_marker: ProfanityMarker,
+    /// Include the alignment projection from source text to translated text, it is in
+    /// [`Translation::alignment`]
+    pub include_alignment: bool,
+    /// Script to transliterate the translation into, e.g. `Latn`. Leave it empty to not
+    /// transliterate, see [`Translator::transliteration_languages`]. The transliteration is in
+    /// [`Translation::transliteration`]
+    pub to_script: String,
+}
+
+impl Microsoft {
+    #[inline(always)]
+    pub fn new(api_key: &str, region: &str) -> Self {
+        Self {
+            api_key: api_key.to_string(),
+            region: region.to_string(),
+            ..Self::default()
+        }
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+/// How profanities should be treated in translations
+pub enum ProfanityAction {
+    #[default]
+    NoAction,
+    Marked,
+    Deleted,
+}
+
+impl FromStr for ProfanityAction {
+    type Err = ();
+
+    /// The names written by [`fmt::Display`] in any case, and `none` for `NoAction`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "none" | "noaction" => Ok(ProfanityAction::NoAction),
+            "marked" => Ok(ProfanityAction::Marked),
+            "deleted" => Ok(ProfanityAction::Deleted),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for ProfanityAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfanityAction::NoAction => "NoAction",
+            ProfanityAction::Marked => "Marked",
+            ProfanityAction::Deleted => "Deleted",
+        }
+        .fmt(f)
+    }
+}
+
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+/// How profanities are marked when [`ProfanityAction::Marked`] is used
+pub enum ProfanityMarker {
+    #[default]
+    Asterisk,
+    Tag,
+}
+
+impl FromStr for ProfanityMarker {
+    type Err = ();
+
+    /// The names written by [`fmt::Display`] in any case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "asterisk" => Ok(ProfanityMarker::Asterisk),
+            "tag" => Ok(ProfanityMarker::Tag),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for ProfanityMarker {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProfanityMarker::Asterisk => "Asterisk",
+            ProfanityMarker::Tag => "Tag",
+        }
+        .fmt(f)
+    }
+}
+
+/// A language returned by [`Translator::transliteration_languages`]
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TransliterationLanguage {
+    pub name: String,
+    pub native_name: String,
+    /// Scripts the language can be transliterated from, each one with its `to_scripts`
+    pub scripts: Vec<TransliterationScript>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct TransliterationScript {
+    /// Code identifying the script, e.g. `Latn` or `Jpan`
+    pub code: String,
+    pub name: String,
+    pub native_name: String,
+    /// Directionality, `ltr` or `rtl`
+    pub dir: String,
+    pub to_scripts: Vec<TransliterationScript>,
+}
+
+/// The translation in the script of [`Microsoft::to_script`]
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Transliteration {
+    /// Code of the script, e.g. `Latn`
+    pub script: String,
+    pub text: String,
+}
+
+/// Alignment of the source text and the translation, see [`Microsoft::include_alignment`]
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct Alignment {
+    /// Pairs of character ranges separated by spaces, `0:4-0:3` maps the characters 0 to 4 of
+    /// the source to the characters 0 to 3 of the translation
+    pub proj: String,
+}
+
+/// Result of a `/dictionary/lookup` call, the alternative translations of a word or idiom
+#[derive(Debug, Default, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DictionaryLookup {
+    /// Source term in the normalized form, used to request examples
+    pub normalized_source: String,
+    /// Source term in the form best suited for display to the end user
+    pub display_source: String,
+    pub translations: Vec<DictionaryTranslation>,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DictionaryTranslation {
+    pub normalized_target: String,
+    pub display_target: String,
+    /// Part of speech, e.g. `NOUN`, `VERB` or `ADJ`
+    pub pos_tag: String,
+    /// Value between 0.0 and 1.0, the confidences of all translations add up to 1.0
+    pub confidence: f64,
+    /// Word to display as a prefix of the translation, e.g. a gender article
+    pub prefix_word: String,
+    pub back_translations: Vec<BackTranslation>,
+}
+
+/// A translation of the target term back into the source language
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct BackTranslation {
+    pub normalized_text: String,
+    pub display_text: String,
+    /// Number of examples available for this pair
+    pub num_examples: usize,
+    /// Frequency of this pair in the data used by the service
+    pub frequency_count: usize,
+}
+
+/// Result of a `/dictionary/examples` call
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DictionaryExamples {
+    pub normalized_source: String,
+    pub normalized_target: String,
+    pub examples: Vec<DictionaryExample>,
+}
+
+/// A usage sentence, the full sentence is `prefix + term + suffix`
+#[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct DictionaryExample {
+    pub source_prefix: String,
+    pub source_term: String,
+    pub source_suffix: String,
+    pub target_prefix: String,
+    pub target_term: String,
+    pub target_suffix: String,
+}
+
+impl DictionaryExample {
+    #[inline(always)]
+    pub fn source_sentence(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.source_prefix, self.source_term, self.source_suffix
+        )
+    }
+
+    #[inline(always)]
+    pub fn target_sentence(&self) -> String {
+        format!(
+            "{}{}{}",
+            self.target_prefix, self.target_term, self.target_suffix
+        )
+    }
+}
+
+/// The first result of a response, the API returns one for each text of the request
+fn first<T: DeserializeOwned>(content: Value) -> Result<T, Error> {
+    let mut results: Vec<T> = serde_json::from_value(content)?;
+    match results.is_empty() {
+        true => Err(Error::TranslationNotFound),
+        false => Ok(results.swap_remove(0)),
+    }
+}
+
+/// The alternative translations of a `/translate` response, the detected language, the
+/// transliteration and the alignment of the first one are added to `details`
+pub(crate) fn translations(content: &Value, details: &mut Translation) -> Result<Value, Error> {
+    let Value::Array(translations) = &content[0]["translations"] else {
+        return Err(Error::TranslationNotFound);
+    };
+
+    details.detected_language = content[0]["detectedLanguage"]["language"]
+        .as_str()
+        .map(String::from);
+    if let Some(translation) = translations.first() {
+        details.transliteration = serde_json::from_value(translation["transliteration"].clone())?;
+        details.alignment = serde_json::from_value(translation["alignment"].clone())?;
+    }
+
+    Ok(translations
+        .iter()
+        .map(|translation| translation["text"].clone())
+        .collect())
+}
+
+impl Translator {
+    /// Send a request to an endpoint of the Microsoft Translator API v3
+    pub(crate) async fn microsoft_request(
+        &self,
+        endpoint: &str,
+        url_params: &[(&str, &str)],
+        body: &Value,
+    ) -> Result<Value, Error> {
+        let Engine::Microsoft(Microsoft {
+            api_key, region, ..
+        }) = &self.engine
+        else {
+            return Err(Error::EngineNotSupported(self.engine.name().to_string()));
+        };
+
+        let mut request = self
+            .client()
+            .build()?
+            .post(format!("{API_URL}/{endpoint}"))
+            .header("Ocp-Apim-Subscription-Key", api_key)
+            .query(&[("api-version", "3.0")])
+            .query(url_params)
+            .json(body);
+
+        if !region.is_empty() {
+            request = request.header("Ocp-Apim-Subscription-Region", region);
+        }
+
+        Ok(response_status(request.send().await?)?.json().await?)
+    }
+
+    /// Alternative translations for a word or idiom, with part of speech, confidence and
+    /// back-translations. Only available with [`Engine::Microsoft`]
+    pub async fn dictionary_lookup(&self, text: &str) -> Result<DictionaryLookup, Error> {
+        let content = self
+            .microsoft_request(
+                "dictionary/lookup",
+                &[("from", &self.source), ("to", &self.target)],
+                &serde_json::json!([{ "Text": text.trim() }]),
+            )
+            .await?;
+
+        first(content)
+    }
+
+    /// Usage sentences of `text` translated as `translation`, both should be the normalized
+    /// forms returned by [`Translator::dictionary_lookup`]. Only available with [`Engine::Microsoft`]
+    pub async fn dictionary_examples(
+        &self,
+        text: &str,
+        translation: &str,
+    ) -> Result<DictionaryExamples, Error> {
+        let content = self
+            .microsoft_request(
+                "dictionary/examples",
+                &[("from", &self.source), ("to", &self.target)],
+                &serde_json::json!([{ "Text": text.trim(), "Translation": translation.trim() }]),
+            )
+            .await?;
+
+        first(content)
+    }
+}
+
+impl Translator {
+    /// Languages that can be transliterated, with the scripts each one can convert from and
+    /// to. It does not need the api key of [`Engine::Microsoft`]
+    pub async fn transliteration_languages(
+        &self,
+    ) -> Result<HashMap<String, TransliterationLanguage>, Error> {
+        let response = self
+            .client()
+            .build()?
+            .get(format!("{API_URL}/languages"))
+            .query(&[("api-version", "3.0"), ("scope", "transliteration")])
+            .send()
+            .await?;
+
+        let mut content: Value = response_status(response)?.json().await?;
+        Ok(serde_json::from_value(content["transliteration"].take())?)
+    }
+
+    /// Convert `text` from one script to another, e.g. `Jpan` to `Latn`. The source language
+    /// of the translator is used as the language of the text. Only available with
+    /// [`Engine::Microsoft`]
+    pub async fn transliterate(
+        &self,
+        text: &str,
+        from_script: &str,
+        to_script: &str,
+    ) -> Result<Transliteration, Error> {
+        let content = self
+            .microsoft_request(
+                "transliterate",
+                &[
+                    ("language", &self.source),
+                    ("fromScript", from_script),
+                    ("toScript", to_script),
+                ],
+                &serde_json::json!([{ "Text": text.trim() }]),
+            )
+            .await?;
+
+        first(content)
+    }
+
+    /// Split `text` into sentences. The source language of the translator is used as a hint,
+    /// unless it is `auto`. Only available with [`Engine::Microsoft`]
+    pub async fn break_sentence(&self, text: &str) -> Result<Vec<String>, Error> {
+        let mut url_params = vec![];
+        if self.source != "auto" {
+            url_params.push(("language", self.source.as_str()));
+        }
+
+        let content = self
+            .microsoft_request(
+                "breaksentence",
+                &url_params,
+                &serde_json::json!([{ "Text": text }]),
+            )
+            .await?;
+
+        let Value::Array(lengths) = &content[0]["sentLen"] else {
+            return Err(Error::TranslationNotFound);
+        };
+
+        // lengths are given in characters
+        let mut chars = text.chars();
+        Ok(lengths
+            .iter()
+            .map(|len| {
+                let len = len.as_u64().unwrap_or_default() as usize;
+                chars.by_ref().take(len).collect()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn profanity_options_round_trip() {
+        for action in [
+            ProfanityAction::NoAction,
+            ProfanityAction::Marked,
+            ProfanityAction::Deleted,
+        ] {
+            assert_eq!(action.to_string().parse(), Ok(action));
+        }
+        for marker in [ProfanityMarker::Asterisk, ProfanityMarker::Tag] {
+            assert_eq!(marker.to_string().parse(), Ok(marker));
+        }
+
+        assert_eq!("none".parse(), Ok(ProfanityAction::NoAction));
+        assert_eq!("marked".parse(), Ok(ProfanityAction::Marked));
+        assert_eq!("tag".parse(), Ok(ProfanityMarker::Tag));
+        assert_eq!("hidden".parse::<ProfanityAction>(), Err(()));
+    }
+
+    #[test]
+    fn translate_response() {
+        let content = json!([{
+            "detectedLanguage": {"language": "ja", "score": 1.0},
+            "translations": [{
+                "text": "Hello",
+                "to": "en",
+                "transliteration": {"script": "Latn", "text": "Hello"},
+                "alignment": {"proj": "0:4-0:4"}
+            }]
+        }]);
+        let mut details = Translation::default();
+
+        assert_eq!(
+            translations(&content, &mut details).unwrap(),
+            json!(["Hello"])
+        );
+        assert_eq!(details.detected_language.as_deref(), Some("ja"));
+        assert_eq!(details.alignment.unwrap().proj, "0:4-0:4");
+        assert_eq!(details.transliteration.unwrap().script, "Latn");
+
+        let mut details = Translation::default();
+        let content = json!([{"translations": [{"text": "Hallo", "to": "de"}]}]);
+        assert_eq!(
+            translations(&content, &mut details).unwrap(),
+            json!(["Hallo"])
+        );
+        assert_eq!(details, Translation::default());
+
+        assert!(matches!(
+            translations(&json!({"error": {"code": 401000}}), &mut details),
+            Err(Error::TranslationNotFound)
+        ));
+    }
+
+    #[test]
+    fn dictionary_response() {
+        let lookup: DictionaryLookup = first(json!([{
+            "normalizedSource": "fly",
+            "displaySource": "fly",
+            "translations": [{
+                "normalizedTarget": "volar",
+                "displayTarget": "volar",
+                "posTag": "VERB",
+                "confidence": 0.4316,
+                "prefixWord": "",
+                "backTranslations": [
+                    {"normalizedText": "fly", "displayText": "fly", "numExamples": 15, "frequencyCount": 4637}
+                ]
+            }]
+        }]))
+        .unwrap();
+
+        assert_eq!(lookup.display_source, "fly");
+        assert_eq!(lookup.translations[0].pos_tag, "VERB");
+        assert_eq!(lookup.translations[0].back_translations[0].num_examples, 15);
+
+        let examples: DictionaryExamples = first(json!([{
+            "normalizedSource": "fly",
+            "normalizedTarget": "volar",
+            "examples": [{
+                "sourcePrefix": "They need machines to ",
+                "sourceTerm": "fly",
+                "sourceSuffix": ".",
+                "targetPrefix": "Necesitan máquinas para ",
+                "targetTerm": "volar",
+                "targetSuffix": "."
+            }]
+        }]))
+        .unwrap();
+
+        assert_eq!(
+            examples.examples[0].source_sentence(),
+            "They need machines to fly."
+        );
+        assert_eq!(
+            examples.examples[0].target_sentence(),
+            "Necesitan máquinas para volar."
+        );
+        assert!(matches!(
+            first::<DictionaryLookup>(json!([])),
+            Err(Error::TranslationNotFound)
+        ));
+    }
+
+    #[test]
+    fn transliterate_response() {
+        let transliteration: Transliteration =
+            first(json!([{"text": "konnnichiha", "script": "Latn"}])).unwrap();
+
+        assert_eq!(
+            transliteration,
+            Transliteration {
+                script: "Latn".into(),
+                text: "konnnichiha".into(),
+            }
+        );
+    }
+}
@@ -1,8 +1,15 @@
 mod libre;
+mod microsoft;
 mod qcri;
 use std::{fmt, str::FromStr};
 
 pub use libre::Libre;
+pub use microsoft::{
+    Alignment, BackTranslation, DictionaryExample, DictionaryExamples, DictionaryLookup,
+    DictionaryTranslation, Microsoft, ProfanityAction, ProfanityMarker, Transliteration,
+    TransliterationLanguage, TransliterationScript,
+};
+pub(crate) use microsoft::translations as microsoft_translations;
 pub use qcri::Qcri;
 
 #[macro_export]
@@ -57,6 +64,51 @@ impl fmt::Display for Version {
     }
 }
 
+#[derive(
+    Debug,
+    Default,
+    Copy,
+    Clone,
+    Eq,
+    PartialEq,
+    Ord,
+    PartialOrd,
+    serde::Deserialize,
+    serde::Serialize,
+)]
+/// Represent the kind of text sent to the translator
+pub enum TextType {
+    #[default]
+    Plain,
+    Html,
+    /// Only the prose is translated, see [`crate::MarkdownOptions`]
+    Markdown,
+}
+
+impl FromStr for TextType {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(TextType::Plain),
+            "html" => Ok(TextType::Html),
+            "markdown" => Ok(TextType::Markdown),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for TextType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextType::Plain => "plain",
+            TextType::Html => "html",
+            TextType::Markdown => "markdown",
+        }
+        .fmt(f)
+    }
+}
+
 /// Enum that wraps engines, which use the translator under the hood to translate word(s)
 #[derive(Debug, Default, Eq, PartialEq, Clone, serde::Deserialize, serde::Serialize)]
 pub enum Engine {
@@ -75,10 +127,7 @@ pub enum Engine {
     Linguee {
         return_all: bool,
     },
-    Microsoft {
-        api_key: String,
-        region: String,
-    },
+    Microsoft(Microsoft),
     MyMemory {
         email: String,
         /// set to True to return all synonym/similars of the translated text
@@ -96,6 +145,39 @@ pub enum Engine {
     },
 }
 
+impl FromStr for Engine {
+    type Err = crate::Error;
+
+    /// The engine with the name, as the CLI writes it, without credentials
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "deepl" => Ok(Self::Deepl {
+                api_key: String::new(),
+                version: Version::default(),
+                use_free_api: false,
+            }),
+            "google" => Ok(Self::Google),
+            "libre" => Ok(Self::Libre(Libre::default())),
+            "linguee" => Ok(Self::Linguee { return_all: false }),
+            "microsoft" => Ok(Self::Microsoft(Microsoft::default())),
+            "mymemory" => Ok(Self::MyMemory {
+                email: String::new(),
+                return_all: false,
+            }),
+            "papago" => Ok(Self::Papago {
+                client_id: String::new(),
+                secret_key: String::new(),
+            }),
+            "pons" => Ok(Self::Pons),
+            "qcri" => Ok(Self::Qcri(Qcri::default())),
+            "yandex" => Ok(Self::Yandex {
+                api_key: String::new(),
+            }),
+            _ => Err(crate::Error::EngineNotSupported(s.to_string())),
+        }
+    }
+}
+
 impl Engine {
     #[inline(always)]
     pub fn base_url(&self) -> String {
@@ -111,9 +193,7 @@ impl Engine {
             }
             Self::Libre(libre) => format!("{}/translate", libre.url),
             Self::Linguee { .. } => "https://www.linguee.com/".into(),
-            Self::Microsoft { .. } => {
-                "https://api.cognitive.microsofttranslator.com/translate?api-version=3.0".into()
-            }
+            Self::Microsoft { .. } => format!("{}/translate?api-version=3.0", microsoft::API_URL),
             Self::MyMemory { .. } => "http://api.mymemory.translated.net/get".into(),
             // "https://papago.naver.com/"
             Self::Papago { .. } => "https://openapi.naver.com/v1/papago/n2mt".into(),
@@ -123,6 +203,16 @@ impl Engine {
         }
     }
 
+    /// Whether the engine translates html by itself, other engines translate the text nodes
+    /// one by one, see [`TextType::Html`]
+    #[inline(always)]
+    pub const fn supports_html(&self) -> bool {
+        matches!(
+            self,
+            Self::Deepl { .. } | Self::Libre(..) | Self::Microsoft(..) | Self::Yandex { .. }
+        )
+    }
+
     #[inline(always)]
     pub const fn name(&self) -> &str {
         match &self {
@@ -139,6 +229,16 @@ impl Engine {
         }
     }
 
+    /// Whether the engine translates from or into the language, the codes are compared
+    /// without case and, when there is no exact match, without the region, `pt-BR` and `pt`
+    pub fn supports_language(&self, code: &str) -> bool {
+        let primary = |code: &str| code.split(['-', '_']).next().unwrap_or_default().to_lowercase();
+        let languages = self.supported_languages();
+
+        languages.values().any(|known| known.eq_ignore_ascii_case(code))
+            || languages.values().any(|known| primary(known) == primary(code))
+    }
+
     #[inline(always)]
     pub fn supported_languages(&self) -> LanguagesToCodes {
         match &self {
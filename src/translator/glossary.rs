@@ -0,0 +1,238 @@
+use super::{mask::Masked, memory::primary};
+use std::collections::BTreeMap;
+
+/// Terms with a fixed translation, they are replaced by tokens before the text is sent
+/// to the engine, so it works the same with every [`crate::Engine`]. The terms that the
+/// engine loses or duplicates are in [`crate::Translation::glossary_violations`]
+///
+///```rust,no_run
+/// use deeptranslator::{Engine, Translator};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let mut translator = Translator::with_engine("en", "de", Engine::Google);
+///     translator.glossary.protect("Acme Cloud");
+///     translator.glossary.add_term("cart", "de", "Warenkorb");
+///
+///     println!("{:?}", translator.translate("Add Acme Cloud to the cart").await);
+/// }
+///```
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Glossary {
+    /// Terms kept as they are in the source text, e.g. brand and product names
+    #[serde(default)]
+    pub do_not_translate: Vec<String>,
+    /// Preferred translation of the terms, by target language
+    #[serde(default)]
+    pub terms: BTreeMap<String, BTreeMap<String, String>>,
+    /// Whether the terms match the text only with the same case
+    #[serde(default)]
+    pub case_sensitive: bool,
+}
+
+/// Whether the target languages are the same, `pt` matches `pt-BR`
+#[inline(always)]
+fn same_language(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b) || primary(a).eq_ignore_ascii_case(primary(b))
+}
+
+impl Glossary {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.do_not_translate.is_empty() && self.terms.is_empty()
+    }
+
+    /// Keep the term as it is in the translations
+    #[inline(always)]
+    pub fn protect(&mut self, term: &str) {
+        self.do_not_translate.push(term.to_string());
+    }
+
+    /// Translate the term always as `translation` into the target language
+    #[inline(always)]
+    pub fn add_term(&mut self, term: &str, target: &str, translation: &str) {
+        self.terms
+            .entry(term.to_string())
+            .or_default()
+            .insert(target.to_string(), translation.to_string());
+    }
+
+    /// The terms for the target language with their translation, `None` when they are kept
+    /// as they are. The longest terms come first, so they win over the terms they contain
+    fn entries(&self, target: &str) -> Vec<(&str, Option<&str>)> {
+        let mut entries = self
+            .terms
+            .iter()
+            .filter_map(|(term, translations)| {
+                let translation = translations.get(target).or_else(|| {
+                    translations.iter().find_map(|(language, translation)| {
+                        same_language(language, target).then_some(translation)
+                    })
+                })?;
+                Some((term.as_str(), Some(translation.as_str())))
+            })
+            .chain(
+                self.do_not_translate
+                    .iter()
+                    .map(|term| (term.as_str(), None)),
+            )
+            .filter(|(term, _)| !term.trim().is_empty())
+            .collect::<Vec<_>>();
+
+        entries.sort_by_key(|(term, _)| std::cmp::Reverse(term.chars().count()));
+        entries
+    }
+
+    /// Length of `term` at the start of `text`, as a whole word
+    fn match_term(&self, text: &str, term: &str, previous: Option<char>) -> Option<usize> {
+        if previous.is_some_and(char::is_alphanumeric) {
+            return None;
+        }
+
+        let mut chars = text.char_indices();
+        for expected in term.chars() {
+            let (_, c) = chars.next()?;
+            let equal = match self.case_sensitive {
+                true => c == expected,
+                false => c.to_lowercase().eq(expected.to_lowercase()),
+            };
+
+            if !equal {
+                return None;
+            }
+        }
+
+        match chars.next() {
+            None => Some(text.len()),
+            Some((_, c)) if c.is_alphanumeric() => None,
+            Some((index, _)) => Some(index),
+        }
+    }
+
+    /// Protect the terms of the glossary in the masked text
+    pub(crate) fn mask(&self, masked: &Masked, target: &str) -> Option<Masked> {
+        let entries = self.entries(target);
+        if entries.is_empty() {
+            return None;
+        }
+
+        Some(masked.with_terms(|text, previous| {
+            entries.iter().find_map(|(term, translation)| {
+                let len = self.match_term(text, term, previous)?;
+                Some((len, translation.unwrap_or(&text[..len]).to_string()))
+            })
+        }))
+    }
+
+    /// Terms of the source text whose fixed translation is not in the translation, useful
+    /// to check translations that were not made with this glossary
+    pub fn violations(&self, text: &str, translation: &str, target: &str) -> Vec<String> {
+        let contains = |haystack: &str, needle: &str| {
+            let mut previous = None;
+            haystack.char_indices().any(|(index, c)| {
+                let found = self
+                    .match_term(&haystack[index..], needle, previous)
+                    .is_some();
+                previous = Some(c);
+                found
+            })
+        };
+
+        self.entries(target)
+            .into_iter()
+            .filter(|(term, expected)| {
+                contains(text, term) && !contains(translation, expected.unwrap_or(term))
+            })
+            .map(|(term, _)| term.to_string())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The text with the terms of the glossary replaced by their translation
+    fn apply(glossary: &Glossary, text: &str, target: &str) -> (String, String) {
+        let mut masked = Masked::new(&[]);
+        masked.push_text(text);
+        let masked = glossary.mask(&masked, target).unwrap();
+        let translated = masked.unmask(&masked.text).unwrap().0;
+
+        (masked.text, translated)
+    }
+
+    fn glossary() -> Glossary {
+        let mut glossary = Glossary::new();
+        glossary.protect("Acme");
+        glossary.protect("Acme Cloud");
+        glossary.add_term("cart", "de", "Warenkorb");
+        glossary.add_term("cart", "pt", "carrinho");
+        glossary
+    }
+
+    #[test]
+    fn longest_term_first() {
+        let (masked, translated) = apply(&glossary(), "Acme Cloud by Acme", "de");
+
+        assert_eq!(masked, "__PH0__ by __PH1__");
+        assert_eq!(translated, "Acme Cloud by Acme");
+    }
+
+    #[test]
+    fn whole_words_only() {
+        let (masked, translated) = apply(&glossary(), "cart, carton, e-cart", "de");
+
+        assert_eq!(masked, "__PH0__, carton, e-__PH1__");
+        assert_eq!(translated, "Warenkorb, carton, e-Warenkorb");
+    }
+
+    #[test]
+    fn case() {
+        let mut glossary = glossary();
+        assert_eq!(apply(&glossary, "CART", "de").1, "Warenkorb");
+        assert_eq!(apply(&glossary, "acme cloud", "de").1, "acme cloud");
+
+        glossary.case_sensitive = true;
+        assert_eq!(apply(&glossary, "CART", "de").0, "CART");
+    }
+
+    #[test]
+    fn languages() {
+        assert_eq!(apply(&glossary(), "cart", "pt-BR").1, "carrinho");
+        assert_eq!(apply(&glossary(), "cart", "fr").0, "cart");
+        assert!(Glossary::new().mask(&Masked::new(&[]), "de").is_none());
+    }
+
+    #[test]
+    fn violations() {
+        let glossary = glossary();
+
+        assert_eq!(
+            glossary.violations("Add Acme to the cart", "Acme zum Korb", "de"),
+            ["cart"]
+        );
+        assert!(glossary
+            .violations("Add Acme to the cart", "Acme zum Warenkorb", "de")
+            .is_empty());
+    }
+
+    #[test]
+    fn file() {
+        let glossary: Glossary = serde_json::from_str(
+            r#"{"do_not_translate": ["Acme"], "terms": {"cart": {"de": "Warenkorb"}}}"#,
+        )
+        .unwrap();
+        assert_eq!(glossary.do_not_translate, ["Acme"]);
+        assert_eq!(glossary.terms["cart"]["de"], "Warenkorb");
+        assert!(!glossary.case_sensitive);
+
+        let empty: Glossary = serde_json::from_str("{}").unwrap();
+        assert!(empty.is_empty());
+    }
+}
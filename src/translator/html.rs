@@ -0,0 +1,174 @@
+use super::{mask::Masked, Translation, Translator};
+use crate::{
+    formats::xml::{attribute, closing_tag, next_tag},
+    Error, PlaceholderSyntax,
+};
+use futures::StreamExt;
+use scraper::{Html, Node};
+use serde_json::Value;
+
+/// Elements whose content is never translated
+const UNTRANSLATABLE_ELEMENTS: [&str; 5] = ["code", "kbd", "samp", "script", "style"];
+
+/// Take the text of a translation, engines that return all the alternatives give the
+/// first one
+pub(crate) fn first_text(value: Value) -> Option<String> {
+    match value {
+        Value::String(text) => Some(text),
+        Value::Array(all) => all.into_iter().find_map(first_text),
+        Value::Object(mut object) => object.remove("text").and_then(first_text),
+        _ => None,
+    }
+}
+
+/// Mask the elements whose content is never translated, for the engines that translate
+/// html by themselves. The other tags are sent to the engine as they are
+pub(crate) fn mask_untranslatable(text: &str, placeholders: &[PlaceholderSyntax]) -> Masked {
+    let mut masked = Masked::new(placeholders);
+    let (mut from, mut search) = (0, 0);
+
+    while let Some(tag) = next_tag(text, search) {
+        let untranslatable = !tag.closing
+            && !tag.empty
+            && (UNTRANSLATABLE_ELEMENTS
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(tag.name))
+                || attribute(tag.raw(text), "translate") == Some("no"));
+
+        match closing_tag(text, &tag).filter(|_| untranslatable) {
+            Some(close) => {
+                masked.push_text(&text[from..tag.range.start]);
+                masked.push_protected(&text[tag.range.start..close.range.end]);
+                from = close.range.end;
+                search = from;
+            }
+            None => search = tag.range.end,
+        }
+    }
+
+    masked.push_text(&text[from..]);
+    masked
+}
+
+impl Translator {
+    /// Translate the text nodes of a html document or fragment one by one, for the engines
+    /// without native html support. Tags, attributes and the content of `<code>`, `<script>`
+    /// and similar elements are left untouched, as well as elements with `translate="no"`.
+    /// The glossary terms the engine did not keep are added to `details`
+    pub(crate) async fn translate_html(
+        &self,
+        text: &str,
+        details: &mut Translation,
+    ) -> Result<Value, Error> {
+        let lowercase = text.trim_start().to_lowercase();
+        let is_document = lowercase.starts_with("<!doctype") || lowercase.starts_with("<html");
+        let mut html = if is_document {
+            Html::parse_document(text)
+        } else {
+            Html::parse_fragment(text)
+        };
+
+        let text_nodes = html
+            .tree
+            .root()
+            .descendants()
+            .filter_map(|node| {
+                let Node::Text(text) = node.value() else {
+                    return None;
+                };
+
+                let skip = node.ancestors().any(|ancestor| {
+                    ancestor.value().as_element().is_some_and(|element| {
+                        UNTRANSLATABLE_ELEMENTS.contains(&element.name())
+                            || element.attr("translate") == Some("no")
+                    })
+                });
+
+                (!skip && !text.trim().is_empty()).then(|| (node.id(), text.to_string()))
+            })
+            .collect::<Vec<_>>();
+
+        let translations = futures::stream::iter(text_nodes.iter().map(|(_, text)| async move {
+            let mut masked = Masked::new(&self.placeholders);
+            masked.push_text(text.trim());
+            if !masked.has_text() {
+                return Ok(Translation {
+                    text: text.trim().to_string(),
+                    ..Translation::default()
+                });
+            }
+
+            self.translate_masked_detailed(&masked).await
+        }))
+        .buffered(self.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        for ((id, original), translation) in text_nodes.into_iter().zip(translations) {
+            let Translation {
+                text: translation,
+                glossary_violations,
+                ..
+            } = translation?;
+            details.glossary_violations.extend(glossary_violations);
+            // Keep the spaces around the text, they separate it from the sibling elements
+            let leading = &original[..original.len() - original.trim_start().len()];
+            let trailing = &original[original.trim_end().len()..];
+
+            if let Some(mut node) = html.tree.get_mut(id) {
+                if let Node::Text(text) = node.value() {
+                    text.text = format!("{leading}{translation}{trailing}").into();
+                }
+            }
+        }
+
+        Ok(Value::String(if is_document {
+            html.html()
+        } else {
+            html.root_element().inner_html()
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formats::translator;
+
+    #[test]
+    fn untranslatable_elements_are_masked() {
+        let html = "<p title=\"Code\">Run <code>ls <b>-l</b></code> and \
+            <SCRIPT>var a = \"<b>\";</SCRIPT><span translate=\"no\">Acme</span><br/></p>";
+        let masked = mask_untranslatable(html, &[]);
+
+        assert_eq!(
+            masked.text,
+            "<p title=\"Code\">Run __PH0__ and __PH1____PH2__<br/></p>"
+        );
+        assert_eq!(masked.original(), html);
+    }
+
+    #[tokio::test]
+    async fn text_nodes_are_translated() {
+        let translator = translator(&[("Hello", "Hallo"), ("world", "Welt"), ("& more", "& mehr")]);
+        let html = "<p class=\"a\">Hello <b>world</b> &amp; more<br/><code>world</code>\
+            <style>b { color: red }</style><img src=\"cat.png\" alt=\"Cat\"/></p>";
+
+        let translation = translator
+            .translate_html(html, &mut Translation::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            translation,
+            "<p class=\"a\">Hallo <b>Welt</b> &amp; mehr<br><code>world</code>\
+            <style>b { color: red }</style><img alt=\"Cat\" src=\"cat.png\"></p>"
+        );
+    }
+
+    #[test]
+    fn first_text_of_the_alternatives() {
+        let value = serde_json::json!([{"text": "Hallo"}, {"text": "Servus"}]);
+        assert_eq!(first_text(value).as_deref(), Some("Hallo"));
+        assert_eq!(first_text(Value::Null), None);
+    }
+}
@@ -0,0 +1,751 @@
+use super::{mask::Masked, Translation, Translator};
+use crate::{Error, PlaceholderSyntax};
+use futures::StreamExt;
+use serde_json::Value;
+
+/// Which parts of a markdown document are translated besides the prose, see
+/// [`crate::TextType::Markdown`]
+#[derive(Debug, Copy, Clone, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
+pub struct MarkdownOptions {
+    /// Translate the text of the links, `[text](url)`
+    pub link_text: bool,
+    /// Translate the alternative text of the images, `![alt](url)`
+    pub image_alt: bool,
+}
+
+impl Default for MarkdownOptions {
+    fn default() -> Self {
+        Self {
+            link_text: true,
+            image_alt: true,
+        }
+    }
+}
+
+/// A part of the document, only the prose is sent to the engine
+enum Piece {
+    Verbatim(String),
+    Prose(Masked),
+}
+
+#[derive(PartialEq)]
+enum LineKind {
+    /// Only indentation and block quotes before the text
+    Plain,
+    ListItem,
+    Heading,
+}
+
+/// Lines of a paragraph are joined, so the engine sees whole sentences
+struct Paragraph {
+    prefix: String,
+    /// The lines of the paragraph separated by line breaks
+    text: String,
+    suffix: String,
+    /// What is between each line and the next one, the end of the line and the block
+    /// markers of the next one
+    gaps: Vec<String>,
+}
+
+#[inline(always)]
+fn split_eol(line: &str) -> (&str, &str) {
+    let content = line.trim_end_matches(['\n', '\r']);
+    (content, &line[content.len()..])
+}
+
+#[inline(always)]
+fn quotes(prefix: &str) -> usize {
+    prefix.matches('>').count()
+}
+
+/// Remove the indentation and the block quote markers
+#[inline(always)]
+fn strip_quotes(content: &str) -> &str {
+    content.trim_start_matches([' ', '\t', '>'])
+}
+
+/// Character and length of a code fence opening or closing the line
+fn fence(content: &str) -> Option<(char, usize)> {
+    let trimmed = strip_quotes(content);
+    let marker = trimmed.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let len = trimmed.len() - trimmed.trim_start_matches(marker).len();
+
+    (len >= 3).then_some((marker, len))
+}
+
+/// Thematic breaks, `***`, and setext heading underlines, `===`
+fn is_break(trimmed: &str) -> bool {
+    let mut markers = trimmed.chars().filter(|c| !c.is_whitespace());
+    let Some(marker @ ('-' | '*' | '_' | '=')) = markers.next() else {
+        return false;
+    };
+
+    let count = 1 + markers.clone().count();
+    markers.all(|c| c == marker) && (marker == '=' || count >= 3)
+}
+
+/// The line below the header of a table, `| --- | :-: |`
+fn is_delimiter_row(content: &str) -> bool {
+    let trimmed = strip_quotes(content).trim();
+    trimmed.contains('|')
+        && trimmed.contains('-')
+        && trimmed
+            .chars()
+            .all(|c| matches!(c, '|' | ':' | '-' | ' ' | '\t'))
+}
+
+/// Length of the block markers before the text: indentation, block quotes, list markers,
+/// task boxes and heading markers
+fn split_prefix(content: &str) -> (usize, LineKind) {
+    let bytes = content.as_bytes();
+    let mut i = 0;
+
+    loop {
+        while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+            i += 1;
+        }
+
+        if i < bytes.len() && bytes[i] == b'>' {
+            i += 1;
+            continue;
+        }
+
+        break;
+    }
+
+    let is_space = |i: usize| i >= bytes.len() || bytes[i] == b' ' || bytes[i] == b'\t';
+    let digits = bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let hashes = bytes[i..].iter().take_while(|b| **b == b'#').count();
+
+    let (mut i, kind) = if i < bytes.len() && b"-*+".contains(&bytes[i]) && is_space(i + 1) {
+        (i + 1, LineKind::ListItem)
+    } else if (1..=9).contains(&digits)
+        && i + digits < bytes.len()
+        && (bytes[i + digits] == b'.' || bytes[i + digits] == b')')
+        && is_space(i + digits + 1)
+    {
+        (i + digits + 1, LineKind::ListItem)
+    } else if (1..=6).contains(&hashes) && is_space(i + hashes) {
+        (i + hashes, LineKind::Heading)
+    } else {
+        return (i, LineKind::Plain);
+    };
+
+    while i < bytes.len() && (bytes[i] == b' ' || bytes[i] == b'\t') {
+        i += 1;
+    }
+
+    if kind == LineKind::ListItem {
+        for task in ["[ ] ", "[x] ", "[X] "] {
+            if content[i..].starts_with(task) {
+                i += task.len();
+            }
+        }
+    }
+
+    (i, kind)
+}
+
+/// Index of the `]` closing the bracket opened at `open`, and the end of the link
+/// destination or reference that follows it
+fn link_end(text: &str, open: usize) -> Option<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let matching = |start: usize, open: u8, close: u8| {
+        let mut depth = 0;
+        let mut i = start;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 1,
+                c if c == open => depth += 1,
+                c if c == close => {
+                    depth -= 1;
+                    if depth == 0 {
+                        return Some(i);
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+
+        None
+    };
+
+    let close = matching(open, b'[', b']')?;
+    let end = match bytes.get(close + 1) {
+        Some(b'(') => matching(close + 1, b'(', b')')?,
+        Some(b'[') => matching(close + 1, b'[', b']')?,
+        _ => return None,
+    };
+
+    Some((close, end + 1))
+}
+
+/// Length of an inline html tag or autolink at the start of `text`
+fn inline_tag(text: &str) -> Option<usize> {
+    let end = text.find('>')?;
+    let inner = &text[1..end];
+    let first = inner.chars().next()?;
+
+    let is_tag = first.is_ascii_alphabetic() || matches!(first, '/' | '!' | '?');
+    let is_autolink = !inner.contains(char::is_whitespace) && inner.contains([':', '@']);
+
+    (!inner.contains(['<', '\n']) && (is_tag || is_autolink)).then_some(end + 1)
+}
+
+/// Length of a bare url at the start of `text`
+fn bare_url(text: &str) -> Option<usize> {
+    if !["http://", "https://", "www."]
+        .iter()
+        .any(|scheme| text.starts_with(scheme))
+    {
+        return None;
+    }
+
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    Some(
+        text[..end]
+            .trim_end_matches(['.', ',', ':', ';', '!', '?', ')'])
+            .len(),
+    )
+}
+
+/// Push a text of the paragraph, each line break is replaced by its gap and protected
+fn push_lines(masked: &mut Masked, text: &str, gaps: &mut std::slice::Iter<String>) {
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            masked.push_space(gaps.next().map_or("\n", String::as_str));
+        }
+        masked.push_text(line);
+    }
+}
+
+/// Protect the inline code, html, urls and link destinations of a prose text. The line
+/// breaks of the text are replaced by the `gaps` and kept as they are
+fn mask_inline(
+    text: &str,
+    gaps: &[String],
+    options: MarkdownOptions,
+    placeholders: &[PlaceholderSyntax],
+) -> Masked {
+    let mut masked = Masked::new(placeholders);
+    // Ranges that close the links and images which text is translated
+    let mut closers: Vec<(usize, usize)> = Vec::new();
+    let mut gaps = gaps.iter();
+    let mut start = 0;
+    let mut i = 0;
+
+    let mut protect = |masked: &mut Masked, start: &mut usize, from: usize, to: usize| {
+        push_lines(masked, &text[*start..from], &mut gaps);
+        let mut protected = String::new();
+        for (index, line) in text[from..to].split('\n').enumerate() {
+            if index > 0 {
+                protected.push_str(gaps.next().map_or("\n", String::as_str));
+            }
+            protected.push_str(line);
+        }
+        masked.push_protected(&protected);
+        *start = to;
+    };
+
+    while i < text.len() {
+        if let Some(&(close, end)) = closers.last() {
+            if i == close {
+                protect(&mut masked, &mut start, close, end);
+                closers.pop();
+                i = end;
+                continue;
+            }
+        }
+
+        let rest = &text[i..];
+        let c = rest.chars().next().unwrap_or_default();
+
+        match c {
+            '\\' => i += 1 + rest[1..].chars().next().map_or(0, char::len_utf8),
+            '`' => {
+                let run = rest.len() - rest.trim_start_matches('`').len();
+                let mut closing = None;
+                let mut j = i + run;
+
+                while let Some(k) = text[j..].find('`').map(|k| j + k) {
+                    let len = text[k..].len() - text[k..].trim_start_matches('`').len();
+                    if len == run {
+                        closing = Some(k + len);
+                        break;
+                    }
+                    j = k + len;
+                }
+
+                if let Some(end) = closing {
+                    protect(&mut masked, &mut start, i, end);
+                    i = end;
+                } else {
+                    i += run;
+                }
+            }
+            '<' => match inline_tag(rest) {
+                Some(len) => {
+                    protect(&mut masked, &mut start, i, i + len);
+                    i += len;
+                }
+                None => i += 1,
+            },
+            '[' if rest.starts_with("[^") => {
+                // Footnote references
+                let end = rest.find(']').map_or(text.len(), |end| i + end + 1);
+                protect(&mut masked, &mut start, i, end);
+                i = end;
+            }
+            '!' | '[' => {
+                let open = if c == '!' { i + 1 } else { i };
+                let translate = if c == '!' {
+                    options.image_alt
+                } else {
+                    options.link_text
+                };
+
+                match link_end(text, open).filter(|_| text[open..].starts_with('[')) {
+                    Some((close, end)) if translate && close > open + 1 => {
+                        protect(&mut masked, &mut start, i, open + 1);
+                        closers.push((close, end));
+                        i = open + 1;
+                    }
+                    Some((_, end)) => {
+                        protect(&mut masked, &mut start, i, end);
+                        i = end;
+                    }
+                    None => i += 1,
+                }
+            }
+            'h' | 'w' if !text[..i].ends_with(char::is_alphanumeric) => match bare_url(rest) {
+                Some(len) => {
+                    protect(&mut masked, &mut start, i, i + len);
+                    i += len;
+                }
+                None => i += 1,
+            },
+            _ => i += c.len_utf8(),
+        }
+    }
+
+    push_lines(&mut masked, &text[start..], &mut gaps);
+    masked
+}
+
+/// Split the document into the parts to keep and the prose to translate
+fn parse(text: &str, options: MarkdownOptions, placeholders: &[PlaceholderSyntax]) -> Vec<Piece> {
+    let mut pieces = Vec::new();
+    let mut lines = text.split_inclusive('\n').peekable();
+
+    // Front matter is kept as it is
+    if let Some(first) = lines.peek() {
+        let marker = split_eol(first).0.trim_end();
+        if marker == "---" || marker == "+++" {
+            let mut front_matter = lines.next().unwrap_or_default().to_string();
+            for line in lines.by_ref() {
+                front_matter.push_str(line);
+                let closing = split_eol(line).0.trim_end();
+                if closing == marker || (marker == "---" && closing == "...") {
+                    break;
+                }
+            }
+            pieces.push(Piece::Verbatim(front_matter));
+        }
+    }
+
+    let lines = lines.collect::<Vec<_>>();
+    let mut paragraph: Option<Paragraph> = None;
+    let mut code_fence: Option<(char, usize)> = None;
+    let mut in_html_comment = false;
+    let mut in_html_block = false;
+    let mut in_table = false;
+    let mut in_list = false;
+    // Whether the previous line ended a block, so an indented line starts a code block
+    let mut after_block = true;
+
+    let flush = |pieces: &mut Vec<Piece>, paragraph: &mut Option<Paragraph>| {
+        if let Some(Paragraph {
+            prefix,
+            text,
+            suffix,
+            gaps,
+        }) = paragraph.take()
+        {
+            pieces.push(Piece::Verbatim(prefix));
+            pieces.push(Piece::Prose(mask_inline(
+                &text,
+                &gaps,
+                options,
+                placeholders,
+            )));
+            pieces.push(Piece::Verbatim(suffix));
+        }
+    };
+
+    for (index, line) in lines.iter().enumerate() {
+        let (content, eol) = split_eol(line);
+        let trimmed = content.trim();
+        let blank = trimmed.is_empty();
+
+        let verbatim = if let Some((marker, len)) = code_fence {
+            if fence(content).is_some_and(|(c, l)| c == marker && l >= len)
+                && strip_quotes(content)
+                    .trim_start_matches(marker)
+                    .trim()
+                    .is_empty()
+            {
+                code_fence = None;
+            }
+            true
+        } else if in_html_comment {
+            in_html_comment = !content.contains("-->");
+            true
+        } else if in_html_block && !blank {
+            true
+        } else if let Some(opening) = fence(content) {
+            code_fence = Some(opening);
+            true
+        } else if blank {
+            in_table = false;
+            in_html_block = false;
+            true
+        } else if after_block
+            && !in_list
+            && (content.starts_with("    ") || content.starts_with('\t'))
+        {
+            // Indented code block
+            true
+        } else if strip_quotes(content).starts_with("<!--") && !content.contains("-->") {
+            in_html_comment = true;
+            true
+        } else {
+            let stripped = strip_quotes(content).trim_end();
+            // Html blocks last until a blank line
+            in_html_block = paragraph.is_none()
+                && stripped.starts_with('<')
+                && stripped.ends_with('>')
+                && inline_tag(stripped).is_some();
+
+            in_html_block
+                || is_break(stripped)
+                || is_delimiter_row(content)
+                || (stripped.starts_with('[')
+                    && !stripped.starts_with("[^")
+                    && stripped.contains("]:"))
+        };
+
+        after_block = verbatim;
+
+        if verbatim {
+            flush(&mut pieces, &mut paragraph);
+            pieces.push(Piece::Verbatim(line.to_string()));
+            continue;
+        }
+
+        let next_is_delimiter = lines
+            .get(index + 1)
+            .is_some_and(|next| is_delimiter_row(split_eol(next).0));
+
+        if next_is_delimiter || (in_table && content.contains('|')) {
+            flush(&mut pieces, &mut paragraph);
+            in_table = true;
+            table_row(&mut pieces, content, options, placeholders);
+            pieces.push(Piece::Verbatim(eol.to_string()));
+            continue;
+        }
+
+        let (prefix_len, kind) = split_prefix(content);
+        let (prefix_len, kind) = match footnote_definition(content, prefix_len) {
+            Some(len) => (len, LineKind::ListItem),
+            None => (prefix_len, kind),
+        };
+
+        after_block = kind == LineKind::Heading;
+        match kind {
+            LineKind::ListItem => in_list = true,
+            LineKind::Plain if prefix_len == 0 => in_list = false,
+            _ => {}
+        }
+
+        let mut text = content[prefix_len..].trim_end();
+        let hard_break = content.ends_with("  ") || text.ends_with('\\');
+        if let Some(without_break) = text.strip_suffix('\\') {
+            text = without_break;
+        }
+
+        if kind == LineKind::Heading {
+            let closing = text.trim_end_matches('#');
+            if closing.is_empty() || closing.ends_with([' ', '\t']) {
+                text = closing.trim_end();
+            }
+        }
+
+        let suffix = format!("{}{eol}", &content[prefix_len + text.len()..]);
+
+        match &mut paragraph {
+            // Lazy continuation lines can omit the block quote markers
+            Some(open)
+                if kind == LineKind::Plain
+                    && (quotes(&content[..prefix_len]) == quotes(&open.prefix)
+                        || quotes(&content[..prefix_len]) == 0) =>
+            {
+                let gap = std::mem::replace(&mut open.suffix, suffix) + &content[..prefix_len];
+                open.gaps.push(gap);
+                open.text.push('\n');
+                open.text.push_str(text);
+            }
+            _ => {
+                flush(&mut pieces, &mut paragraph);
+                paragraph = Some(Paragraph {
+                    prefix: content[..prefix_len].to_string(),
+                    text: text.to_string(),
+                    suffix,
+                    gaps: Vec::new(),
+                });
+            }
+        }
+
+        if kind == LineKind::Heading || hard_break {
+            flush(&mut pieces, &mut paragraph);
+        }
+    }
+
+    flush(&mut pieces, &mut paragraph);
+    pieces
+}
+
+/// Length of the label of a footnote definition, `[^1]: `
+fn footnote_definition(content: &str, prefix_len: usize) -> Option<usize> {
+    let rest = &content[prefix_len..];
+    if !rest.starts_with("[^") {
+        return None;
+    }
+
+    let label = rest.find("]:")? + 2;
+    let spaces = rest[label..].len() - rest[label..].trim_start().len();
+
+    Some(prefix_len + label + spaces)
+}
+
+/// Every cell of a table row is translated apart, the pipes are kept
+fn table_row(
+    pieces: &mut Vec<Piece>,
+    content: &str,
+    options: MarkdownOptions,
+    placeholders: &[PlaceholderSyntax],
+) {
+    let bytes = content.as_bytes();
+    let mut in_code = false;
+    let mut cell_start = 0;
+    let mut i = 0;
+
+    let push_cell = |pieces: &mut Vec<Piece>, cell: &str| {
+        let text = cell.trim();
+        if text.is_empty() {
+            pieces.push(Piece::Verbatim(cell.to_string()));
+            return;
+        }
+
+        let leading = cell.len() - cell.trim_start().len();
+        pieces.push(Piece::Verbatim(cell[..leading].to_string()));
+        pieces.push(Piece::Prose(mask_inline(text, &[], options, placeholders)));
+        pieces.push(Piece::Verbatim(cell[leading + text.len()..].to_string()));
+    };
+
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 1,
+            b'`' => in_code = !in_code,
+            b'|' if !in_code => {
+                push_cell(pieces, &content[cell_start..i]);
+                pieces.push(Piece::Verbatim("|".into()));
+                cell_start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    push_cell(pieces, &content[cell_start.min(content.len())..]);
+}
+
+impl Translator {
+    /// Translate the prose of a markdown document. Code blocks, inline code, html, urls,
+    /// link destinations and front matter are left untouched, the structure of the
+    /// document is kept. The glossary terms the engine did not keep are added to `details`
+    pub(crate) async fn translate_markdown(
+        &self,
+        text: &str,
+        details: &mut Translation,
+    ) -> Result<Value, Error> {
+        let pieces = parse(text, self.markdown, &self.placeholders);
+
+        let translations = futures::stream::iter(pieces.iter().map(|piece| async move {
+            match piece {
+                Piece::Verbatim(text) => Ok(Translation {
+                    text: text.clone(),
+                    ..Translation::default()
+                }),
+                Piece::Prose(masked) if !masked.has_text() => Ok(Translation {
+                    text: masked.original(),
+                    ..Translation::default()
+                }),
+                Piece::Prose(masked) => self.translate_masked_detailed(masked).await,
+            }
+        }))
+        .buffered(self.max_concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+
+        let mut translation = String::new();
+        for piece in translations {
+            let piece = piece?;
+            translation.push_str(&piece.text);
+            details
+                .glossary_violations
+                .extend(piece.glossary_violations);
+        }
+
+        Ok(Value::String(translation))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Masked texts sent to the engine
+    fn prose(text: &str, options: MarkdownOptions) -> Vec<String> {
+        parse(text, options, &[])
+            .into_iter()
+            .filter_map(|piece| match piece {
+                Piece::Prose(masked) if masked.has_text() => Some(masked.text),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The document with the prose translated by `engine`
+    fn translate(text: &str, engine: fn(&str) -> String) -> String {
+        parse(text, MarkdownOptions::default(), &[])
+            .into_iter()
+            .map(|piece| match piece {
+                Piece::Verbatim(text) => text,
+                Piece::Prose(masked) if !masked.has_text() => masked.original(),
+                Piece::Prose(masked) => masked.unmask(&engine(&masked.text)).unwrap().0,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn soft_wrapped_lines_are_kept() {
+        let text = "> Some text that\n> wraps `code\n> span` and\nlazily goes on.\n";
+        let pieces = parse(text, MarkdownOptions::default(), &[]);
+
+        let prose = pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Prose(masked) => Some(masked),
+                Piece::Verbatim(_) => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(prose.len(), 1);
+        assert_eq!(
+            prose[0].text,
+            "Some text that __PH0__ wraps __PH1__ and __PH2__ lazily goes on."
+        );
+
+        let translation = prose[0]
+            .unmask("Ein Text, der __PH0__ umbricht __PH1__ und __PH2__ weitergeht.")
+            .unwrap()
+            .0;
+        assert_eq!(
+            translation,
+            "Ein Text, der\n> umbricht `code\n> span` und\nweitergeht."
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let text = "- [ ] Buy milk\n- Call **Bob**\n  later\n\n1. First\n   * Nested item\n";
+        assert_eq!(
+            prose(text, MarkdownOptions::default()),
+            [
+                "Buy milk",
+                "Call **Bob** __PH0__ later",
+                "First",
+                "Nested item"
+            ]
+        );
+        assert_eq!(
+            translate(text, str::to_uppercase),
+            "- [ ] BUY MILK\n- CALL **BOB**\n  LATER\n\n1. FIRST\n   * NESTED ITEM\n"
+        );
+    }
+
+    #[test]
+    fn fenced_code() {
+        let text = "# Run it #\n\n```sh\nls -l\n```\n\n~~~~\n```\nnot closed\n~~~~\n    indented code\nDone\n";
+        assert_eq!(prose(text, MarkdownOptions::default()), ["Run it", "Done"]);
+        assert_eq!(
+            translate(text, str::to_uppercase),
+            text.replace("Run it", "RUN IT").replace("Done", "DONE")
+        );
+    }
+
+    #[test]
+    fn tables() {
+        let text = "| Name | Note |\n| --- | :-: |\n| Apple | `red` \\| ripe |\n|  | Pear |\n";
+        assert_eq!(
+            prose(text, MarkdownOptions::default()),
+            ["Name", "Note", "Apple", "__PH0__ \\| ripe", "Pear"]
+        );
+        assert_eq!(
+            translate(text, str::to_uppercase),
+            "| NAME | NOTE |\n| --- | :-: |\n| APPLE | `red` \\| RIPE |\n|  | PEAR |\n"
+        );
+    }
+
+    #[test]
+    fn links() {
+        let text = "See [the docs](https://x.io/a_b) and ![a cat](cat.png \"Cat\"), \
+            [ref][1] at <https://x.io> or www.x.io.\n\n[1]: https://x.io/ref\n";
+        assert_eq!(
+            prose(text, MarkdownOptions::default()),
+            ["See __PH0__the docs__PH1__ and __PH2__a cat__PH3__, __PH4__ref__PH5__ at __PH6__ or __PH7__."]
+        );
+        assert_eq!(
+            translate(text, str::to_uppercase),
+            text.replace("See [the docs]", "SEE [THE DOCS]")
+                .replace("and ![a cat]", "AND ![A CAT]")
+                .replace("[ref]", "[REF]")
+                .replace(" at <", " AT <")
+                .replace(" or ", " OR ")
+        );
+
+        let options = MarkdownOptions {
+            link_text: false,
+            image_alt: false,
+        };
+        assert_eq!(
+            prose(text, options),
+            ["See __PH0__ and __PH1__, __PH2__ at __PH3__ or __PH4__."]
+        );
+    }
+
+    #[test]
+    fn lost_or_moved_line_breaks() {
+        let text = "> Some text that\n> wraps here.\n\nNext paragraph\ngoes on.\n";
+        let translation = translate(text, |text| match text.contains("wraps") {
+            true => text.replace(" __PH0__ ", " "),
+            false => "Next __PH0__ paragraph goes __PH0__ on.".into(),
+        });
+
+        assert_eq!(
+            translation,
+            "> Some text that wraps here.\n\nNext\nparagraph goes on.\n"
+        );
+    }
+}
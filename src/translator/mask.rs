@@ -0,0 +1,368 @@
+use crate::{Error, PlaceholderSyntax};
+
+/// Text with some parts replaced by tokens that the engines leave untouched, so they can be
+/// restored after the translation
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+pub(crate) struct Masked {
+    pub text: String,
+    protected: Vec<Protected>,
+    /// Placeholders found in the text are protected too
+    placeholders: Vec<PlaceholderSyntax>,
+}
+
+/// A part of the text replaced by a token
+#[derive(Debug, Default, Clone, Eq, PartialEq)]
+struct Protected {
+    /// What the token is replaced with in the translation
+    text: String,
+    /// What the token stands for in the original text, it differs from `text` for the
+    /// glossary terms with a preferred translation
+    original: String,
+    /// Whether it is a term of the glossary
+    term: bool,
+    /// Whether it is whitespace between words, e.g. a line break. Its token is sent between
+    /// spaces, they are dropped when it is restored. The engine may lose or repeat it, the
+    /// words are then joined by the spaces of the translation
+    space: bool,
+}
+
+#[inline(always)]
+fn token(index: usize) -> String {
+    format!("__PH{index}__")
+}
+
+/// Parse a token at the start of `text`, engines sometimes add spaces or change the case
+/// of it. Return the index of the token and its length
+fn parse_token(text: &str) -> Option<(usize, usize)> {
+    let rest = text.strip_prefix("__")?;
+    let after_spaces = rest.trim_start_matches(' ');
+    let after_ph = after_spaces
+        .strip_prefix("PH")
+        .or_else(|| after_spaces.strip_prefix("ph"))?
+        .trim_start_matches(' ');
+    let digits = after_ph.len()
+        - after_ph
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    if digits == 0 {
+        return None;
+    }
+
+    let index = after_ph[..digits].parse().ok()?;
+    let rest = after_ph[digits..]
+        .trim_start_matches(' ')
+        .strip_prefix("__")?;
+
+    Some((index, text.len() - rest.len()))
+}
+
+impl Masked {
+    #[inline(always)]
+    pub fn new(placeholders: &[PlaceholderSyntax]) -> Self {
+        Self {
+            placeholders: placeholders.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    pub fn push_text(&mut self, text: &str) {
+        let mut previous = None;
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            // A token written in the text is protected too, so it is not taken for the one
+            // of another part
+            let placeholder = parse_token(rest).map(|(_, len)| len).or_else(|| {
+                self.placeholders
+                    .iter()
+                    .find_map(|syntax| syntax.find(rest, previous))
+            });
+
+            let len = match placeholder {
+                Some(len) => {
+                    self.push_protected(&rest[..len]);
+                    len
+                }
+                None => {
+                    self.text.push(c);
+                    c.len_utf8()
+                }
+            };
+
+            previous = rest[..len].chars().next_back();
+            rest = &rest[len..];
+        }
+    }
+
+    /// Replace `text` with a token
+    #[inline(always)]
+    pub fn push_protected(&mut self, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        self.text.push_str(&token(self.protected.len()));
+        self.protected.push(Protected {
+            text: text.to_string(),
+            original: text.to_string(),
+            ..Protected::default()
+        });
+    }
+
+    /// Replace whitespace between two words with a token, e.g. a line break and the
+    /// indentation of the next line
+    #[inline(always)]
+    pub fn push_space(&mut self, text: &str) {
+        self.text
+            .push_str(&format!(" {} ", token(self.protected.len())));
+        self.protected.push(Protected {
+            text: text.to_string(),
+            original: text.to_string(),
+            space: true,
+            ..Protected::default()
+        });
+    }
+
+    /// Replace a term of the glossary with a token, it becomes `translation` in the
+    /// translated text
+    #[inline(always)]
+    pub fn push_term(&mut self, term: &str, translation: &str) {
+        self.text.push_str(&token(self.protected.len()));
+        self.protected.push(Protected {
+            text: translation.to_string(),
+            original: term.to_string(),
+            term: true,
+            ..Protected::default()
+        });
+    }
+
+    /// The same masked text with the terms protected too, `find` gives the length of the
+    /// term at the start of the text and its translation
+    pub fn with_terms<F>(&self, find: F) -> Self
+    where
+        F: Fn(&str, Option<char>) -> Option<(usize, String)>,
+    {
+        let mut masked = Self::new(&self.placeholders);
+        let mut previous = None;
+        let mut rest = self.text.as_str();
+
+        while let Some(c) = rest.chars().next() {
+            let token_len = parse_token(rest).filter(|(index, _)| *index < self.protected.len());
+            let len = if let Some((index, len)) = token_len {
+                masked.text.push_str(&token(masked.protected.len()));
+                masked.protected.push(self.protected[index].clone());
+                len
+            } else if let Some((len, translation)) = find(rest, previous) {
+                masked.push_term(&rest[..len], &translation);
+                len
+            } else {
+                masked.text.push(c);
+                c.len_utf8()
+            };
+
+            previous = rest[..len].chars().next_back();
+            rest = &rest[len..];
+        }
+
+        masked
+    }
+
+    #[inline(always)]
+    pub fn has_protected(&self) -> bool {
+        !self.protected.is_empty()
+    }
+
+    /// Whether there is something to translate besides the tokens
+    pub fn has_text(&self) -> bool {
+        let mut text = self.text.as_str();
+        while !text.is_empty() {
+            if let Some((_, len)) = parse_token(text) {
+                text = &text[len..];
+                continue;
+            }
+
+            let mut chars = text.chars();
+            if chars.next().is_some_and(char::is_alphabetic) {
+                return true;
+            }
+            text = chars.as_str();
+        }
+
+        false
+    }
+
+    /// The original text, without translating it
+    pub fn original(&self) -> String {
+        self.restore(&self.text, |protected| &protected.original).0
+    }
+
+    /// Replace the tokens of the text with a part of what they protect, also count how
+    /// many times each token is found
+    fn restore(&self, translation: &str, part: fn(&Protected) -> &String) -> (String, Vec<usize>) {
+        let mut counts = vec![0; self.protected.len()];
+        let mut res = String::with_capacity(translation.len());
+        let mut rest = translation;
+
+        while let Some(start) = rest.find("__") {
+            res.push_str(&rest[..start]);
+            rest = &rest[start..];
+
+            match parse_token(rest) {
+                Some((index, len)) if index < self.protected.len() => {
+                    let protected = &self.protected[index];
+                    counts[index] += 1;
+                    if protected.space {
+                        res.truncate(res.trim_end_matches(' ').len());
+                    }
+                    match protected.space && counts[index] > 1 {
+                        true => res.push(' '),
+                        false => res.push_str(part(protected)),
+                    }
+                    rest = &rest[len..];
+                    if protected.space {
+                        rest = rest.trim_start_matches(' ');
+                    }
+                }
+                _ => {
+                    res.push('_');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        res.push_str(rest);
+
+        (res, counts)
+    }
+
+    /// Restore the protected parts in the translation of the masked text, with the glossary
+    /// terms that the engine lost or duplicated. An error is returned when the engine lost
+    /// or duplicated some other token, except the spaces
+    pub fn unmask(&self, translation: &str) -> Result<(String, Vec<String>), Error> {
+        let (res, counts) = self.restore(translation, |protected| &protected.text);
+
+        let select = |predicate: fn(usize) -> bool, term: bool| {
+            counts
+                .iter()
+                .zip(&self.protected)
+                .filter(|(count, protected)| {
+                    predicate(**count) && protected.term == term && !protected.space
+                })
+                .map(|(_, protected)| protected.original.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let missing = select(|count| count == 0, false);
+        let duplicated = select(|count| count > 1, false);
+        if !missing.is_empty() || !duplicated.is_empty() {
+            return Err(Error::PlaceholderMismatch {
+                missing,
+                duplicated,
+            });
+        }
+
+        let mut violations = select(|count| count != 1, true);
+        violations.dedup();
+
+        Ok((res, violations))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printf(text: &str) -> Masked {
+        let mut masked = Masked::new(&[PlaceholderSyntax::Printf]);
+        masked.push_text(text);
+        masked
+    }
+
+    #[test]
+    fn round_trip() {
+        let masked = printf("Hello %s, you have %d files");
+
+        assert_eq!(masked.text, "Hello __PH0__, you have __PH1__ files");
+        assert_eq!(masked.original(), "Hello %s, you have %d files");
+        assert_eq!(
+            masked
+                .unmask("Hallo __PH0__, du hast __ ph 1 __ Dateien")
+                .unwrap(),
+            ("Hallo %s, du hast %d Dateien".to_string(), vec![])
+        );
+    }
+
+    #[test]
+    fn reordered_tokens() {
+        let masked = printf("%s sent %d files");
+
+        assert_eq!(
+            masked.unmask("__PH1__ Dateien von __PH0__").unwrap().0,
+            "%d Dateien von %s"
+        );
+    }
+
+    #[test]
+    fn lost_or_duplicated_tokens() {
+        let masked = printf("%s sent %d files");
+
+        match masked.unmask("__PH0__ hat Dateien gesendet, __PH0__") {
+            Err(Error::PlaceholderMismatch {
+                missing,
+                duplicated,
+            }) => {
+                assert_eq!(missing, ["%d"]);
+                assert_eq!(duplicated, ["%s"]);
+            }
+            res => panic!("{res:?}"),
+        }
+    }
+
+    #[test]
+    fn tokens_written_in_the_text() {
+        let masked = printf("Use __PH0__ for %s");
+        assert_eq!(masked.text, "Use __PH0__ for __PH1__");
+        assert_eq!(masked.original(), "Use __PH0__ for %s");
+        assert_eq!(
+            masked.unmask("Nutze __PH0__ für __PH1__").unwrap().0,
+            "Nutze __PH0__ für %s"
+        );
+
+        let mut masked = Masked::new(&[]);
+        masked.push_text("x __PH3__");
+        assert!(masked.has_protected());
+        assert!(masked.unmask("y").is_err());
+    }
+
+    #[test]
+    fn spaces_and_terms() {
+        let mut masked = Masked::new(&[]);
+        masked.push_text("The");
+        masked.push_term("Fox", "Fuchs");
+        masked.push_space("\n  ");
+        masked.push_text("runs");
+
+        assert_eq!(masked.text, "The__PH0__ __PH1__ runs");
+        assert_eq!(masked.original(), "TheFox\n  runs");
+        assert_eq!(
+            masked.unmask("Der__PH0__  __PH1__ läuft").unwrap(),
+            ("DerFuchs\n  läuft".to_string(), vec![])
+        );
+        assert_eq!(
+            masked.unmask("Der Fuchs __PH1__ läuft").unwrap(),
+            ("Der Fuchs\n  läuft".to_string(), vec!["Fox".to_string()])
+        );
+
+        // Lost or repeated spaces are not errors
+        assert_eq!(
+            masked.unmask("Der__PH0__ läuft").unwrap().0,
+            "DerFuchs läuft"
+        );
+        assert_eq!(
+            masked
+                .unmask("Der__PH0__ __PH1__ läuft __PH1__ schnell")
+                .unwrap()
+                .0,
+            "DerFuchs\n  läuft schnell"
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::{collections::HashMap, sync::Arc};
+
+/// A segment of the memory, the translation of a text between two languages
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Segment {
+    source_language: String,
+    target_language: String,
+    translation: String,
+}
+
+/// Translations approved before, they are returned by [`crate::Translator`] instead of
+/// calling the engine. It is cheap to clone, the segments are shared until it is changed
+#[derive(Debug, Clone, Default, Eq, PartialEq)]
+pub struct TranslationMemory {
+    segments: Arc<HashMap<String, Vec<Segment>>>,
+}
+
+/// Language without the region or script, `pt` for `pt-BR`
+#[inline(always)]
+pub(super) fn primary(language: &str) -> &str {
+    language.split(['-', '_']).next().unwrap_or(language)
+}
+
+impl TranslationMemory {
+    #[inline(always)]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.segments.values().map(Vec::len).sum()
+    }
+
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.segments.is_empty()
+    }
+
+    /// Add the translation of a text, it replaces the one between the same languages
+    pub fn insert(
+        &mut self,
+        source_language: &str,
+        target_language: &str,
+        text: &str,
+        translation: &str,
+    ) {
+        let segments = Arc::make_mut(&mut self.segments)
+            .entry(text.trim().to_string())
+            .or_default();
+        segments.retain(|segment| {
+            !segment
+                .source_language
+                .eq_ignore_ascii_case(source_language)
+                || !segment
+                    .target_language
+                    .eq_ignore_ascii_case(target_language)
+        });
+
+        segments.push(Segment {
+            source_language: source_language.to_string(),
+            target_language: target_language.to_string(),
+            translation: translation.to_string(),
+        });
+    }
+
+    /// Translation of the text, the languages match with or without the region, `en-US`
+    /// and `en`, and `auto` matches any source language
+    pub fn get(&self, source_language: &str, target_language: &str, text: &str) -> Option<&str> {
+        let segments = self.segments.get(text.trim())?;
+        let matches = |memory: &str, language: &str, exact: bool| match exact {
+            true => memory.eq_ignore_ascii_case(language),
+            false => primary(memory).eq_ignore_ascii_case(primary(language)),
+        };
+        let find = |exact: bool| {
+            segments.iter().find(|segment| {
+                (source_language == "auto"
+                    || matches(&segment.source_language, source_language, exact))
+                    && matches(&segment.target_language, target_language, exact)
+            })
+        };
+
+        find(true)
+            .or_else(|| find(false))
+            .map(|segment| segment.translation.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn languages_match_with_or_without_the_region() {
+        let mut memory = TranslationMemory::new();
+        memory.insert("en", "pt-BR", " Bus ", "Ônibus");
+        memory.insert("en", "pt-PT", "Bus", "Autocarro");
+        memory.insert("en", "de", "Bus", "Bus");
+
+        assert_eq!(memory.len(), 3);
+        assert_eq!(memory.get("en", "pt-PT", "Bus"), Some("Autocarro"));
+        assert_eq!(memory.get("EN", "pt-br", "Bus\n"), Some("Ônibus"));
+        assert_eq!(memory.get("en-US", "pt", "Bus"), Some("Ônibus"));
+        assert_eq!(memory.get("auto", "de", "Bus"), Some("Bus"));
+        assert_eq!(memory.get("fr", "de", "Bus"), None);
+        assert_eq!(memory.get("en", "it", "Bus"), None);
+        assert_eq!(memory.get("en", "de", "Car"), None);
+    }
+
+    #[test]
+    fn insert_replaces_the_same_languages() {
+        let mut memory = TranslationMemory::new();
+        memory.insert("en", "de", "Save", "Sichern");
+        let copy = memory.clone();
+        memory.insert("EN", "DE", "Save", "Speichern");
+
+        assert_eq!(memory.len(), 1);
+        assert_eq!(memory.get("en", "de", "Save"), Some("Speichern"));
+        // The clones do not see the changes
+        assert_eq!(copy.get("en", "de", "Save"), Some("Sichern"));
+        assert!(TranslationMemory::new().is_empty());
+    }
+}
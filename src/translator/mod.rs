@@ -1,15 +1,44 @@
 mod engine;
+mod glossary;
+pub(crate) mod html;
+mod markdown;
+pub(crate) mod mask;
+mod memory;
+mod placeholder;
 #[cfg(target_arch = "wasm32")]
 pub mod wasm;
+#[cfg(feature = "python")]
+pub mod python;
 
 use crate::Error;
 pub use engine::*;
+pub use glossary::Glossary;
+pub use markdown::MarkdownOptions;
+pub use memory::TranslationMemory;
+pub use placeholder::PlaceholderSyntax;
 use reqwest::{Client, ClientBuilder, Response};
+use futures::StreamExt;
+use mask::Masked;
 use serde_json::Value;
-use std::ops::{Deref, DerefMut};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    ops::{Deref, DerefMut},
+};
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::sync::Semaphore;
+
+/// Number of requests a [`Translator`] sends at the same time by default
+pub const DEFAULT_MAX_CONCURRENCY: usize = 4;
 
 #[inline(always)]
-fn response_status(response: Response) -> Result<Response, Error> {
+fn default_max_concurrency() -> usize {
+    DEFAULT_MAX_CONCURRENCY
+}
+
+#[inline(always)]
+pub(crate) fn response_status(response: Response) -> Result<Response, Error> {
 
     if response.status() != 200 {
         return Err(Error::Response(response.status()));
@@ -22,7 +51,7 @@ fn response_status(response: Response) -> Result<Response, Error> {
 /// This is the main structure of the library, which allows to serve the translations
 /// taking advantage of the logic of the different engines
 /// 
-///```rust
+///```rust,no_run
 /// use deeptranslator::{Engine, Translator};
 /// 
 /// #[tokio::main]
@@ -31,16 +60,85 @@ fn response_status(response: Response) -> Result<Response, Error> {
 ///     let translation_result = translator.translate("Saludos a todo el mundo").await;
 ///
 ///     println!("{:?}", translation_result);
-// }
+/// }
 ///```
 #[derive(Debug, Clone, serde::Deserialize, serde::Serialize)]
 pub struct Translator {
     pub source: String,
     pub target: String,
     pub engine: Engine,
+    /// Kind of the text to translate, with [`TextType::Html`] the markup is preserved
+    #[serde(default)]
+    pub text_type: TextType,
+    /// Parts of the document translated with [`TextType::Markdown`]
+    #[serde(default)]
+    pub markdown: MarkdownOptions,
+    /// Placeholders that are kept as they are, e.g. `{name}` or `%s`. The translation
+    /// fails with [`Error::PlaceholderMismatch`] when the engine loses or duplicates one
+    #[serde(default)]
+    pub placeholders: Vec<PlaceholderSyntax>,
+    /// Maximum number of requests in flight at the same time within one call of the functions
+    /// that translate concurrently. [`Translator::translate_to_many`] shares a limit of that
+    /// many requests between its targets when [`Translator::limit`] is not set
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+    /// Terms kept as they are or with a fixed translation, with every engine
+    #[serde(default)]
+    pub glossary: Glossary,
+    /// Text about the text to translate that is not translated, e.g. the `msgctxt` of a
+    /// gettext message. Only Deepl uses it
+    #[serde(default)]
+    pub context: Option<String>,
+    /// Translations returned as they are, without calling the engine, see
+    /// [`crate::formats::tmx`] to fill it from a TMX file
+    #[serde(skip)]
+    pub memory: TranslationMemory,
     #[cfg(not(target_arch = "wasm32"))]
     #[serde(skip)]
     pub proxies: Vec<reqwest::Proxy>,
+    /// Requests to the engine in flight, shared by the translator and its clones, each
+    /// request waits for a permit. There is no limit when it is `None`
+    #[cfg(not(target_arch = "wasm32"))]
+    #[serde(skip)]
+    pub limit: Option<Arc<Semaphore>>,
+}
+
+/// A translation with what the engine tells about it, see
+/// [`Translator::translate_detailed`]
+#[derive(Debug, Clone, Default, Eq, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Translation {
+    pub text: String,
+    /// Other translations given by the engine, e.g. the synonyms of Linguee
+    pub alternatives: Vec<String>,
+    /// Language of the text found by the engine when the source is `auto`
+    pub detected_language: Option<String>,
+    /// Terms of the [`Glossary`] that the engine lost or duplicated, the text has them as
+    /// the engine left them
+    #[serde(default)]
+    pub glossary_violations: Vec<String>,
+    /// The translation in another script, when [`Microsoft::to_script`] is set
+    #[serde(default)]
+    pub transliteration: Option<Transliteration>,
+    /// Alignment of the text and the translation, when [`Microsoft::include_alignment`] is set
+    #[serde(default)]
+    pub alignment: Option<Alignment>,
+}
+
+impl Translation {
+    /// The texts of a value returned by [`Translator::translate`], there are several when
+    /// the engine gives alternatives
+    pub fn texts(value: &Value) -> Vec<String> {
+        match value {
+            Value::String(text) => vec![text.clone()],
+            Value::Array(values) => values.iter().flat_map(Self::texts).collect(),
+            Value::Object(object) => match object.get("text") {
+                Some(text) => Self::texts(text),
+                None => vec![value.to_string()],
+            },
+            Value::Null => Vec::new(),
+            value => vec![value.to_string()],
+        }
+    }
 }
 
 impl Translator {
@@ -61,6 +159,21 @@ impl Translator {
         }
     }
 
+    /// Check that the engine supports the source and target languages, `auto` is always
+    /// accepted as source
+    pub fn check_languages(&self) -> Result<(), Error> {
+        let source = (self.source != "auto").then_some(&self.source);
+
+        match source
+            .into_iter()
+            .chain([&self.target])
+            .find(|code| !self.engine.supports_language(code))
+        {
+            Some(code) => Err(Error::LanguageNotSupported(code.clone())),
+            None => Ok(()),
+        }
+    }
+
     #[inline(always)]
     fn client(&self) -> ClientBuilder {
         #[allow(unused_mut)]
@@ -97,28 +210,183 @@ impl Translator {
 
     #[inline(always)]
     pub async fn translate(&self, text: &str) -> Result<Value, Error> {
-        let text = text.trim();
-        if text.is_empty() || self.source == self.target {
-            return Ok(Value::String(text.into()));
+        self.translate_value(text, &mut Translation::default()).await
+    }
+
+    /// Translate the text, with the alternatives, the language detected by the engine and
+    /// the glossary terms it did not keep. Only some engines tell the language, e.g. Deepl,
+    /// Libre and Microsoft
+    pub async fn translate_detailed(&self, text: &str) -> Result<Translation, Error> {
+        let mut details = Translation::default();
+        let value = self.translate_value(text, &mut details).await?;
+        let mut alternatives = Translation::texts(&value).into_iter();
+
+        details.text = alternatives.next().ok_or(Error::TranslationNotFound)?;
+        details.alternatives = alternatives.collect();
+        let mut violations = BTreeSet::new();
+        details
+            .glossary_violations
+            .retain(|term| violations.insert(term.clone()));
+
+        Ok(details)
+    }
+
+    /// Translate the text, what the engine and the glossary tell about the translation
+    /// is set in `details`
+    async fn translate_value(
+        &self,
+        text: &str,
+        details: &mut Translation,
+    ) -> Result<Value, Error> {
+        let trimmed = text.trim();
+        if trimmed.is_empty() || self.source == self.target {
+            // Whitespace is meaningful in markdown, the other texts are trimmed as always
+            return Ok(Value::String(match self.text_type {
+                TextType::Markdown => text.into(),
+                _ => trimmed.into(),
+            }));
+        }
+
+        if let Some(translation) = self.memory.get(&self.source, &self.target, trimmed) {
+            return Ok(Value::String(translation.into()));
+        }
+
+        match self.text_type {
+            TextType::Html if !self.engine.supports_html() => {
+                self.translate_html(trimmed, details).await
+            }
+            // Whitespace is meaningful in markdown, e.g. for indented code blocks
+            TextType::Markdown => self.translate_markdown(text, details).await,
+            // The engine translates the html, the code elements are masked so it can not
+            TextType::Html => {
+                let masked = html::mask_untranslatable(trimmed, &self.placeholders);
+                self.translate_masked(&masked, details).await
+            }
+            _ => {
+                let mut masked = Masked::new(&self.placeholders);
+                masked.push_text(trimmed);
+                self.translate_masked(&masked, details).await
+            }
+        }
+    }
+
+    /// Translate the masked text into a string, see [`Translator::translate_masked`]
+    #[inline(always)]
+    pub(crate) async fn translate_masked_text(&self, masked: &Masked) -> Result<String, Error> {
+        Ok(self.translate_masked_detailed(masked).await?.text)
+    }
+
+    /// Translate the masked text into a string, with the glossary terms the engine did not
+    /// keep
+    pub(crate) async fn translate_masked_detailed(
+        &self,
+        masked: &Masked,
+    ) -> Result<Translation, Error> {
+        let mut details = Translation::default();
+        let translation = self.translate_masked(masked, &mut details).await?;
+
+        details.text = html::first_text(translation).ok_or(Error::TranslationNotFound)?;
+        Ok(details)
+    }
+
+    /// Send the masked text to the engine and restore the protected parts in every
+    /// translation it returns, the glossary terms it did not keep are added to `details`
+    async fn translate_masked(
+        &self,
+        masked: &Masked,
+        details: &mut Translation,
+    ) -> Result<Value, Error> {
+        fn unmask(
+            masked: &Masked,
+            value: Value,
+            violations: &mut Vec<String>,
+        ) -> Result<Value, Error> {
+            match value {
+                Value::String(text) => {
+                    let (text, lost) = masked.unmask(&text)?;
+                    for term in lost {
+                        if !violations.contains(&term) {
+                            violations.push(term);
+                        }
+                    }
+                    Ok(Value::String(text))
+                }
+                Value::Array(all) => all
+                    .into_iter()
+                    .map(|value| unmask(masked, value, violations))
+                    .collect(),
+                Value::Object(mut object) => {
+                    if let Some(text) = object.remove("text") {
+                        object.insert("text".into(), unmask(masked, text, violations)?);
+                    }
+                    Ok(Value::Object(object))
+                }
+                value => Ok(value),
+            }
+        }
+
+        let original = masked.original();
+        if let Some(translation) = self.memory.get(&self.source, &self.target, &original) {
+            return Ok(Value::String(translation.into()));
+        }
+
+        let glossed = self.glossary.mask(masked, &self.target);
+        let masked = glossed.as_ref().unwrap_or(masked);
+
+        if !masked.has_protected() {
+            return self.engine_translate(&masked.text, details).await;
+        }
+
+        if !masked.has_text() {
+            return Ok(Value::String(masked.unmask(&masked.text)?.0));
         }
 
+        let translation = self.engine_translate(&masked.text, details).await?;
+        unmask(masked, translation, &mut details.glossary_violations)
+    }
+
+    /// Send the text to the engine as it is, the language is set in `details` when the
+    /// engine detects it
+    async fn engine_translate(
+        &self,
+        text: &str,
+        details: &mut Translation,
+    ) -> Result<Value, Error> {
+        let html = self.text_type == TextType::Html;
+        #[cfg(not(target_arch = "wasm32"))]
+        let _permit = match &self.limit {
+            Some(limit) => limit.acquire().await.ok(),
+            None => None,
+        };
+
         match &self.engine {
             Engine::Deepl { api_key, .. } => {
+                let mut url_params = vec![
+                    ("auth_key", &api_key[..]),
+                    ("source_lang", &self.source),
+                    ("target_lang", &self.target),
+                    ("text", text),
+                ];
+
+                if html {
+                    url_params.push(("tag_handling", "html"));
+                    url_params.push(("ignore_tags", "code,script,style"));
+                }
+                if let Some(context) = &self.context {
+                    url_params.push(("context", context));
+                }
+
                 let response: Value = self
-                    .request(
-                        None,
-                        &[
-                            ("auth_key", &api_key[..]),
-                            ("source_lang", &self.source),
-                            ("target_lang", &self.target),
-                            ("text", text),
-                        ],
-                    )
+                    .request(None, &url_params)
                     .await?
                     .json()
                     .await?;
 
-                Ok(response["translations"][0]["text"].clone())
+                let translation = &response["translations"][0];
+                details.detected_language = translation["detected_source_language"]
+                    .as_str()
+                    .map(str::to_lowercase);
+                Ok(translation["text"].clone())
             }
 
             Engine::Google => {
@@ -150,7 +418,7 @@ impl Translator {
                     ("q", text),
                     ("source", &self.source),
                     ("target", &self.target),
-                    ("format", "text"),
+                    ("format", if html { "html" } else { "text" }),
                     ("alternatives", &alternatives_str),
                 ];
 
@@ -167,7 +435,18 @@ impl Translator {
                     .await?;
 
                 let data: Value = response_status(response)?.json().await?;
-                Ok(data["translatedText"].clone())
+                details.detected_language = data["detectedLanguage"]["language"]
+                    .as_str()
+                    .map(String::from);
+
+                match &data["alternatives"] {
+                    Value::Array(alternatives) if !alternatives.is_empty() => Ok(
+                        std::iter::once(data["translatedText"].clone())
+                            .chain(alternatives.iter().cloned())
+                            .collect(),
+                    ),
+                    _ => Ok(data["translatedText"].clone()),
+                }
             }
             Engine::Linguee { return_all } => {
                 // It url in the other engines would be `.query(&url_params)`
@@ -210,39 +489,38 @@ impl Translator {
                     Err(Error::TranslationNotFound)
                 }
             }
-            Engine::Microsoft { api_key, region } => {
-                let mut request = self
-                    .client()
-                    .build()?
-                    .post(self.base_url())
-                    .header("Ocp-Apim-Subscription-Key", api_key)
-                    .header("Content-type", "application/json");
+            Engine::Microsoft(microsoft) => {
+                let text_type = if html { "html" } else { "plain" };
+                let profanity_action = microsoft.profanity_action.to_string();
+                let profanity_marker = microsoft.profanity_marker.to_string();
+                let mut url_params = vec![
+                    ("to", self.target.as_str()),
+                    ("textType", text_type),
+                    ("profanityAction", &profanity_action),
+                    ("profanityMarker", &profanity_marker),
+                ];
 
-                if !region.is_empty() {
-                    request = request.header("Ocp-Apim-Subscription-Region", region);
+                if self.source != "auto" {
+                    url_params.push(("from", &self.source));
                 }
 
-                let response = request
-                    .query(&[
-                        ("from", self.source.as_str()),
-                        ("to", &self.target),
-                        ("text", text),
-                    ])
-                    .send()
-                    .await?;
-
-                let content: Value = response_status(response)?.json().await?;
+                if microsoft.include_alignment {
+                    url_params.push(("includeAlignment", "true"));
+                }
 
-                let Value::Array(translations_hash) = &content[0]["translations"] else {
-                    panic!("{content:?}")
-                };
+                if !microsoft.to_script.is_empty() {
+                    url_params.push(("toScript", &microsoft.to_script));
+                }
 
-                let all_translations = translations_hash
-                    .iter()
-                    .map(|translation| translation["text"].clone())
-                    .collect::<Vec<Value>>();
+                let content = self
+                    .microsoft_request(
+                        "translate",
+                        &url_params,
+                        &serde_json::json!([{ "Text": text }]),
+                    )
+                    .await?;
 
-                Ok(Value::Array(all_translations))
+                engine::microsoft_translations(&content, details)
             }
             Engine::MyMemory { email, return_all } => {
                 if text.len() > 500 {
@@ -355,7 +633,7 @@ impl Translator {
                     .post(self.base_url())
                     .form(&[
                         ("text", text),
-                        ("format", "plain"),
+                        ("format", if html { "html" } else { "plain" }),
                         ("lang", &format!("{}-{}", self.source, self.target)),
                         ("key", api_key),
                     ])
@@ -368,20 +646,125 @@ impl Translator {
         }
     }
 
-    /// translate directly from file
+    /// translate directly from file, markdown and html files are translated with their
+    /// text type unless other than [`TextType::Plain`] is set
     pub async fn translate_file(&self, path: &str) -> Result<Value, Error> {
-        self.translate(&std::fs::read_to_string(path)?).await
+        let text = std::fs::read_to_string(path)?;
+        let extension = std::path::Path::new(path)
+            .extension()
+            .and_then(|extension| extension.to_str());
+
+        let text_type = match extension {
+            _ if self.text_type != TextType::Plain => self.text_type,
+            Some("md" | "markdown") => TextType::Markdown,
+            Some("html" | "htm") => TextType::Html,
+            _ => TextType::Plain,
+        };
+
+        if text_type == self.text_type {
+            return self.translate(&text).await;
+        }
+
+        Translator {
+            text_type,
+            ..self.clone()
+        }
+        .translate(&text)
+        .await
     }
 
-    /// Translate batch of text
-    pub async fn translate_batch(&self, batch: &Vec<String>) -> Vec<Result<Value, Error>> {
-        let mut res = Vec::new();
+    /// Translate batch of text, at most [`Translator::max_concurrency`] requests are sent at
+    /// the same time. The translations are in the order of the batch
+    pub async fn translate_batch(&self, batch: &[String]) -> Vec<Result<Value, Error>> {
+        futures::stream::iter(batch.iter().map(|source_text| self.translate(source_text)))
+            .buffered(self.max_concurrency.max(1))
+            .collect()
+            .await
+    }
 
-        for source_text in batch {
-            res.push(self.translate(source_text).await);
-        }
+    /// Translate each text into a string concurrently, keeping the order. Engines that
+    /// return all the alternatives give the first one
+    pub(crate) async fn translate_strings<S: AsRef<str>>(
+        &self,
+        texts: &[S],
+    ) -> Vec<Result<String, Error>> {
+        futures::stream::iter(texts.iter().map(|text| async move {
+            let translation = self.translate(text.as_ref()).await?;
+            html::first_text(translation).ok_or(Error::TranslationNotFound)
+        }))
+        .buffered(self.max_concurrency.max(1))
+        .collect()
+        .await
+    }
+
+    /// Translate the same text into each one of the `targets` languages concurrently. The
+    /// requests of every target share [`Translator::limit`], or a limit of
+    /// [`Translator::max_concurrency`] requests when it is not set
+    ///
+    ///```rust,no_run
+    /// use deeptranslator::{Engine, Translator};
+    ///
+    /// #[tokio::main]
+    /// async fn main() {
+    ///     let translator = Translator::with_engine("en", "en", Engine::Google);
+    ///
+    ///     for (target, translation) in translator.translate_to_many("Hello", &["fr", "de"]).await {
+    ///         println!("{target}: {:?}", translation);
+    ///     }
+    /// }
+    ///```
+    pub async fn translate_to_many<S: AsRef<str>>(
+        &self,
+        text: &str,
+        targets: &[S],
+    ) -> BTreeMap<String, Result<Value, Error>> {
+        self.to_many(targets, |translator| async move { translator.translate(text).await })
+            .await
+    }
+
+    /// [`Translator::translate_to_many`] with what the engines tell about the translations,
+    /// see [`Translator::translate_detailed`]
+    pub async fn translate_detailed_to_many<S: AsRef<str>>(
+        &self,
+        text: &str,
+        targets: &[S],
+    ) -> BTreeMap<String, Result<Translation, Error>> {
+        self.to_many(targets, |translator| async move {
+            translator.translate_detailed(text).await
+        })
+        .await
+    }
 
-        res
+    /// Run `translate` with a clone of the translator for each target, the clones share
+    /// the limit of the requests
+    async fn to_many<S, T, F, Fut>(&self, targets: &[S], translate: F) -> BTreeMap<String, T>
+    where
+        S: AsRef<str>,
+        F: Fn(Translator) -> Fut,
+        Fut: std::future::Future<Output = T>,
+    {
+        let shared = Translator {
+            #[cfg(not(target_arch = "wasm32"))]
+            limit: self
+                .limit
+                .clone()
+                .or_else(|| Some(Arc::new(Semaphore::new(self.max_concurrency.max(1))))),
+            ..self.clone()
+        };
+
+        futures::stream::iter(targets.iter().map(|target| {
+            let translator = Translator {
+                target: target.as_ref().to_string(),
+                ..shared.clone()
+            };
+            let target = translator.target.clone();
+            let translation = translate(translator);
+
+            async move { (target, translation.await) }
+        }))
+        .buffer_unordered(self.max_concurrency.max(1))
+        .collect()
+        .await
     }
 }
 
@@ -391,8 +774,17 @@ impl Default for Translator {
             source: "auto".to_string(),
             target: "en".to_string(),
             engine: Engine::Google,
+            text_type: TextType::Plain,
+            markdown: MarkdownOptions::default(),
+            placeholders: Vec::new(),
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            glossary: Glossary::default(),
+            context: None,
+            memory: TranslationMemory::default(),
             #[cfg(not(target_arch = "wasm32"))]
             proxies: Vec::new(),
+            #[cfg(not(target_arch = "wasm32"))]
+            limit: None,
         }
     }
 }
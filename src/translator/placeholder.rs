@@ -0,0 +1,256 @@
+use std::{fmt, str::FromStr};
+
+#[derive(
+    Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, serde::Deserialize, serde::Serialize,
+)]
+/// Syntax of the placeholders that are protected from the engines, see
+/// [`crate::Translator::placeholders`]
+pub enum PlaceholderSyntax {
+    /// `{name}`, `{count, plural, ...}` and `{{count}}`
+    Icu,
+    /// `%s`, `%d`, `%1$d`, `%.2f` and `%%`
+    Printf,
+    /// `%(name)s`, `{name}`, `{0}` and `{}`
+    Python,
+    /// `%{name}`, `%<name>s` and `#{name}`
+    Ruby,
+    /// `:name`
+    Laravel,
+    /// `<b>`, `</b>` and `<br/>`
+    Html,
+}
+
+impl PlaceholderSyntax {
+    pub const ALL: [PlaceholderSyntax; 6] = [
+        PlaceholderSyntax::Icu,
+        PlaceholderSyntax::Printf,
+        PlaceholderSyntax::Python,
+        PlaceholderSyntax::Ruby,
+        PlaceholderSyntax::Laravel,
+        PlaceholderSyntax::Html,
+    ];
+
+    /// Length of the placeholder at the start of `text`, `previous` is the character
+    /// before it
+    pub(crate) fn find(&self, text: &str, previous: Option<char>) -> Option<usize> {
+        match self {
+            PlaceholderSyntax::Icu => {
+                if text.starts_with("{{") {
+                    return text.find("}}").map(|end| end + 2);
+                }
+
+                braces(text)
+            }
+            PlaceholderSyntax::Printf => {
+                let rest = text.strip_prefix('%')?;
+                if rest.starts_with('%') {
+                    return Some(2);
+                }
+
+                let mut len = 1 + position(rest);
+                len += format_spec(&text[len..], "-+0#");
+                let conversion = &text[len..];
+
+                let modifiers = ["hh", "ll", "h", "l", "L", "z", "j", "t", "q"];
+                if let Some(modifier) = modifiers.iter().find(|m| conversion.starts_with(**m)) {
+                    len += modifier.len();
+                }
+
+                text[len..]
+                    .starts_with(|c: char| "diouxXeEfFgGaAcspn@".contains(c))
+                    .then_some(len + 1)
+            }
+            PlaceholderSyntax::Python => {
+                if let Some(rest) = text.strip_prefix("%(") {
+                    let mut len = 2 + rest.find(')')? + 1;
+                    len += format_spec(&text[len..], "-+ 0#");
+
+                    return text[len..]
+                        .starts_with(|c: char| "diouxXeEfFgGcrsa".contains(c))
+                        .then_some(len + 1);
+                }
+
+                braces(text).filter(|len| !text[1..*len].starts_with(char::is_whitespace))
+            }
+            PlaceholderSyntax::Ruby => {
+                if text.starts_with("%{") || text.starts_with("#{") {
+                    return text.find('}').map(|end| end + 1);
+                }
+
+                let rest = text.strip_prefix("%<")?;
+                let mut len = 2 + rest.find('>')? + 1;
+                len += format_spec(&text[len..], "-+ 0#");
+
+                match text[len..].chars().next() {
+                    Some(c) if c.is_ascii_alphabetic() => Some(len + 1),
+                    _ => Some(len),
+                }
+            }
+            PlaceholderSyntax::Laravel => {
+                if previous.is_some_and(|c| c.is_alphanumeric() || c == ':') {
+                    return None;
+                }
+
+                let rest = text.strip_prefix(':')?;
+                if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                    return None;
+                }
+
+                Some(1 + rest.len() - rest.trim_start_matches(is_identifier).len())
+            }
+            PlaceholderSyntax::Html => {
+                let rest = text.strip_prefix('<')?;
+                if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '/') {
+                    return None;
+                }
+
+                let end = rest.find('>')?;
+                (!rest[..end].contains('<')).then_some(end + 2)
+            }
+        }
+    }
+}
+
+#[inline(always)]
+fn is_identifier(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Length of balanced braces at the start of `text`, with an identifier-like content
+fn braces(text: &str) -> Option<usize> {
+    if !text.starts_with('{') {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (index, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    let inner = &text[1..index];
+                    let is_placeholder = inner.is_empty()
+                        || inner.starts_with(|c: char| is_identifier(c) || c == '$');
+
+                    return is_placeholder.then_some(index + 1);
+                }
+            }
+            '\n' => return None,
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Length of a positional argument, `1$`
+fn position(text: &str) -> usize {
+    let digits = text.len() - text.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+
+    match text[digits..].starts_with('$') {
+        true if digits > 0 => digits + 1,
+        _ => 0,
+    }
+}
+
+/// Length of the flags, width and precision of a format specification
+fn format_spec(text: &str, flags: &str) -> usize {
+    let mut rest = text.trim_start_matches(|c: char| flags.contains(c));
+    rest = rest.trim_start_matches(|c: char| c.is_ascii_digit() || c == '*');
+    if let Some(precision) = rest.strip_prefix('.') {
+        rest = precision.trim_start_matches(|c: char| c.is_ascii_digit() || c == '*');
+    }
+
+    text.len() - rest.len()
+}
+
+impl FromStr for PlaceholderSyntax {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "icu" => Ok(PlaceholderSyntax::Icu),
+            "printf" => Ok(PlaceholderSyntax::Printf),
+            "python" => Ok(PlaceholderSyntax::Python),
+            "ruby" => Ok(PlaceholderSyntax::Ruby),
+            "laravel" => Ok(PlaceholderSyntax::Laravel),
+            "html" => Ok(PlaceholderSyntax::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for PlaceholderSyntax {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlaceholderSyntax::Icu => "icu",
+            PlaceholderSyntax::Printf => "printf",
+            PlaceholderSyntax::Python => "python",
+            PlaceholderSyntax::Ruby => "ruby",
+            PlaceholderSyntax::Laravel => "laravel",
+            PlaceholderSyntax::Html => "html",
+        }
+        .fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The placeholders of the syntax found in the text
+    fn placeholders(syntax: PlaceholderSyntax, text: &str) -> Vec<&str> {
+        let mut found = Vec::new();
+        let mut previous = None;
+        let mut rest = text;
+
+        while let Some(c) = rest.chars().next() {
+            let len = match syntax.find(rest, previous) {
+                Some(len) => {
+                    found.push(&rest[..len]);
+                    len
+                }
+                None => c.len_utf8(),
+            };
+            previous = rest[..len].chars().next_back();
+            rest = &rest[len..];
+        }
+
+        found
+    }
+
+    #[test]
+    fn syntaxes() {
+        use PlaceholderSyntax::*;
+
+        assert_eq!(
+            placeholders(
+                Icu,
+                "{name} has {count, plural, one {# file} other {# files}} {{x}}"
+            ),
+            [
+                "{name}",
+                "{count, plural, one {# file} other {# files}}",
+                "{{x}}"
+            ]
+        );
+        assert_eq!(
+            placeholders(Printf, "%s %1$d %.2f %-5lld 100%% %z"),
+            ["%s", "%1$d", "%.2f", "%-5lld", "%%"]
+        );
+        assert_eq!(
+            placeholders(Python, "%(name)s {0} {} {name} { not }"),
+            ["%(name)s", "{0}", "{}", "{name}"]
+        );
+        assert_eq!(
+            placeholders(Ruby, "%{name} %<count>d #{x}"),
+            ["%{name}", "%<count>d", "#{x}"]
+        );
+        assert_eq!(placeholders(Laravel, "Hi :name, at 10:30 :"), [":name"]);
+        assert_eq!(
+            placeholders(Html, "<b>bold</b><br/> 1 < 2"),
+            ["<b>", "</b>", "<br/>"]
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use super::{credential, BaseTranslator};
+use crate::config::DEEPL_API_KEY;
+use pyo3::{prelude::*, types::PyDict};
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct DeeplTranslator;
+
+#[pymethods]
+impl DeeplTranslator {
+    /// The api key is taken from `DEEPL_API_KEY` when it is not given
+    #[new]
+    #[pyo3(signature = (api_key = None, source = "de", target = "en", use_free_api = true, **_kwargs))]
+    fn new(
+        api_key: Option<String>,
+        source: &str,
+        target: &str,
+        use_free_api: bool,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let api_key = credential(api_key, DEEPL_API_KEY).ok_or_else(|| {
+            super::ApiKeyException::new_err(format!(
+                "Deepl needs an api key, pass it as `api_key` or set {DEEPL_API_KEY}"
+            ))
+        })?;
+        let engine = crate::Engine::Deepl {
+            api_key,
+            version: Default::default(),
+            use_free_api,
+        };
+
+        Ok((Self, BaseTranslator::new(source, target, engine, None)?))
+    }
+}
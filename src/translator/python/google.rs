@@ -0,0 +1,21 @@
+use super::BaseTranslator;
+use pyo3::{prelude::*, types::PyDict};
+use std::collections::HashMap;
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct GoogleTranslator;
+
+#[pymethods]
+impl GoogleTranslator {
+    #[new]
+    #[pyo3(signature = (source = "auto", target = "en", proxies = None, **_kwargs))]
+    fn new(
+        source: &str,
+        target: &str,
+        proxies: Option<HashMap<String, String>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let base = BaseTranslator::new(source, target, crate::Engine::Google, proxies)?;
+        Ok((Self, base))
+    }
+}
@@ -0,0 +1,49 @@
+use super::{credential, BaseTranslator};
+use crate::config::{LIBRE_API_KEY, LIBRE_URL};
+use pyo3::{prelude::*, types::PyDict};
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct LibreTranslator;
+
+#[pymethods]
+impl LibreTranslator {
+    /// The api key and the url are taken from `LIBRE_API_KEY` and `LIBRE_URL` when they are
+    /// not given. Without `use_free_api` the key is required, as libretranslate.com asks it
+    #[new]
+    #[pyo3(signature = (
+        api_key = None,
+        source = "en",
+        target = "es",
+        use_free_api = true,
+        custom_url = None,
+        **_kwargs
+    ))]
+    fn new(
+        api_key: Option<String>,
+        source: &str,
+        target: &str,
+        use_free_api: bool,
+        custom_url: Option<String>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let api_key = credential(api_key, LIBRE_API_KEY);
+        if !use_free_api && api_key.is_none() {
+            return Err(super::ApiKeyException::new_err(format!(
+                "Libre needs an api key without the free api, pass it as `api_key` or set \
+                {LIBRE_API_KEY}"
+            )));
+        }
+
+        let url = credential(custom_url, LIBRE_URL).unwrap_or_else(|| match use_free_api {
+            true => "https://libretranslate.de/".into(),
+            false => "https://libretranslate.com/".into(),
+        });
+        let engine = crate::Engine::Libre(crate::Libre {
+            api_key: api_key.unwrap_or_default(),
+            url,
+            alternatives: 1,
+        });
+
+        Ok((Self, BaseTranslator::new(source, target, engine, None)?))
+    }
+}
@@ -0,0 +1,22 @@
+use super::BaseTranslator;
+use pyo3::{prelude::*, types::PyDict};
+use std::collections::HashMap;
+
+/// Dictionary of words, `translate(word, return_all=True)` gives all the translations found
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct LingueeTranslator;
+
+#[pymethods]
+impl LingueeTranslator {
+    #[new]
+    #[pyo3(signature = (source = "en", target = "de", proxies = None, **_kwargs))]
+    fn new(
+        source: &str,
+        target: &str,
+        proxies: Option<HashMap<String, String>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let engine = crate::Engine::Linguee { return_all: false };
+        Ok((Self, BaseTranslator::new(source, target, engine, proxies)?))
+    }
+}
@@ -0,0 +1,48 @@
+use super::{credential, BaseTranslator};
+use crate::config::{MICROSOFT_TRANSLATOR_KEY, MICROSOFT_TRANSLATOR_REGION};
+use pyo3::{prelude::*, types::PyDict};
+use std::collections::HashMap;
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct MicrosoftTranslator;
+
+#[pymethods]
+impl MicrosoftTranslator {
+    /// The api key and the region are taken from `MICROSOFT_TRANSLATOR_KEY` and
+    /// `MICROSOFT_TRANSLATOR_REGION` when they are not given. Without languages the source
+    /// is detected and the target is english
+    #[new]
+    #[pyo3(signature = (
+        api_key = None,
+        region = None,
+        source = None,
+        target = None,
+        proxies = None,
+        **_kwargs
+    ))]
+    fn new(
+        api_key: Option<String>,
+        region: Option<String>,
+        source: Option<&str>,
+        target: Option<&str>,
+        proxies: Option<HashMap<String, String>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let api_key = credential(api_key, MICROSOFT_TRANSLATOR_KEY).ok_or_else(|| {
+            super::ApiKeyException::new_err(format!(
+                "Microsoft needs an api key, pass it as `api_key` or set \
+                {MICROSOFT_TRANSLATOR_KEY}"
+            ))
+        })?;
+        let region = credential(region, MICROSOFT_TRANSLATOR_REGION).unwrap_or_default();
+        let engine = crate::Engine::Microsoft(crate::Microsoft::new(&api_key, &region));
+
+        let base = BaseTranslator::new(
+            source.unwrap_or("auto"),
+            target.unwrap_or("en"),
+            engine,
+            proxies,
+        )?;
+        Ok((Self, base))
+    }
+}
@@ -0,0 +1,430 @@
+//! Python extension module, with the classes and the signatures of the `deep-translator`
+//! Python library. Every method has an `a` prefixed version that returns an awaitable
+//!
+//!```python
+//! from deep_translator import GoogleTranslator
+//!
+//! translator = GoogleTranslator(source="auto", target="german")
+//! print(translator.translate("keep it up, you are awesome"))
+//! print(await translator.atranslate_batch(["first text", "second text"]))
+//!```
+
+mod deepl;
+mod google;
+mod libre;
+mod linguee;
+mod microsoft;
+mod mymemory;
+mod papago;
+mod pons;
+mod qcri;
+mod yandex;
+
+pub use deepl::*;
+pub use google::*;
+pub use libre::*;
+pub use linguee::*;
+pub use microsoft::*;
+pub use mymemory::*;
+pub use papago::*;
+pub use pons::*;
+pub use qcri::*;
+pub use yandex::*;
+
+use crate::{detection, Engine, Error, Translation};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyRuntimeError},
+    prelude::*,
+    types::PyDict,
+    IntoPyObjectExt,
+};
+use std::collections::{BTreeMap, HashMap};
+
+create_exception!(deep_translator, BaseError, PyException);
+create_exception!(deep_translator, LanguageNotSupportedException, BaseError);
+create_exception!(deep_translator, NotValidLength, BaseError);
+create_exception!(deep_translator, RequestError, BaseError);
+create_exception!(deep_translator, TooManyRequests, BaseError);
+create_exception!(deep_translator, TranslationNotFound, BaseError);
+create_exception!(deep_translator, AuthorizationException, BaseError);
+create_exception!(deep_translator, ApiKeyException, BaseError);
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> Self {
+        let message = err.to_string();
+
+        match err {
+            Error::LanguageNotSupported(_) => LanguageNotSupportedException::new_err(message),
+            Error::NotValidLength { .. } => NotValidLength::new_err(message),
+            Error::TooManyRequests => TooManyRequests::new_err(message),
+            Error::Response(status) if status == 429 => TooManyRequests::new_err(message),
+            Error::Response(status) if status == 401 || status == 403 => {
+                AuthorizationException::new_err(message)
+            }
+            Error::Response(_) | Error::Reqwest(_) => RequestError::new_err(message),
+            Error::TranslationNotFound => TranslationNotFound::new_err(message),
+            Error::Config(_) | Error::MissingCredentials(_) => ApiKeyException::new_err(message),
+            _ => BaseError::new_err(message),
+        }
+    }
+}
+
+/// Run the future until it ends, without holding the GIL
+#[inline(always)]
+fn block_on<F: std::future::Future>(future: F) -> F::Output {
+    pyo3_async_runtimes::tokio::get_runtime().block_on(future)
+}
+
+/// Awaitable of the task. The futures of the translator are not `Send`, so the task runs
+/// in a blocking thread of the runtime
+fn awaitable<'py, T, F>(py: Python<'py>, task: F) -> PyResult<Bound<'py, PyAny>>
+where
+    T: for<'a> IntoPyObject<'a> + Send + 'static,
+    F: FnOnce() -> PyResult<T> + Send + 'static,
+{
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        tokio::task::spawn_blocking(task)
+            .await
+            .map_err(|err| PyRuntimeError::new_err(err.to_string()))?
+    })
+}
+
+/// Value of the argument, or else of the environment variable
+#[inline(always)]
+fn credential(value: Option<String>, var: &str) -> Option<String> {
+    value.or_else(|| std::env::var(var).ok().filter(|value| !value.is_empty()))
+}
+
+/// The value as Python objects, objects are dicts and arrays are lists
+fn json_to_py(py: Python<'_>, value: &serde_json::Value) -> PyResult<PyObject> {
+    use serde_json::Value;
+
+    match value {
+        Value::Null => Ok(py.None()),
+        Value::Bool(value) => value.into_py_any(py),
+        Value::Number(number) => match (number.as_i64(), number.as_u64()) {
+            (Some(number), _) => number.into_py_any(py),
+            (_, Some(number)) => number.into_py_any(py),
+            _ => number.as_f64().into_py_any(py),
+        },
+        Value::String(value) => value.into_py_any(py),
+        Value::Array(values) => values
+            .iter()
+            .map(|value| json_to_py(py, value))
+            .collect::<PyResult<Vec<_>>>()?
+            .into_py_any(py),
+        Value::Object(object) => {
+            let dict = PyDict::new(py);
+            for (key, value) in object {
+                dict.set_item(key, json_to_py(py, value)?)?;
+            }
+            dict.into_py_any(py)
+        }
+    }
+}
+
+/// The translation, or all its alternatives with `return_all`
+#[derive(IntoPyObject)]
+enum Text {
+    One(String),
+    All(Vec<String>),
+}
+
+impl Text {
+    #[inline(always)]
+    fn new(value: serde_json::Value, return_all: bool) -> Result<Self, Error> {
+        let mut texts = Translation::texts(&value);
+
+        match return_all {
+            true => Ok(Text::All(texts)),
+            false if texts.is_empty() => Err(Error::TranslationNotFound),
+            false => Ok(Text::One(texts.swap_remove(0))),
+        }
+    }
+}
+
+/// Base class of the translators, it has the methods shared by all the engines
+#[pyclass(subclass, module = "deep_translator")]
+pub struct BaseTranslator {
+    translator: crate::Translator,
+}
+
+impl BaseTranslator {
+    /// Translator of the engine, the languages can be names, e.g. `german`, or codes. The
+    /// proxies are the ones of `requests`, a dict of scheme to url
+    fn new(
+        source: &str,
+        target: &str,
+        engine: Engine,
+        proxies: Option<HashMap<String, String>>,
+    ) -> PyResult<Self> {
+        let mut translator = crate::Translator::with_engine(
+            &language_code(&engine, source),
+            &language_code(&engine, target),
+            engine,
+        );
+
+        translator.proxies = proxies
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(scheme, url)| match scheme.as_str() {
+                "http" => reqwest::Proxy::http(url),
+                "https" => reqwest::Proxy::https(url),
+                _ => reqwest::Proxy::all(url),
+            })
+            .collect::<Result<_, _>>()
+            .map_err(Error::from)?;
+        translator.check_languages()?;
+
+        Ok(Self { translator })
+    }
+
+    /// Translator with the engine option `return_all` set
+    fn with_return_all(&self, return_all: bool) -> crate::Translator {
+        let mut translator = self.translator.clone();
+        match &mut translator.engine {
+            Engine::Linguee { return_all: all } => *all = return_all,
+            Engine::MyMemory {
+                return_all: all, ..
+            } => *all = return_all,
+            _ => {}
+        }
+
+        translator
+    }
+}
+
+/// Code of the language, it can be written with its name or its code
+fn language_code(engine: &Engine, language: &str) -> String {
+    engine
+        .supported_languages()
+        .into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(language))
+        .map_or_else(|| language.to_string(), |(_, code)| code.to_string())
+}
+
+#[pymethods]
+impl BaseTranslator {
+    #[getter]
+    fn source(&self) -> &str {
+        &self.translator.source
+    }
+
+    #[setter]
+    fn set_source(&mut self, source: &str) -> PyResult<()> {
+        self.translator.source = language_code(&self.translator.engine, source);
+        Ok(self.translator.check_languages()?)
+    }
+
+    #[getter]
+    fn target(&self) -> &str {
+        &self.translator.target
+    }
+
+    #[setter]
+    fn set_target(&mut self, target: &str) -> PyResult<()> {
+        self.translator.target = language_code(&self.translator.engine, target);
+        Ok(self.translator.check_languages()?)
+    }
+
+    /// Names of the languages of the engine, or a dict of name to code with `as_dict`
+    #[pyo3(signature = (as_dict = false, **_kwargs))]
+    fn get_supported_languages<'py>(
+        &self,
+        py: Python<'py>,
+        as_dict: bool,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let languages: BTreeMap<_, _> = self
+            .translator
+            .engine
+            .supported_languages()
+            .into_iter()
+            .map(|(name, code)| (name.to_lowercase(), code.to_string()))
+            .collect();
+
+        match as_dict {
+            true => languages.into_pyobject(py).map(Bound::into_any),
+            false => languages.into_keys().collect::<Vec<_>>().into_pyobject(py),
+        }
+    }
+
+    /// Whether the engine supports the language, by name or by code
+    #[pyo3(signature = (language, **_kwargs))]
+    fn is_language_supported(&self, language: &str, _kwargs: Option<&Bound<'_, PyDict>>) -> bool {
+        let engine = &self.translator.engine;
+        engine.supports_language(&language_code(engine, language))
+    }
+
+    /// Translate the text, Linguee, MyMemory and Pons give all the translations found with
+    /// `return_all`
+    #[pyo3(signature = (text, return_all = false, **_kwargs))]
+    fn translate(
+        &self,
+        py: Python<'_>,
+        text: String,
+        return_all: bool,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Text> {
+        let translator = self.with_return_all(return_all);
+        py.allow_threads(|| translate(translator, text, return_all))
+    }
+
+    #[pyo3(signature = (text, return_all = false, **_kwargs))]
+    fn atranslate<'py>(
+        &self,
+        py: Python<'py>,
+        text: String,
+        return_all: bool,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let translator = self.with_return_all(return_all);
+        awaitable(py, move || translate(translator, text, return_all))
+    }
+
+    /// Translate the texts concurrently, the first error is raised
+    #[pyo3(signature = (batch, **_kwargs))]
+    fn translate_batch(
+        &self,
+        py: Python<'_>,
+        batch: Vec<String>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<String>> {
+        let translator = self.translator.clone();
+        py.allow_threads(|| translate_batch(translator, batch))
+    }
+
+    #[pyo3(signature = (batch, **_kwargs))]
+    fn atranslate_batch<'py>(
+        &self,
+        py: Python<'py>,
+        batch: Vec<String>,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let translator = self.translator.clone();
+        awaitable(py, move || translate_batch(translator, batch))
+    }
+
+    /// Translate the content of the file, markdown and html files keep their markup
+    #[pyo3(signature = (path, **_kwargs))]
+    fn translate_file(
+        &self,
+        py: Python<'_>,
+        path: String,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<String> {
+        let translator = self.translator.clone();
+        py.allow_threads(|| translate_file(translator, path))
+    }
+
+    #[pyo3(signature = (path, **_kwargs))]
+    fn atranslate_file<'py>(
+        &self,
+        py: Python<'py>,
+        path: String,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let translator = self.translator.clone();
+        awaitable(py, move || translate_file(translator, path))
+    }
+
+    fn __repr__(&self) -> String {
+        format!(
+            "{}Translator(source={:?}, target={:?})",
+            self.translator.engine.name(),
+            self.translator.source,
+            self.translator.target
+        )
+    }
+}
+
+fn translate(translator: crate::Translator, text: String, return_all: bool) -> PyResult<Text> {
+    Ok(Text::new(
+        block_on(translator.translate(&text))?,
+        return_all,
+    )?)
+}
+
+fn translate_batch(translator: crate::Translator, batch: Vec<String>) -> PyResult<Vec<String>> {
+    block_on(translator.translate_strings(&batch))
+        .into_iter()
+        .map(|translation| Ok(translation?))
+        .collect()
+}
+
+fn translate_file(translator: crate::Translator, path: String) -> PyResult<String> {
+    let translation = block_on(translator.translate_file(&path))?;
+    Ok(super::html::first_text(translation).ok_or(Error::TranslationNotFound)?)
+}
+
+/// Language of the text with detectlanguage.com, the code or all the details with
+/// `detailed`
+#[pyfunction]
+#[pyo3(signature = (text, api_key = None, detailed = false))]
+fn single_detection(
+    py: Python<'_>,
+    text: String,
+    api_key: Option<String>,
+    detailed: bool,
+) -> PyResult<PyObject> {
+    let api_key = credential(api_key, crate::config::DETECTLANGUAGE_API_KEY).unwrap_or_default();
+    let language = py.allow_threads(|| block_on(detection::single(&text, &api_key, detailed)))?;
+
+    json_to_py(py, &language)
+}
+
+/// Languages of the texts with detectlanguage.com, in the same order
+#[pyfunction]
+#[pyo3(signature = (text_list, api_key = None, detailed = false))]
+fn batch_detection(
+    py: Python<'_>,
+    text_list: Vec<String>,
+    api_key: Option<String>,
+    detailed: bool,
+) -> PyResult<Vec<PyObject>> {
+    let api_key = credential(api_key, crate::config::DETECTLANGUAGE_API_KEY).unwrap_or_default();
+    let languages =
+        py.allow_threads(|| block_on(detection::batch(text_list, &api_key, detailed)))?;
+
+    languages
+        .iter()
+        .map(|language| json_to_py(py, language))
+        .collect()
+}
+
+#[pymodule]
+fn deep_translator(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    let py = m.py();
+
+    m.add_class::<BaseTranslator>()?;
+    m.add_class::<DeeplTranslator>()?;
+    m.add_class::<GoogleTranslator>()?;
+    m.add_class::<LibreTranslator>()?;
+    m.add_class::<LingueeTranslator>()?;
+    m.add_class::<MicrosoftTranslator>()?;
+    m.add_class::<MyMemoryTranslator>()?;
+    m.add_class::<PapagoTranslator>()?;
+    m.add_class::<PonsTranslator>()?;
+    m.add_class::<QcriTranslator>()?;
+    m.add_class::<YandexTranslator>()?;
+    m.add_function(wrap_pyfunction!(single_detection, m)?)?;
+    m.add_function(wrap_pyfunction!(batch_detection, m)?)?;
+
+    m.add("BaseError", py.get_type::<BaseError>())?;
+    m.add(
+        "LanguageNotSupportedException",
+        py.get_type::<LanguageNotSupportedException>(),
+    )?;
+    m.add("NotValidLength", py.get_type::<NotValidLength>())?;
+    m.add("RequestError", py.get_type::<RequestError>())?;
+    m.add("TooManyRequests", py.get_type::<TooManyRequests>())?;
+    m.add("TranslationNotFound", py.get_type::<TranslationNotFound>())?;
+    m.add(
+        "AuthorizationException",
+        py.get_type::<AuthorizationException>(),
+    )?;
+    m.add("ApiKeyException", py.get_type::<ApiKeyException>())?;
+
+    Ok(())
+}
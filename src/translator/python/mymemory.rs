@@ -0,0 +1,30 @@
+use super::{credential, BaseTranslator};
+use crate::config::MYMEMORY_EMAIL;
+use pyo3::{prelude::*, types::PyDict};
+use std::collections::HashMap;
+
+/// `translate(text, return_all=True)` gives all the matches of the translation memory
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct MyMemoryTranslator;
+
+#[pymethods]
+impl MyMemoryTranslator {
+    /// The email raises the daily limit, it is taken from `MYMEMORY_EMAIL` when it is not
+    /// given
+    #[new]
+    #[pyo3(signature = (source = "auto", target = "en", proxies = None, email = None, **_kwargs))]
+    fn new(
+        source: &str,
+        target: &str,
+        proxies: Option<HashMap<String, String>>,
+        email: Option<String>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let engine = crate::Engine::MyMemory {
+            email: credential(email, MYMEMORY_EMAIL).unwrap_or_default(),
+            return_all: false,
+        };
+
+        Ok((Self, BaseTranslator::new(source, target, engine, proxies)?))
+    }
+}
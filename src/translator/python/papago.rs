@@ -0,0 +1,37 @@
+use super::{credential, BaseTranslator};
+use crate::config::{PAPAGO_CLIENT_ID, PAPAGO_SECRET_KEY};
+use pyo3::{prelude::*, types::PyDict};
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct PapagoTranslator;
+
+#[pymethods]
+impl PapagoTranslator {
+    /// The credentials are taken from `PAPAGO_CLIENT_ID` and `PAPAGO_SECRET_KEY` when they
+    /// are not given
+    #[new]
+    #[pyo3(signature = (client_id = None, secret_key = None, source = "auto", target = "en", **_kwargs))]
+    fn new(
+        client_id: Option<String>,
+        secret_key: Option<String>,
+        source: &str,
+        target: &str,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let (Some(client_id), Some(secret_key)) = (
+            credential(client_id, PAPAGO_CLIENT_ID),
+            credential(secret_key, PAPAGO_SECRET_KEY),
+        ) else {
+            return Err(super::ApiKeyException::new_err(format!(
+                "Papago needs `client_id` and `secret_key`, pass them or set {PAPAGO_CLIENT_ID} \
+                and {PAPAGO_SECRET_KEY}"
+            )));
+        };
+        let engine = crate::Engine::Papago {
+            client_id,
+            secret_key,
+        };
+
+        Ok((Self, BaseTranslator::new(source, target, engine, None)?))
+    }
+}
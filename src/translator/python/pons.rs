@@ -0,0 +1,22 @@
+use super::BaseTranslator;
+use pyo3::{prelude::*, types::PyDict};
+use std::collections::HashMap;
+
+/// Dictionary of words, `translate(word, return_all=True)` gives all the translations found
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct PonsTranslator;
+
+#[pymethods]
+impl PonsTranslator {
+    #[new]
+    #[pyo3(signature = (source, target = "english", proxies = None, **_kwargs))]
+    fn new(
+        source: &str,
+        target: &str,
+        proxies: Option<HashMap<String, String>>,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let base = BaseTranslator::new(source, target, crate::Engine::Pons, proxies)?;
+        Ok((Self, base))
+    }
+}
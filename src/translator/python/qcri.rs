@@ -0,0 +1,97 @@
+use super::{awaitable, block_on, credential, BaseTranslator, Text};
+use crate::config::QCRI_API_KEY;
+use pyo3::{prelude::*, types::PyDict};
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct QcriTranslator;
+
+#[pymethods]
+impl QcriTranslator {
+    /// The api key is taken from `QCRI_API_KEY` when it is not given. The domain is given
+    /// to the translate methods, see `get_domains`
+    #[new]
+    #[pyo3(signature = (api_key = None, source = "en", target = "en", **_kwargs))]
+    fn new(
+        api_key: Option<String>,
+        source: &str,
+        target: &str,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let api_key = credential(api_key, QCRI_API_KEY).ok_or_else(|| {
+            super::ApiKeyException::new_err(format!(
+                "Qcri needs an api key, pass it as `api_key` or set {QCRI_API_KEY}"
+            ))
+        })?;
+        let engine = crate::Engine::Qcri(crate::Qcri {
+            api_key,
+            domain: "general".into(),
+        });
+
+        Ok((Self, BaseTranslator::new(source, target, engine, None)?))
+    }
+
+    /// Translate the text in the domain
+    #[pyo3(signature = (text, domain = "general", **_kwargs))]
+    fn translate(
+        self_: PyRef<'_, Self>,
+        py: Python<'_>,
+        text: String,
+        domain: &str,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Text> {
+        let translator = with_domain(self_.as_ref(), domain);
+        py.allow_threads(|| super::translate(translator, text, false))
+    }
+
+    #[pyo3(signature = (text, domain = "general", **_kwargs))]
+    fn atranslate<'py>(
+        self_: PyRef<'py, Self>,
+        py: Python<'py>,
+        text: String,
+        domain: &str,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let translator = with_domain(self_.as_ref(), domain);
+        awaitable(py, move || super::translate(translator, text, false))
+    }
+
+    /// Translate the texts in the domain concurrently, the first error is raised
+    #[pyo3(signature = (batch, domain = "general", **_kwargs))]
+    fn translate_batch(
+        self_: PyRef<'_, Self>,
+        py: Python<'_>,
+        batch: Vec<String>,
+        domain: &str,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<Vec<String>> {
+        let translator = with_domain(self_.as_ref(), domain);
+        py.allow_threads(|| super::translate_batch(translator, batch))
+    }
+
+    #[pyo3(signature = (batch, domain = "general", **_kwargs))]
+    fn atranslate_batch<'py>(
+        self_: PyRef<'py, Self>,
+        py: Python<'py>,
+        batch: Vec<String>,
+        domain: &str,
+        _kwargs: Option<&Bound<'py, PyDict>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let translator = with_domain(self_.as_ref(), domain);
+        awaitable(py, move || super::translate_batch(translator, batch))
+    }
+
+    /// Domains of the translations, as the api returns them
+    fn get_domains(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(py.allow_threads(|| block_on(crate::Qcri::domains()))?)
+    }
+}
+
+/// Translator of the base with the domain, the Python library gives it to `translate`
+fn with_domain(base: &BaseTranslator, domain: &str) -> crate::Translator {
+    let mut translator = base.translator.clone();
+    if let crate::Engine::Qcri(qcri) = &mut translator.engine {
+        qcri.domain = domain.to_string();
+    }
+
+    translator
+}
@@ -0,0 +1,28 @@
+use super::{credential, BaseTranslator};
+use crate::config::YANDEX_API_KEY;
+use pyo3::{prelude::*, types::PyDict};
+
+#[pyclass(extends = BaseTranslator, module = "deep_translator")]
+pub struct YandexTranslator;
+
+#[pymethods]
+impl YandexTranslator {
+    /// The api key is taken from `YANDEX_API_KEY` when it is not given
+    #[new]
+    #[pyo3(signature = (api_key = None, source = "en", target = "de", **_kwargs))]
+    fn new(
+        api_key: Option<String>,
+        source: &str,
+        target: &str,
+        _kwargs: Option<&Bound<'_, PyDict>>,
+    ) -> PyResult<(Self, BaseTranslator)> {
+        let api_key = credential(api_key, YANDEX_API_KEY).ok_or_else(|| {
+            super::ApiKeyException::new_err(format!(
+                "Yandex needs an api key, pass it as `api_key` or set {YANDEX_API_KEY}"
+            ))
+        })?;
+
+        let engine = crate::Engine::Yandex { api_key };
+        Ok((Self, BaseTranslator::new(source, target, engine, None)?))
+    }
+}
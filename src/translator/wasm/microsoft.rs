@@ -85,7 +85,7 @@ impl From<Microsoft> for crate::Translator {
         let api_key = api_key.as_string().unwrap();
         let region = region.as_string().unwrap();
 
-        let engine = crate::Engine::Microsoft { api_key, region };
+        let engine = crate::Engine::Microsoft(crate::Microsoft::new(&api_key, &region));
 
         crate::Translator::with_engine(&source, &target, engine)
     }